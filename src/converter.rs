@@ -2,21 +2,200 @@
 
 use anyhow::Result;
 use rustdoc_types::{Crate, Id, Item, ItemEnum, Visibility};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+/// Options controlling how rustdoc data is rendered to markdown.
+#[derive(Debug, Clone, Default)]
+pub struct MarkdownOptions {
+    /// Whether to include private items in the output, whatever `visibility` is
+    pub include_private: bool,
+    /// The least visible items to include, e.g. [`VisibilityLevel::Crate`] for
+    /// `pub(crate)` items but not private ones. Whenever items less visible than `pub`
+    /// are included, each item, field, and inherent impl item shows its visibility
+    pub visibility: VisibilityLevel,
+    /// Prepend YAML frontmatter (title, slug, sidebar position) to each file
+    pub frontmatter: bool,
+    /// Crates documented alongside this one, by crate name, with their directories
+    /// relative to the output root (`serde`, or `deps/serde` when nested). Intra-doc
+    /// links into these crates become relative links; other external links point at
+    /// docs.rs.
+    pub documented_crates: BTreeMap<String, String>,
+    /// Items of the documented crates whose heading has their kind appended, as
+    /// `(path, kind)` pairs like `("serde::Serialize", "trait")` (see
+    /// [`disambiguated_items`]), so that links into them land on those headings
+    pub disambiguated_items: BTreeSet<(String, String)>,
+    /// This crate's directory relative to the output root, which cross-crate links
+    /// are made relative to; `None` means a top-level directory
+    pub crate_dir: Option<String>,
+    /// Glob patterns (`*` and `?` wildcards) matched against full item paths;
+    /// matching items, and everything inside matching modules, are skipped
+    pub exclude: Vec<String>,
+    /// Base URL for `[src]` links on item headings (e.g. a GitHub `blob/main` URL).
+    /// Only items whose source path is relative, i.e. inside the workspace, get one.
+    pub src_base: Option<String>,
+    /// Render the whole crate into one file, `{crate}.md`, with modules as sections
+    /// and intra-crate links pointing at in-document anchors
+    pub single_file: bool,
+    /// Only give modules up to this many levels below the crate root a file of their
+    /// own; deeper modules become sections of their nearest ancestor's file, and `0`
+    /// puts the whole crate in `index.md`
+    pub max_depth: Option<usize>,
+    /// Write links between markdown files as Obsidian wikilinks,
+    /// `[[path#Heading|text]]`, instead of `[text](path.md#anchor)`
+    pub wikilinks: bool,
+    /// URL or path the output directory is served from, e.g. `/docs/api`; links
+    /// between markdown files start with it instead of being relative to the linking file
+    pub base_url: Option<String>,
+    /// Only note `#[stable(since = "...")]` versions newer than this one, e.g. the
+    /// minimum supported Rust version; `None` notes every stable item
+    pub since: Option<String>,
+    /// How items are ordered within a module's file and its contents listing
+    pub order: ItemOrder,
+    /// Wrap each impl block in a `<details>` element whose summary is the impl's
+    /// signature, so readers of HTML-capable renderers can expand them on demand
+    pub collapsible: bool,
+    /// Keep the `# ` lines that rustdoc hides in Rust examples, so the examples can be
+    /// copied and run as they are
+    pub keep_hidden_lines: bool,
+    /// Repeat the `# Returns`, `# Errors`, and `# Panics` sections of a function's docs
+    /// as `**Returns:**` notes and the like under its signature
+    pub extract_sections: bool,
+}
+
+/// Order of the items within a module.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ItemOrder {
+    /// By name
+    #[default]
+    Alpha,
+    /// As declared in the source, by file and line; items without a span come last
+    Source,
+}
+
+/// How widely an item is visible, from `pub` to private to its module; ordered from
+/// most to least visible.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum VisibilityLevel {
+    /// `pub`
+    #[default]
+    Public,
+    /// `pub(crate)`; rustdoc records private items at the crate root as this too
+    Crate,
+    /// `pub(super)` or `pub(in path)`
+    Restricted,
+    /// Declared without `pub`
+    Private,
+}
+
+impl MarkdownOptions {
+    /// Whether items less visible than `pub` are included.
+    fn includes_non_public(&self) -> bool {
+        self.include_private || self.visibility > VisibilityLevel::Public
+    }
+
+    /// Whether `item`, declared in `module`, is visible enough to include.
+    fn includes(&self, item: &Item, module: &[String]) -> bool {
+        self.include_private || visibility_level(item, module) <= self.visibility
+    }
+}
 
 /// Represents the multi-file markdown output
 pub struct MarkdownOutput {
     /// Crate name
     pub crate_name: String,
-    /// Map of relative file path -> content
+    /// Crate version, if rustdoc recorded one
+    pub crate_version: Option<String>,
+    /// First sentence of the crate root's docs
+    pub crate_summary: Option<String>,
+    /// Map of relative file path -> content, when the content is kept
     pub files: HashMap<String, String>,
+    /// Map of relative file path -> length of its content in bytes
+    pub file_sizes: HashMap<String, usize>,
+    /// Map of module file path -> number of items of each kind it documents
+    pub item_counts: HashMap<String, BTreeMap<&'static str, usize>>,
+    /// Every documented item and where it is rendered, sorted by path
+    pub search_index: Vec<SearchEntry>,
+    /// Items whose heading has their kind appended, as [`disambiguated_items`] returns
+    pub disambiguated_items: BTreeSet<(String, String)>,
+}
+
+/// Where one item is documented, for client-side search over the markdown.
+#[derive(Debug, Clone)]
+pub struct SearchEntry {
+    /// Fully-qualified item path, e.g. `my_crate::io::Reader`
+    pub path: String,
+    /// Item kind, as used in `item_counts`
+    pub kind: &'static str,
+    /// First sentence of the item's docs
+    pub summary: Option<String>,
+    /// File the item is documented in, relative to the crate directory
+    pub file: String,
+    /// Heading anchor within `file`, or `None` when the item is the file itself
+    pub anchor: Option<String>,
+    /// Alternative names from `#[doc(alias)]`
+    pub aliases: Vec<String>,
+}
+
+impl MarkdownOutput {
+    /// Files in reading order: crate index, root module, then other modules depth-first.
+    pub fn files_in_order(&self) -> Vec<(&str, &str)> {
+        let mut files: Vec<_> = self
+            .files
+            .iter()
+            .map(|(path, content)| (path.as_str(), content.as_str()))
+            .collect();
+
+        files.sort_by(|(a, _), (b, _)| {
+            file_order_key(a, &self.crate_name).cmp(&file_order_key(b, &self.crate_name))
+        });
+
+        files
+    }
+}
+
+/// Sort key placing the crate index first, then the root module, then modules by path.
+pub(crate) fn file_order_key<'a>(path: &'a str, crate_name: &str) -> (u8, Vec<&'a str>) {
+    let rank = if path == "index.md" {
+        0
+    } else if path.strip_suffix(".md") == Some(crate_name) {
+        1
+    } else {
+        2
+    };
+    (rank, path.trim_end_matches(".md").split('/').collect())
 }
 
 /// Convert a rustdoc Crate to multi-file markdown format.
 pub fn convert_to_markdown_multifile(
     crate_data: &Crate,
-    include_private: bool,
+    options: &MarkdownOptions,
 ) -> Result<MarkdownOutput> {
+    let mut files = HashMap::new();
+    let mut output = convert_to_markdown_streaming(crate_data, options, |path, content| {
+        files.insert(path.to_string(), content);
+        Ok(())
+    })?;
+    output.files = files;
+    Ok(output)
+}
+
+/// Convert a rustdoc Crate to multi-file markdown, handing each file to `emit` as soon
+/// as it is generated instead of keeping them all in memory.
+///
+/// `emit` receives the path relative to the crate directory and the finished content
+/// (frontmatter included), in the order of [`MarkdownOutput::files_in_order`]. The
+/// returned output has everything except `files`, which is left empty; `file_sizes`
+/// still lists every file emitted. Modules inlined
+/// into another file (see [`MarkdownOptions::max_depth`] and
+/// [`MarkdownOptions::single_file`]) are held until that file is complete.
+pub fn convert_to_markdown_streaming<F>(
+    crate_data: &Crate,
+    options: &MarkdownOptions,
+    mut emit: F,
+) -> Result<MarkdownOutput>
+where
+    F: FnMut(&str, String) -> Result<()>,
+{
     let root_item = crate_data
         .index
         .get(&crate_data.root)
@@ -28,946 +207,4164 @@ pub fn convert_to_markdown_multifile(
     let item_paths = build_path_map(crate_data);
 
     // Group items by module
-    let modules = group_by_module(crate_data, &item_paths, include_private);
+    let modules = group_by_module(crate_data, &item_paths, options);
+    let ambiguous_items = ambiguous_items(&modules);
+    let disambiguated_items = disambiguated(crate_data, &item_paths, &ambiguous_items);
 
-    let mut files = HashMap::new();
+    let mut module_files: Vec<(&String, String)> = modules
+        .keys()
+        .map(|module_name| (module_name, module_file_path(module_name, crate_name)))
+        .collect();
+    module_files.sort_by(|(_, a), (_, b)| {
+        file_order_key(a, crate_name).cmp(&file_order_key(b, crate_name))
+    });
+
+    let index_file = index_file_name(crate_name, options);
+    let mut frontmatter = if options.frontmatter {
+        let paths: Vec<String> = std::iter::once(index_file.clone())
+            .chain(
+                module_files
+                    .iter()
+                    .filter(|(module_name, _)| {
+                        !module_placement(&module_components(module_name), crate_name, options)
+                            .inlined
+                    })
+                    .map(|(_, path)| path.clone()),
+            )
+            .collect();
+        frontmatter_by_path(&paths, crate_name, options)
+    } else {
+        HashMap::new()
+    };
+
+    let mut file_sizes = HashMap::new();
+    let mut emit_file = |path: String, mut content: String| -> Result<()> {
+        if options.wikilinks || options.base_url.is_some() {
+            // Wikilink paths are relative to the vault, which is the output root, and so
+            // are paths under the base URL
+            let crate_dir = options.crate_dir.as_deref().unwrap_or(crate_name);
+            let file_in_vault = if options.single_file {
+                crate_dir.to_string()
+            } else {
+                format!("{}/{}", crate_dir, path)
+            };
+            let dir = file_in_vault.rsplit_once('/').map_or("", |(dir, _)| dir);
+            if options.wikilinks {
+                content = wikilinks(&content, dir);
+            }
+            if let Some(base_url) = &options.base_url {
+                content = base_url_links(&content, dir, base_url);
+            }
+        }
+        if let Some(frontmatter) = frontmatter.remove(&path) {
+            content.insert_str(0, &frontmatter);
+        }
+        file_sizes.insert(path.clone(), content.len());
+        emit(&path, content)
+    };
+
+    let mut item_counts: HashMap<String, BTreeMap<&'static str, usize>> = HashMap::new();
+    let mut search_index = Vec::new();
 
     // Generate index.md with crate overview and module list
-    let index_content = generate_crate_index(crate_name, root_item, &modules);
-    files.insert("index.md".to_string(), index_content);
+    let index_ctx = RenderContext {
+        crate_data,
+        item_paths: &item_paths,
+        ambiguous_items: &ambiguous_items,
+        legacy: false,
+        options,
+        crate_name,
+        current_file: index_file.clone(),
+    };
+    let index_content = generate_crate_index(root_item, &modules, &index_ctx);
+
+    // The file being assembled: inlined modules come right after their host in
+    // reading order, so a file is complete once the next module starts a new one
+    let mut current = HostFile::new(index_file, index_content, 0);
 
     // Generate one file per module
-    for (module_name, items) in &modules {
-        let module_filename = module_name
-            .strip_prefix(&format!("{}::", crate_name))
-            .unwrap_or(module_name)
-            .replace("::", "/");
+    for (module_name, file_path) in module_files {
+        let items = &modules[module_name];
+        let components = module_components(module_name);
+        let placement = module_placement(&components, crate_name, options);
+        let ctx = RenderContext {
+            crate_data,
+            item_paths: &item_paths,
+            ambiguous_items: &ambiguous_items,
+            legacy: false,
+            options,
+            crate_name,
+            current_file: placement.file.clone(),
+        };
+        let module_content = generate_module_file(module_name, items, &ctx);
+        let level = components.len() + 1;
 
-        let file_path = format!("{}.md", module_filename);
-        let module_content =
-            generate_module_file(module_name, items, crate_data, &item_paths, crate_name);
-        files.insert(file_path, module_content);
+        if placement.inlined {
+            current.inline(&module_content, level);
+        } else {
+            let finished = std::mem::replace(
+                &mut current,
+                HostFile::new(file_path, module_content, level),
+            );
+            emit_file(finished.path, finished.content)?;
+        }
+
+        let counts = item_counts.entry(placement.file.clone()).or_default();
+        for (id, item) in items {
+            if let Some(kind) = item_kind_name(item) {
+                *counts.entry(kind).or_insert(0) += 1;
+            }
+            search_index.extend(search_entry(id, item, &ctx));
+        }
     }
+    emit_file(current.path, current.content)?;
+    search_index.sort_by(|a, b| (&a.path, a.kind).cmp(&(&b.path, b.kind)));
 
     Ok(MarkdownOutput {
         crate_name: crate_name.to_string(),
-        files,
+        crate_version: crate_data.crate_version.clone(),
+        crate_summary: root_item.docs.as_deref().and_then(first_sentence),
+        files: HashMap::new(),
+        file_sizes,
+        item_counts,
+        search_index,
+        disambiguated_items,
     })
 }
 
-/// Convert a rustdoc Crate to markdown format (legacy single-file).
-pub fn convert_to_markdown(crate_data: &Crate, include_private: bool) -> Result<String> {
-    let mut output = String::new();
+/// Search index entry for an item rendered into `ctx.current_file`.
+///
+/// Modules point at their own file rather than at their stub in the parent, or at
+/// their section when they are inlined into another file.
+fn search_entry(id: &Id, item: &Item, ctx: &RenderContext) -> Option<SearchEntry> {
+    let kind = item_kind_name(item)?;
+    let path = ctx.item_paths.get(id)?;
+    let full_name = path.join("::");
 
-    let root_item = crate_data
-        .index
-        .get(&crate_data.root)
-        .ok_or_else(|| anyhow::anyhow!("Root item not found in index"))?;
+    let (file, anchor) = match &item.inner {
+        ItemEnum::Module(_) => {
+            let placement = module_placement(&path[1..], ctx.crate_name, ctx.options);
+            let anchor = placement
+                .inlined
+                .then(|| module_section_anchor(&path[1..], ctx.crate_name));
+            (placement.file, anchor)
+        }
+        _ => (ctx.current_file.clone(), Some(ctx.item_anchor(item, path))),
+    };
+
+    Some(SearchEntry {
+        path: full_name,
+        kind,
+        summary: item.docs.as_deref().and_then(first_sentence),
+        file,
+        anchor,
+        aliases: item_aliases(item),
+    })
+}
 
-    let crate_name = root_item.name.as_deref().unwrap_or("unknown");
-    output.push_str(&format!("# {}\n\n", crate_name));
+/// The first sentence of a doc comment's opening paragraph, on one line.
+///
+/// Headings before it, like a crate's `# Title`, are skipped.
+pub(crate) fn first_sentence(docs: &str) -> Option<String> {
+    let is_heading = |line: &str| {
+        let rest = line.trim_start_matches('#');
+        rest.len() < line.len() && (rest.is_empty() || rest.starts_with(' '))
+    };
+    let paragraph: Vec<&str> = docs
+        .lines()
+        .map(str::trim)
+        .skip_while(|line| line.is_empty() || is_heading(line))
+        .take_while(|line| !line.is_empty())
+        .collect();
+    let paragraph = paragraph.join(" ");
 
-    if let Some(docs) = &root_item.docs {
-        output.push_str(&format!("{}\n\n", docs));
+    let sentence = match paragraph.find(". ") {
+        Some(end) => &paragraph[..=end],
+        None => paragraph.as_str(),
+    };
+    (!sentence.is_empty()).then(|| sentence.to_string())
+}
+
+/// An output file being assembled from a module and the modules inlined into it.
+struct HostFile {
+    path: String,
+    content: String,
+    /// Heading depth of the file's own module: 0 for the crate index, 1 for the root
+    level: usize,
+    /// Whether `content` has been normalized for appending sections
+    joined: bool,
+}
+
+impl HostFile {
+    fn new(path: String, content: String, level: usize) -> Self {
+        HostFile {
+            path,
+            content,
+            level,
+            joined: false,
+        }
     }
 
-    // Build a map of item_id -> full_path using the paths data
-    let item_paths = build_path_map(crate_data);
+    /// Append a deeper module's file as a section, its headings pushed down to match.
+    fn inline(&mut self, module_content: &str, level: usize) {
+        if !self.joined {
+            self.content = format!("{}\n\n", shift_headings(&self.content, 0).trim_end());
+            self.joined = true;
+        }
+        let shifted = shift_headings(module_content, level.saturating_sub(self.level));
+        self.content.push_str(shifted.trim_end());
+        self.content.push_str("\n\n");
+    }
+}
 
-    // Group items by module
-    let modules = group_by_module(crate_data, &item_paths, include_private);
+/// Name of the crate index: `index.md`, or `{crate}.md` in single-file output.
+fn index_file_name(crate_name: &str, options: &MarkdownOptions) -> String {
+    if options.single_file {
+        format!("{}.md", crate_name)
+    } else {
+        "index.md".to_string()
+    }
+}
 
-    // Generate hierarchical ToC
-    output.push_str("## Table of Contents\n\n");
-    output.push_str(&generate_toc(&modules, crate_name));
-    output.push_str("\n\n---\n\n");
+/// A module's path below the crate root, e.g. `["nested", "inner"]` for `my_crate::nested::inner`.
+fn module_components(module_name: &str) -> Vec<String> {
+    module_name.split("::").skip(1).map(String::from).collect()
+}
 
-    // Generate content organized by module
-    output.push_str(&generate_content(&modules, crate_data, &item_paths));
+/// Where a module is rendered in the output.
+struct ModulePlacement {
+    /// File holding the module's documentation, relative to the crate directory
+    file: String,
+    /// The module is a section of `file` rather than the whole of it
+    inlined: bool,
+}
 
-    Ok(output)
+/// Place a module, given its path below the crate root, according to `single_file`
+/// and `max_depth`.
+fn module_placement(
+    components: &[String],
+    crate_name: &str,
+    options: &MarkdownOptions,
+) -> ModulePlacement {
+    let own_file = |components: &[String]| module_own_file(components, crate_name);
+
+    match options.max_depth {
+        _ if options.single_file => ModulePlacement {
+            file: index_file_name(crate_name, options),
+            inlined: true,
+        },
+        Some(0) => ModulePlacement {
+            file: index_file_name(crate_name, options),
+            inlined: true,
+        },
+        Some(depth) if components.len() > depth => ModulePlacement {
+            file: own_file(&components[..depth]),
+            inlined: true,
+        },
+        _ => ModulePlacement {
+            file: own_file(components),
+            inlined: false,
+        },
+    }
 }
 
-fn build_path_map(crate_data: &Crate) -> HashMap<Id, Vec<String>> {
-    crate_data
-        .paths
-        .iter()
-        .map(|(id, summary)| (*id, summary.path.clone()))
-        .collect()
+/// Anchor of an inlined module's section heading.
+fn module_section_anchor(components: &[String], crate_name: &str) -> String {
+    heading_anchor(&module_section_heading(components, crate_name))
 }
 
-fn group_by_module(
-    crate_data: &Crate,
-    item_paths: &HashMap<Id, Vec<String>>,
-    include_private: bool,
-) -> HashMap<String, Vec<(Id, Item)>> {
-    let mut modules: HashMap<String, Vec<(Id, Item)>> = HashMap::new();
+/// Heading of an inlined module's section.
+fn module_section_heading(components: &[String], crate_name: &str) -> String {
+    let display_name = if components.is_empty() {
+        crate_name.to_string()
+    } else {
+        components.join("::")
+    };
+    format!("Module: {}", display_name)
+}
 
-    for (id, item) in &crate_data.index {
-        if id == &crate_data.root {
-            continue;
+/// Demote every ATX heading outside code blocks by `levels`, stopping at `######`.
+fn shift_headings(markdown: &str, levels: usize) -> String {
+    let mut output = String::with_capacity(markdown.len() + levels * 16);
+    let mut fence: Option<&str> = None;
+
+    for line in markdown.lines() {
+        let trimmed = line.trim_start();
+        if let Some(marker) = ["```", "~~~"].into_iter().find(|m| trimmed.starts_with(m)) {
+            match fence {
+                None => fence = Some(marker),
+                Some(open) if open == marker => fence = None,
+                Some(_) => {}
+            }
         }
 
-        if !include_private && !is_public(item) {
-            continue;
+        let hashes = line.chars().take_while(|&c| c == '#').count();
+        if fence.is_none() && (1..=6).contains(&hashes) && line[hashes..].starts_with(' ') {
+            output.push_str(&"#".repeat((hashes + levels).min(6)));
+            output.push_str(&line[hashes..]);
+        } else {
+            output.push_str(line);
         }
+        output.push('\n');
+    }
 
-        // Skip if we can't format this item type
-        if !can_format_item(item) {
-            continue;
-        }
+    output
+}
 
-        // Get the module path (all elements except the last one)
-        let module_path = if let Some(path) = item_paths.get(id) {
-            if path.len() > 1 {
-                path[..path.len() - 1].join("::")
-            } else {
-                continue; // Skip root-level items without module
-            }
+/// YAML frontmatter for static site generators, for each of `paths`.
+///
+/// Sidebar positions follow alphabetical order within each directory, with the
+/// crate index and root module first. Slugs are the file's path below the output
+/// root, without the extension.
+fn frontmatter_by_path(
+    paths: &[String],
+    crate_name: &str,
+    options: &MarkdownOptions,
+) -> HashMap<String, String> {
+    let crate_dir = options.crate_dir.as_deref().unwrap_or(crate_name);
+    let mut paths: Vec<&String> = paths.iter().collect();
+    paths.sort_by(|a, b| file_order_key(a, crate_name).cmp(&file_order_key(b, crate_name)));
+
+    let mut positions: HashMap<&str, usize> = HashMap::new();
+    let mut frontmatter = HashMap::new();
+    for path in paths {
+        let dir = path.rsplit_once('/').map_or("", |(dir, _)| dir);
+        let position = positions.entry(dir).or_insert(0);
+        *position += 1;
+
+        let stem = path.trim_end_matches(".md");
+        let title = if path == "index.md" {
+            crate_name
         } else {
-            continue; // Skip items without path info
+            stem.rsplit('/').next().unwrap_or(stem)
         };
 
-        modules
-            .entry(module_path)
-            .or_default()
-            .push((*id, item.clone()));
-    }
-
-    // Sort items within each module by name
-    for items in modules.values_mut() {
-        items.sort_by(|a, b| {
-            let name_a = a.1.name.as_deref().unwrap_or("");
-            let name_b = b.1.name.as_deref().unwrap_or("");
-            name_a.cmp(name_b)
-        });
+        // Single-file output is one `{crate_dir}.md` file in the output root
+        let slug = if options.single_file {
+            crate_dir.to_string()
+        } else {
+            format!("{}/{}", crate_dir, stem)
+        };
+        frontmatter.insert(path.clone(), self::frontmatter(title, &slug, *position));
     }
-
-    modules
+    frontmatter
 }
 
-fn can_format_item(item: &Item) -> bool {
-    matches!(
-        item.inner,
-        ItemEnum::Struct(_)
-            | ItemEnum::Enum(_)
-            | ItemEnum::Function(_)
-            | ItemEnum::Trait(_)
-            | ItemEnum::Module(_)
-            | ItemEnum::Constant { .. }
-            | ItemEnum::TypeAlias(_)
-            | ItemEnum::Static(_)
-            | ItemEnum::Union(_)
-            | ItemEnum::Macro(_)
-            | ItemEnum::ProcMacro(_)
-            | ItemEnum::TraitAlias(_)
+/// YAML frontmatter block for a file with the given title, slug, and sidebar position.
+pub fn frontmatter(title: &str, slug: &str, sidebar_position: usize) -> String {
+    format!(
+        "---\ntitle: \"{}\"\nslug: {}\nsidebar_position: {}\n---\n\n",
+        title, slug, sidebar_position
     )
 }
 
-fn generate_toc(modules: &HashMap<String, Vec<(Id, Item)>>, crate_name: &str) -> String {
-    let mut toc = String::new();
+/// Convert a rustdoc Crate to markdown format (legacy single-file).
+pub fn convert_to_markdown(crate_data: &Crate, include_private: bool) -> Result<String> {
+    let mut output = String::new();
 
-    // Sort modules alphabetically
-    let mut module_names: Vec<_> = modules.keys().collect();
-    module_names.sort();
+    let root_item = crate_data
+        .index
+        .get(&crate_data.root)
+        .ok_or_else(|| anyhow::anyhow!("Root item not found in index"))?;
 
-    for module_name in module_names {
-        let items = &modules[module_name];
+    let crate_name = root_item.name.as_deref().unwrap_or("unknown");
 
-        // Get the last component of the module path for display
-        let display_name = module_name
-            .strip_prefix(&format!("{}::", crate_name))
-            .unwrap_or(module_name);
+    // Build a map of item_id -> full_path using the paths data
+    let item_paths = build_path_map(crate_data);
 
-        toc.push_str(&format!("- **{}**\n", display_name));
+    let options = MarkdownOptions {
+        include_private,
+        ..Default::default()
+    };
+    let modules = group_by_module(crate_data, &item_paths, &options);
+    let ambiguous_items = ambiguous_items(&modules);
+    let ctx = RenderContext {
+        crate_data,
+        item_paths: &item_paths,
+        ambiguous_items: &ambiguous_items,
+        legacy: true,
+        options: &options,
+        crate_name,
+        current_file: "index.md".to_string(),
+    };
 
-        for (_id, item) in items {
-            if let Some(name) = &item.name {
-                let full_path = format!("{}::{}", module_name, name);
-                let anchor = full_path.to_lowercase().replace("::", "-");
-                toc.push_str(&format!("  - [{}](#{})\n", name, anchor));
-            }
-        }
+    output.push_str(&format!("# {}\n\n", crate_name));
+
+    if let Some(docs) = ctx.docs(root_item) {
+        output.push_str(&format!("{}\n\n", docs));
     }
 
-    toc
-}
+    // Generate hierarchical ToC
+    output.push_str("## Table of Contents\n\n");
+    output.push_str(&generate_toc(&modules, &ctx));
+    output.push_str("\n\n---\n\n");
 
-fn generate_content(
-    modules: &HashMap<String, Vec<(Id, Item)>>,
-    crate_data: &Crate,
-    item_paths: &HashMap<Id, Vec<String>>,
-) -> String {
-    let mut output = String::new();
+    // Generate content organized by module
+    output.push_str(&generate_content(&modules, &ctx));
 
-    // Sort modules alphabetically
-    let mut module_names: Vec<_> = modules.keys().collect();
-    module_names.sort();
+    Ok(output)
+}
 
-    for module_name in module_names {
-        let items = &modules[module_name];
+/// Version of the schema produced by [`convert_to_ir`], bumped on breaking changes.
+pub const IR_SCHEMA_VERSION: u32 = 1;
+
+/// Convert a rustdoc Crate to the intermediate representation: the normalized doc
+/// model the markdown is rendered from, as JSON.
+///
+/// Modules and items are selected exactly as for markdown (including `include_private`
+/// and `exclude`), names are resolved to full paths, signatures are pre-rendered, and
+/// docs have their intra-doc links rewritten as in the markdown output.
+///
+/// ```text
+/// {
+///   "schema_version": 1,
+///   "name": "my_crate",
+///   "version": "0.1.0" | null,
+///   "docs": "..." | null,
+///   "modules": [{
+///     "path": "my_crate::io",
+///     "file": "io.md",                       // where the markdown output puts it
+///     "docs": "..." | null,
+///     "reexports": [{ "statement": "pub use inner::Reader", "target": "my_crate::inner::Reader" | null }],
+///     "items": [{
+///       "name": "Reader",
+///       "path": "my_crate::io::Reader",
+///       "kind": "struct",                    // as in manifest.json
+///       "signature": "struct Reader<R>" | null,
+///       "docs": "..." | null,
+///       "deprecated": { "since": "..." | null, "note": "..." | null } | null,
+///       "cfg": "Unix" | null,                // prose form of #[doc(cfg)]
+///       "aliases": ["reader"],               // from #[doc(alias)]
+///       "fields": [{ "name": "inner", "type": "R", "docs": "..." | null }],
+///       "variants": [{ "name": "Eof", "discriminant": "1" | null, "docs": "..." | null }],
+///       "impls": [{
+///         "kind": "inherent" | "trait" | "auto" | "blanket",
+///         "trait": "Clone" | null,
+///         "header": "impl<R: Clone> Clone for Reader<R>",
+///         "items": [{ "name": "clone", "signature": "fn clone(&self) -> Self", "docs": "..." | null }]
+///       }]
+///     }]
+///   }]
+/// }
+/// ```
+///
+/// Modules come in the same order as [`MarkdownOutput::files_in_order`]; a type's inherent
+/// impls come first, then trait, auto trait, and blanket impls sorted by trait name.
+/// `fields`, `variants`, and `impls` are empty for kinds that have none; a trait's own
+/// items are listed as a single `"trait"` impl with a `null` trait.
+pub fn convert_to_ir(crate_data: &Crate, options: &MarkdownOptions) -> Result<serde_json::Value> {
+    use serde_json::json;
+
+    // Links in the IR's docs stay plain markdown links
+    let options = &MarkdownOptions {
+        wikilinks: false,
+        ..options.clone()
+    };
 
-        // Module header
-        output.push_str(&format!("# Module: `{}`\n\n", module_name));
+    let root_item = crate_data
+        .index
+        .get(&crate_data.root)
+        .ok_or_else(|| anyhow::anyhow!("Root item not found in index"))?;
 
-        // Generate content for each item in the module
-        for (id, item) in items {
-            if let Some(section) = format_item_with_path(id, item, crate_data, item_paths) {
-                output.push_str(&section);
-                output.push_str("\n\n");
-            }
-        }
+    let crate_name = root_item.name.as_deref().unwrap_or("unknown");
+    let item_paths = build_path_map(crate_data);
+    let modules = group_by_module(crate_data, &item_paths, options);
+    let ambiguous_items = ambiguous_items(&modules);
 
-        output.push_str("---\n\n");
+    let mut module_names: Vec<(&String, String)> = modules
+        .keys()
+        .map(|module_name| (module_name, module_file_path(module_name, crate_name)))
+        .collect();
+    module_names.sort_by(|(_, a), (_, b)| {
+        file_order_key(a, crate_name).cmp(&file_order_key(b, crate_name))
+    });
+
+    let index_ctx = RenderContext {
+        crate_data,
+        item_paths: &item_paths,
+        ambiguous_items: &ambiguous_items,
+        legacy: false,
+        options,
+        crate_name,
+        current_file: "index.md".to_string(),
+    };
+
+    let mut module_entries = Vec::new();
+    for (module_name, _) in module_names {
+        let file = module_placement(&module_components(module_name), crate_name, options).file;
+        let ctx = RenderContext {
+            crate_data,
+            item_paths: &item_paths,
+            ambiguous_items: &ambiguous_items,
+            legacy: false,
+            options,
+            crate_name,
+            current_file: file.clone(),
+        };
+
+        let module_item = find_module(module_name, &ctx).map(|(_, item)| item);
+        let items: Vec<_> = modules[module_name]
+            .iter()
+            .filter_map(|(id, item)| ir_item(id, item, &ctx))
+            .collect();
+
+        module_entries.push(json!({
+            "path": module_name,
+            "file": file,
+            "docs": module_item.and_then(|item| ctx.docs(item)),
+            "reexports": ir_reexports(module_name, &ctx),
+            "items": items,
+        }));
     }
 
-    output
+    Ok(json!({
+        "schema_version": IR_SCHEMA_VERSION,
+        "name": crate_name,
+        "version": crate_data.crate_version,
+        "docs": index_ctx.docs(root_item),
+        "modules": module_entries,
+    }))
 }
 
-fn format_item_with_path(
-    item_id: &Id,
-    item: &Item,
-    crate_data: &Crate,
-    item_paths: &HashMap<Id, Vec<String>>,
-) -> Option<String> {
-    let full_path = item_paths.get(item_id)?;
-    let full_name = full_path.join("::");
-
-    let mut output = format_item(item_id, item, crate_data)?;
+/// File the markdown output writes a module to, relative to the crate directory.
+pub(crate) fn module_file_path(module_name: &str, crate_name: &str) -> String {
+    module_own_file(&module_components(module_name), crate_name)
+}
 
-    // Replace the simple name header with the full path
-    if let Some(name) = &item.name {
-        let old_header = format!("## {}\n\n", name);
-        let new_header = format!("## {}\n\n", full_name);
-        output = output.replace(&old_header, &new_header);
+/// The file a module gets when it is not inlined, given its path below the crate root.
+///
+/// A top-level module named `index` or after the crate would land on the crate index
+/// or the root module's file, so it gets a `-module` suffix, which no module name can
+/// contain.
+fn module_own_file(components: &[String], crate_name: &str) -> String {
+    match components {
+        [] => format!("{}.md", crate_name),
+        [name] if name == "index" || name == crate_name => format!("{}-module.md", name),
+        _ => format!("{}.md", components.join("/")),
     }
-
-    Some(output)
 }
 
-fn is_public(item: &Item) -> bool {
-    matches!(item.visibility, Visibility::Public)
+/// The module item whose full path is `module_name`.
+fn find_module<'a>(module_name: &str, ctx: &RenderContext<'a>) -> Option<(&'a Id, &'a Item)> {
+    ctx.crate_data.index.iter().find(|(id, item)| {
+        matches!(item.inner, ItemEnum::Module(_))
+            && ctx
+                .item_paths
+                .get(*id)
+                .map(|path| path.join("::"))
+                .as_deref()
+                == Some(module_name)
+    })
 }
 
-fn format_item(item_id: &rustdoc_types::Id, item: &Item, crate_data: &Crate) -> Option<String> {
-    let name = item.name.as_ref()?;
-    let mut output = String::new();
-
-    match &item.inner {
-        ItemEnum::Struct(s) => {
-            output.push_str(&format!("## {}\n\n", name));
-            output.push_str("*Struct*\n\n");
+fn ir_item(id: &Id, item: &Item, ctx: &RenderContext) -> Option<serde_json::Value> {
+    use serde_json::json;
 
-            if let Some(docs) = &item.docs {
-                output.push_str(&format!("{}\n\n", docs));
+    let crate_data = ctx.crate_data;
+    let name = item.name.as_ref()?;
+    let path = ctx.item_paths.get(id)?;
+
+    let field_entries = |field_ids: &[Id]| -> Vec<serde_json::Value> {
+        field_ids
+            .iter()
+            .enumerate()
+            .filter_map(|(i, field_id)| {
+                let field = crate_data.index.get(field_id)?;
+                if !ctx.options.includes(field, &path[..path.len() - 1]) {
+                    return None;
+                }
+                let ItemEnum::StructField(ty) = &field.inner else {
+                    return None;
+                };
+                Some(json!({
+                    "name": field.name.clone().unwrap_or_else(|| i.to_string()),
+                    "type": format_type(ty),
+                    "docs": ctx.docs(field),
+                }))
+            })
+            .collect()
+    };
+
+    let fields = match &item.inner {
+        ItemEnum::Struct(s) => match &s.kind {
+            rustdoc_types::StructKind::Plain { fields, .. } => field_entries(fields),
+            rustdoc_types::StructKind::Tuple(fields) => {
+                let fields: Vec<Id> = fields.iter().flatten().copied().collect();
+                field_entries(&fields)
             }
-
-            let non_synthetic_params: Vec<_> = s
-                .generics
-                .params
+            rustdoc_types::StructKind::Unit => Vec::new(),
+        },
+        ItemEnum::Union(u) => field_entries(&u.fields),
+        _ => Vec::new(),
+    };
+
+    let variants: Vec<_> = match &item.inner {
+        ItemEnum::Enum(e) => e
+            .variants
+            .iter()
+            .filter_map(|variant_id| crate_data.index.get(variant_id))
+            .filter_map(|variant| {
+                let discriminant = match &variant.inner {
+                    ItemEnum::Variant(v) => v.discriminant.as_ref(),
+                    _ => None,
+                };
+                Some(json!({
+                    "name": variant.name.as_ref()?,
+                    "discriminant": discriminant.map(|discriminant| &discriminant.expr),
+                    "docs": ctx.docs(variant),
+                }))
+            })
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    let impls = match &item.inner {
+        ItemEnum::Struct(_) | ItemEnum::Enum(_) | ItemEnum::Union(_) => {
+            let (inherent_impls, trait_impls, derived_impls) =
+                collect_impls_for_type(id, crate_data);
+            let mut trait_impls: Vec<_> = trait_impls
                 .iter()
-                .filter(|p| {
-                    !matches!(&p.kind, rustdoc_types::GenericParamDefKind::Lifetime { .. })
-                        || !is_synthetic_lifetime(&p.name)
-                })
+                .chain(&derived_impls)
+                .map(|impl_block| ir_impl(impl_block, &path[..path.len() - 1], ctx))
                 .collect();
+            trait_impls.sort_by_key(|impl_| {
+                let kind_rank = match impl_["kind"].as_str() {
+                    Some("trait") => 0,
+                    Some("auto") => 1,
+                    _ => 2,
+                };
+                (
+                    kind_rank,
+                    impl_["trait"].to_string(),
+                    impl_["header"].to_string(),
+                )
+            });
+            inherent_impls
+                .iter()
+                .map(|impl_block| ir_impl(impl_block, &path[..path.len() - 1], ctx))
+                .chain(trait_impls)
+                .collect()
+        }
+        ItemEnum::Trait(t) => {
+            let items: Vec<_> = t
+                .items
+                .iter()
+                .filter_map(|item_id| crate_data.index.get(item_id))
+                .filter_map(|member| ir_impl_member(member, ctx))
+                .collect();
+            vec![json!({
+                "kind": "trait",
+                "trait": null,
+                "header": format!("{}trait {}", if t.is_unsafe { "unsafe " } else { "" }, name),
+                "items": items,
+            })]
+        }
+        _ => Vec::new(),
+    };
+
+    Some(json!({
+        "name": name,
+        "path": path.join("::"),
+        "kind": item_kind_name(item),
+        "signature": item_signature(name, item),
+        "docs": ctx.docs(item),
+        "deprecated": item.deprecation.as_ref().map(|deprecation| json!({
+            "since": deprecation.since,
+            "note": deprecation.note,
+        })),
+        "cfg": item_cfg_prose(item),
+        "aliases": item_aliases(item),
+        "fields": fields,
+        "variants": variants,
+        "impls": impls,
+    }))
+}
 
-            if !non_synthetic_params.is_empty() {
-                output.push_str("**Generic Parameters:**\n");
-                for param in non_synthetic_params {
-                    output.push_str(&format!("- {}\n", format_generic_param(param)));
-                }
-                output.push('\n');
-            }
+/// An impl of a type in `module`, whose visibility decides which inherent items are in.
+fn ir_impl(
+    impl_block: &rustdoc_types::Impl,
+    module: &[String],
+    ctx: &RenderContext,
+) -> serde_json::Value {
+    let kind = if impl_block.is_synthetic {
+        "auto"
+    } else if impl_block.blanket_impl.is_some() {
+        "blanket"
+    } else if impl_block.trait_.is_some() {
+        "trait"
+    } else {
+        "inherent"
+    };
+
+    let header = match &impl_block.blanket_impl {
+        Some(blanket) => format_impl_signature(
+            &rustdoc_types::Impl {
+                for_: blanket.clone(),
+                ..impl_block.clone()
+            },
+            ctx.crate_data,
+        ),
+        None => format_impl_signature(impl_block, ctx.crate_data),
+    };
+
+    let items: Vec<_> = impl_block
+        .items
+        .iter()
+        .filter_map(|item_id| ctx.crate_data.index.get(item_id))
+        .filter(|member| impl_block.trait_.is_some() || ctx.options.includes(member, module))
+        .filter_map(|member| ir_impl_member(member, ctx))
+        .collect();
 
-            match &s.kind {
-                rustdoc_types::StructKind::Plain { fields, .. } => {
-                    if !fields.is_empty() {
-                        output.push_str("**Fields:**\n");
-                        for field_id in fields {
-                            if let Some(field) = crate_data.index.get(field_id) {
-                                if let Some(field_name) = &field.name {
-                                    let field_type = if let ItemEnum::StructField(ty) = &field.inner
-                                    {
-                                        format_type(ty)
-                                    } else {
-                                        "?".to_string()
-                                    };
-                                    output.push_str(&format!("- `{}: {}`", field_name, field_type));
-                                    if let Some(docs) = &field.docs {
-                                        let first_line = docs.lines().next().unwrap_or("").trim();
-                                        if !first_line.is_empty() {
-                                            output.push_str(&format!(" - {}", first_line));
-                                        }
-                                    }
-                                    output.push('\n');
-                                }
-                            }
-                        }
-                        output.push('\n');
-                    }
-                }
-                rustdoc_types::StructKind::Tuple(fields) => {
-                    let types: Vec<String> = fields
-                        .iter()
-                        .filter_map(|field_id| {
-                            field_id.and_then(|id| {
-                                crate_data.index.get(&id).map(|field| {
-                                    if let ItemEnum::StructField(ty) = &field.inner {
-                                        format_type(ty)
-                                    } else {
-                                        "?".to_string()
-                                    }
-                                })
-                            })
-                        })
-                        .collect();
-                    output.push_str(&format!("**Tuple Struct**: `({})`\n\n", types.join(", ")));
-                }
-                rustdoc_types::StructKind::Unit => {
-                    output.push_str("**Unit Struct**\n\n");
-                }
-            }
+    serde_json::json!({
+        "kind": kind,
+        "trait": impl_block.trait_.as_ref().map(|trait_| trait_.path.clone()),
+        "header": header,
+        "items": items,
+    })
+}
 
-            let (inherent_impls, trait_impls) = collect_impls_for_type(item_id, crate_data);
+fn ir_impl_member(member: &Item, ctx: &RenderContext) -> Option<serde_json::Value> {
+    let name = member.name.as_ref()?;
+    let signature = match &member.inner {
+        ItemEnum::Function(f) => format!(
+            "{}{}",
+            format_function_signature(name, f),
+            format_where_clause(&f.generics)
+        ),
+        _ => format_associated_item(member)?,
+    };
+
+    Some(serde_json::json!({
+        "name": name,
+        "signature": signature,
+        "docs": ctx.docs(member),
+    }))
+}
 
-            if !inherent_impls.is_empty() {
-                output.push_str("**Methods:**\n\n");
-                for impl_block in inherent_impls {
-                    output.push_str(&format_impl_methods(impl_block, crate_data));
-                }
-                output.push('\n');
+fn ir_reexports(module_name: &str, ctx: &RenderContext) -> Vec<serde_json::Value> {
+    let Some((_, module_item)) = find_module(module_name, ctx) else {
+        return Vec::new();
+    };
+    let ItemEnum::Module(module) = &module_item.inner else {
+        return Vec::new();
+    };
+    let module_path: Vec<String> = module_name.split("::").map(String::from).collect();
+
+    let mut reexports: Vec<_> = module
+        .items
+        .iter()
+        .filter_map(|id| ctx.crate_data.index.get(id))
+        .filter(|item| ctx.options.includes(item, &module_path))
+        .filter_map(|item| match &item.inner {
+            ItemEnum::Use(use_) => Some((
+                use_statement(use_),
+                use_.id
+                    .and_then(|target| ctx.crate_data.paths.get(&target))
+                    .map(|summary| summary.path.join("::")),
+            )),
+            _ => None,
+        })
+        .collect();
+    reexports.sort();
+
+    reexports
+        .into_iter()
+        .map(|(statement, target)| {
+            serde_json::json!({
+                "statement": statement,
+                "target": target,
+            })
+        })
+        .collect()
+}
+
+/// One-line declaration of an item, without its body.
+pub(crate) fn item_signature(name: &str, item: &Item) -> Option<String> {
+    let generics = |generics: &rustdoc_types::Generics| {
+        format!(
+            "{}{}",
+            format_generic_params(generics),
+            format_where_clause(generics)
+        )
+    };
+
+    Some(match &item.inner {
+        ItemEnum::Function(f) => format!(
+            "{}{}",
+            format_function_signature(name, f),
+            format_where_clause(&f.generics)
+        ),
+        ItemEnum::Struct(s) => format!("struct {}{}", name, generics(&s.generics)),
+        ItemEnum::Enum(e) => format!("enum {}{}", name, generics(&e.generics)),
+        ItemEnum::Union(u) => format!("union {}{}", name, generics(&u.generics)),
+        ItemEnum::Trait(t) => format!(
+            "trait {}{}{}{}",
+            name,
+            format_generic_params(&t.generics),
+            format_supertraits(&t.bounds),
+            format_where_clause(&t.generics)
+        ),
+        ItemEnum::Module(_) => format!("mod {}", name),
+        ItemEnum::Constant { type_, .. } => format!("const {}: {}", name, format_type(type_)),
+        ItemEnum::TypeAlias(ta) => format!(
+            "type {}{} = {}",
+            name,
+            generics(&ta.generics),
+            format_type(&ta.type_)
+        ),
+        ItemEnum::Static(s) => format!(
+            "{}static {}{}: {}",
+            if s.is_unsafe { "unsafe " } else { "" },
+            if s.is_mutable { "mut " } else { "" },
+            name,
+            format_type(&s.type_)
+        ),
+        ItemEnum::Macro(_) => format!("{}!(...)", name),
+        ItemEnum::ProcMacro(pm) => match &pm.kind {
+            rustdoc_types::MacroKind::Bang => format!("{}!(...)", name),
+            rustdoc_types::MacroKind::Attr => format!("#[{}]", name),
+            rustdoc_types::MacroKind::Derive => format!("#[derive({})]", name),
+        },
+        ItemEnum::TraitAlias(ta) => {
+            let bounds: Vec<String> = ta.params.iter().map(format_generic_bound).collect();
+            format!(
+                "trait {}{} = {}",
+                name,
+                format_generic_params(&ta.generics),
+                bounds.join(" + ")
+            )
+        }
+        _ => return None,
+    })
+}
+
+pub(crate) fn build_path_map(crate_data: &Crate) -> HashMap<Id, Vec<String>> {
+    crate_data
+        .paths
+        .iter()
+        .map(|(id, summary)| (*id, summary.path.clone()))
+        .collect()
+}
+
+/// State shared by the renderers while generating one output file.
+struct RenderContext<'a> {
+    crate_data: &'a Crate,
+    item_paths: &'a HashMap<Id, Vec<String>>,
+    /// Items whose heading would share an anchor with another item in their module
+    ambiguous_items: &'a HashSet<Id>,
+    /// Rendering [`convert_to_markdown`]'s single document, where local links are anchors
+    legacy: bool,
+    options: &'a MarkdownOptions,
+    crate_name: &'a str,
+    /// Path of the file being generated, relative to the crate directory
+    current_file: String,
+}
+
+impl RenderContext<'_> {
+    /// The item's documentation, unindented, with intra-doc links rewritten to markdown targets.
+    fn docs(&self, item: &Item) -> Option<String> {
+        let mut docs = unindent_docs(item.docs.as_ref()?);
+        if !self.options.keep_hidden_lines {
+            docs = strip_hidden_lines(&docs);
+        }
+        Some(resolve_doc_links(&docs, |dest| {
+            item.links.get(dest).and_then(|id| self.link_target(id))
+        }))
+    }
+
+    /// `[src]` link target for an item, when `src_base` is set and the item has a local span.
+    fn src_link(&self, item: &Item) -> Option<String> {
+        let base = self.options.src_base.as_deref()?;
+        let span = item.span.as_ref()?;
+        if !span.filename.is_relative() {
+            return None;
+        }
+
+        let filename = span.filename.to_string_lossy().replace('\\', "/");
+        Some(format!(
+            "{}/{}#L{}",
+            base.trim_end_matches('/'),
+            filename,
+            span.begin.0
+        ))
+    }
+
+    /// Anchor of the heading `format_item_with_path` gives an item.
+    ///
+    /// A module inlined into another file is linked to by its section instead.
+    fn item_anchor(&self, item: &Item, full_path: &[String]) -> String {
+        if matches!(item.inner, ItemEnum::Module(_)) && self.is_inlined(full_path) {
+            return module_section_anchor(full_path.get(1..).unwrap_or(&[]), self.crate_name);
+        }
+
+        let mut heading = self.item_heading(item, full_path);
+        if self.src_link(item).is_some() {
+            heading.push_str(" [src]");
+        }
+        heading_anchor(&heading)
+    }
+
+    /// Heading an item is rendered under, with its kind appended when another item in
+    /// the module would otherwise get the same anchor, and its visibility prefixed when
+    /// private items are documented alongside public ones.
+    ///
+    /// Module stubs keep their `Module: name` heading without a visibility.
+    fn item_heading(&self, item: &Item, full_path: &[String]) -> String {
+        let mut heading = item_heading(item, full_path);
+        if let Some(kind) = item_kind_name(item) {
+            if self.ambiguous_items.contains(&item.id) {
+                heading = format!("{} ({})", heading, kind.replace('_', " "));
             }
+        }
+        if !matches!(item.inner, ItemEnum::Module(_)) {
+            let module = &full_path[..full_path.len().saturating_sub(1)];
+            heading = self.with_visibility(heading, &visibility_label(item, module));
+        }
+        heading
+    }
 
-            if !trait_impls.is_empty() {
-                let user_impls: Vec<_> = trait_impls
-                    .iter()
-                    .filter(|impl_block| {
-                        !impl_block.is_synthetic && impl_block.blanket_impl.is_none()
-                    })
-                    .collect();
+    /// `heading` starting with `visibility` when private items are documented
+    /// alongside public ones.
+    fn with_visibility(&self, heading: String, visibility: &str) -> String {
+        if self.options.includes_non_public() {
+            format!("{} {}", visibility, heading)
+        } else {
+            heading
+        }
+    }
 
-                if !user_impls.is_empty() {
-                    let mut derives = Vec::new();
-                    let mut trait_with_methods = Vec::new();
+    /// Whether the module at `full_path` is a section of another module's file.
+    fn is_inlined(&self, full_path: &[String]) -> bool {
+        let components = full_path.get(1..).unwrap_or(&[]);
+        module_placement(components, self.crate_name, self.options).inlined
+    }
 
-                    for impl_block in user_impls {
-                        if let Some(trait_ref) = &impl_block.trait_ {
-                            let methods = format_impl_methods(impl_block, crate_data);
-                            if methods.is_empty() {
-                                derives.push(trait_ref.path.as_str());
-                            } else {
-                                trait_with_methods.push((trait_ref, methods));
-                            }
-                        }
-                    }
+    /// Link to where a crate-local item is documented, relative to the current file.
+    ///
+    /// Items that are not rendered (private, excluded, or of an unsupported kind)
+    /// have no target.
+    fn local_item_link(&self, id: &Id) -> Option<String> {
+        let item = self.crate_data.index.get(id)?;
+        let path = self.item_paths.get(id)?;
+        let rendered = self
+            .options
+            .includes(item, &path[..path.len().saturating_sub(1)])
+            && can_format_item(item)
+            && !is_excluded(path, &self.options.exclude);
+        if !rendered {
+            return None;
+        }
 
-                    let public_derives: Vec<_> = derives
-                        .into_iter()
-                        .filter(|t| !is_compiler_internal_trait(t))
-                        .collect();
+        let is_module = matches!(item.inner, ItemEnum::Module(_));
+        if self.options.single_file || self.legacy {
+            return Some(format!("#{}", self.item_fragment(item, path)));
+        }
 
-                    if !public_derives.is_empty() {
-                        output.push_str("**Traits:** ");
-                        output.push_str(&public_derives.join(", "));
-                        output.push_str("\n\n");
-                    }
+        let module_path = if is_module {
+            &path[..]
+        } else {
+            &path[..path.len().saturating_sub(1)]
+        };
+        let placement = module_placement(
+            module_path.get(1..).unwrap_or(&[]),
+            self.crate_name,
+            self.options,
+        );
+        let module_file = placement.file;
+        let up = "../".repeat(self.current_file.matches('/').count());
+
+        if is_module && !placement.inlined {
+            Some(format!("{}{}", up, module_file))
+        } else if module_file == self.current_file {
+            Some(format!("#{}", self.item_fragment(item, path)))
+        } else {
+            Some(format!(
+                "{}{}#{}",
+                up,
+                module_file,
+                self.item_fragment(item, path)
+            ))
+        }
+    }
 
-                    if !trait_with_methods.is_empty() {
-                        output.push_str("**Trait Implementations:**\n\n");
-                        for (trait_ref, methods) in trait_with_methods {
-                            output.push_str(&format!("- **{}**\n", trait_ref.path));
-                            for line in methods.lines() {
-                                output.push_str(&format!("  {}\n", line));
-                            }
-                        }
-                        output.push('\n');
-                    }
+    /// The `pub ` or `pub(crate) ` a field of a type in `module` is declared with,
+    /// when private items are documented alongside public ones; empty otherwise.
+    fn shown_visibility(&self, item: &Item, module: &[String]) -> String {
+        if self.options.includes_non_public() {
+            declared_visibility(item, module)
+        } else {
+            String::new()
+        }
+    }
+
+    /// Link fragment for a heading: its anchor, or with `wikilinks` the heading itself.
+    ///
+    /// Obsidian matches headings by text, ignoring the characters that wikilink
+    /// syntax reserves.
+    fn fragment(&self, heading: &str) -> String {
+        if !self.options.wikilinks {
+            return heading_anchor(heading);
+        }
+        heading
+            .replace(['#', '|', '^', ':', '[', ']'], " ")
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Link fragment for where [`Self::item_anchor`] points.
+    fn item_fragment(&self, item: &Item, full_path: &[String]) -> String {
+        if !self.options.wikilinks {
+            return self.item_anchor(item, full_path);
+        }
+        if matches!(item.inner, ItemEnum::Module(_)) && self.is_inlined(full_path) {
+            let components = full_path.get(1..).unwrap_or(&[]);
+            return self.fragment(&module_section_heading(components, self.crate_name));
+        }
+        self.fragment(&self.item_heading(item, full_path))
+    }
+
+    /// Where a link to `id` should point, if it can be resolved.
+    ///
+    /// Items of this crate and of crates documented in the same run get a relative
+    /// link to their module file; other external items link to their rustdoc HTML page.
+    fn link_target(&self, id: &Id) -> Option<String> {
+        let summary = self.crate_data.paths.get(id)?;
+        if summary.crate_id == 0 {
+            return self.local_item_link(id);
+        }
+        let external = self.crate_data.external_crates.get(&summary.crate_id)?;
+        if let Some(link) = self.documented_crate_link(summary, &external.name) {
+            return Some(link);
+        }
+
+        let root = external
+            .html_root_url
+            .clone()
+            .unwrap_or_else(|| format!("https://docs.rs/{}/latest/", external.name));
+        html_page_link(&root, summary.kind, &summary.path)
+    }
+
+    /// Where a type named in a signature should link to, if anywhere.
+    ///
+    /// Like [`Self::link_target`], except that standard library items link to the
+    /// stable docs and items of crates not documented in this run aren't linked.
+    fn signature_link(&self, id: &Id) -> Option<String> {
+        let summary = self.crate_data.paths.get(id)?;
+        if summary.crate_id == 0 {
+            return self.local_item_link(id);
+        }
+        let external = self.crate_data.external_crates.get(&summary.crate_id)?;
+        if let Some(link) = self.documented_crate_link(summary, &external.name) {
+            return Some(link);
+        }
+
+        // Paths are where items are defined, which for std's re-exports of core and
+        // alloc only have pages in those crates' docs
+        match external.name.as_str() {
+            "std" | "core" | "alloc" => html_page_link(STD_DOCS_ROOT, summary.kind, &summary.path),
+            _ => None,
+        }
+    }
+
+    /// `*Types: ...*` line linking the named types in `types` to their docs.
+    ///
+    /// Code blocks can't hold links, so the types a signature mentions are listed
+    /// under it instead. Types without a known destination are left out.
+    fn signature_types(&self, types: &[&rustdoc_types::Type]) -> String {
+        let mut refs = Vec::new();
+        for ty in types {
+            collect_type_refs(ty, &mut refs);
+        }
+
+        let mut links: Vec<String> = Vec::new();
+        for type_ref in refs {
+            let (name, target) = match type_ref {
+                TypeRef::Path(path, id) => (
+                    path.rsplit("::").next().unwrap_or(path).to_string(),
+                    self.signature_link(id),
+                ),
+                TypeRef::Primitive(name) => {
+                    let path = ["std".to_string(), name.to_string()];
+                    let kind = rustdoc_types::ItemKind::Primitive;
+                    (name.to_string(), html_page_link(STD_DOCS_ROOT, kind, &path))
+                }
+            };
+            if let Some(target) = target {
+                let link = format!("[`{}`]({})", name, target);
+                if !links.contains(&link) {
+                    links.push(link);
                 }
             }
         }
-        ItemEnum::Enum(e) => {
-            output.push_str(&format!("## {}\n\n", name));
-            output.push_str("*Enum*\n\n");
 
-            if let Some(docs) = &item.docs {
-                output.push_str(&format!("{}\n\n", docs));
+        if links.is_empty() {
+            return String::new();
+        }
+        format!("*Types: {}*\n\n", links.join(", "))
+    }
+
+    /// Relative link to an item of a crate documented in the same run.
+    fn documented_crate_link(
+        &self,
+        summary: &rustdoc_types::ItemSummary,
+        crate_name: &str,
+    ) -> Option<String> {
+        use rustdoc_types::ItemKind;
+
+        let target_dir = self.options.documented_crates.get(crate_name)?;
+
+        // Climb out of this crate's own directory to the output root
+        let crate_depth = self
+            .options
+            .crate_dir
+            .as_deref()
+            .map_or(0, |dir| dir.matches('/').count());
+        if self.options.single_file {
+            let heading = match (summary.kind, &summary.path[..]) {
+                (ItemKind::Module, [_]) => format!("Module: {}", crate_name),
+                (ItemKind::Module, [_, rest @ ..]) => format!("Module: {}", rest.join("::")),
+                _ => self.documented_item_heading(summary),
+            };
+            return Some(format!(
+                "{}{}.md#{}",
+                "../".repeat(crate_depth),
+                target_dir,
+                self.fragment(&heading)
+            ));
+        }
+
+        let module_path = match summary.kind {
+            ItemKind::Module => &summary.path[..],
+            _ => &summary.path[..summary.path.len().saturating_sub(1)],
+        };
+        let components = module_path.get(1..).unwrap_or(&[]);
+        let placement = module_placement(components, crate_name, self.options);
+        let anchor = match summary.kind {
+            ItemKind::Module if placement.inlined => {
+                format!(
+                    "#{}",
+                    self.fragment(&module_section_heading(components, crate_name))
+                )
             }
+            ItemKind::Module => String::new(),
+            _ => format!("#{}", self.fragment(&self.documented_item_heading(summary))),
+        };
+        let up = "../".repeat(self.current_file.matches('/').count() + 1 + crate_depth);
+        Some(format!("{}{}/{}{}", up, target_dir, placement.file, anchor))
+    }
 
-            let non_synthetic_params: Vec<_> = e
-                .generics
-                .params
-                .iter()
-                .filter(|p| {
-                    !matches!(&p.kind, rustdoc_types::GenericParamDefKind::Lifetime { .. })
-                        || !is_synthetic_lifetime(&p.name)
-                })
-                .collect();
+    /// Heading another documented crate renders the item `summary` under, as
+    /// [`Self::item_heading`] would there.
+    ///
+    /// Only public items can be linked to from another crate.
+    fn documented_item_heading(&self, summary: &rustdoc_types::ItemSummary) -> String {
+        let mut heading = summary.path.join("::");
+        if let Some(kind) = summary_kind_name(summary.kind) {
+            let key = (heading.clone(), kind.to_string());
+            if self.options.disambiguated_items.contains(&key) {
+                heading = format!("{} ({})", heading, kind.replace('_', " "));
+            }
+        }
+        self.with_visibility(heading, "pub")
+    }
+}
 
-            if !non_synthetic_params.is_empty() {
-                output.push_str("**Generic Parameters:**\n");
-                for param in non_synthetic_params {
-                    output.push_str(&format!("- {}\n", format_generic_param(param)));
-                }
-                output.push('\n');
+/// Root of the standard library's docs for the latest stable release.
+const STD_DOCS_ROOT: &str = "https://doc.rust-lang.org/";
+
+/// Link to the rustdoc HTML page of the item at `path`, under `root`.
+fn html_page_link(root: &str, kind: rustdoc_types::ItemKind, path: &[String]) -> Option<String> {
+    use rustdoc_types::ItemKind;
+
+    let root = root.trim_end_matches('/');
+    let (name, parents) = path.split_last()?;
+
+    let page_prefix = match kind {
+        ItemKind::Module => {
+            return Some(format!("{}/{}/index.html", root, path.join("/")));
+        }
+        ItemKind::Struct => "struct",
+        ItemKind::Enum => "enum",
+        ItemKind::Union => "union",
+        ItemKind::Trait => "trait",
+        ItemKind::TraitAlias => "traitalias",
+        ItemKind::Function => "fn",
+        ItemKind::TypeAlias => "type",
+        ItemKind::Constant => "constant",
+        ItemKind::Static => "static",
+        ItemKind::Macro => "macro",
+        ItemKind::ProcAttribute => "attr",
+        ItemKind::ProcDerive => "derive",
+        ItemKind::Primitive => "primitive",
+        ItemKind::Keyword => "keyword",
+        _ => return Some(format!("{}/{}/index.html", root, parents.join("/"))),
+    };
+
+    Some(format!(
+        "{}/{}/{}.{}.html",
+        root,
+        parents.join("/"),
+        page_prefix,
+        name
+    ))
+}
+
+/// `pub use` lines of a module, each linked to the documentation of what it re-exports.
+fn format_reexports(module_name: &str, ctx: &RenderContext) -> String {
+    let crate_data = ctx.crate_data;
+    let Some((_, module_item)) = find_module(module_name, ctx) else {
+        return String::new();
+    };
+    let ItemEnum::Module(module) = &module_item.inner else {
+        return String::new();
+    };
+    let module_path: Vec<String> = module_name.split("::").map(String::from).collect();
+
+    let mut lines = Vec::new();
+    for id in &module.items {
+        let Some(item) = crate_data.index.get(id) else {
+            continue;
+        };
+        let ItemEnum::Use(use_) = &item.inner else {
+            continue;
+        };
+        if !ctx.options.includes(item, &module_path) {
+            continue;
+        }
+
+        let statement = use_statement(use_);
+
+        let target = use_
+            .id
+            .as_ref()
+            .and_then(|target| match crate_data.paths.get(target) {
+                Some(summary) if summary.crate_id != 0 => ctx.link_target(target),
+                _ => ctx.local_item_link(target),
+            });
+        lines.push(match target {
+            Some(target) => format!("- [`{}`]({})\n", statement, target),
+            None => format!("- `{}`\n", statement),
+        });
+    }
+
+    lines.sort();
+    lines.concat()
+}
+
+/// A re-export as written, e.g. `pub use inner::Reader as Source`.
+fn use_statement(use_: &rustdoc_types::Use) -> String {
+    let mut statement = format!("pub use {}", use_.source);
+    if use_.is_glob {
+        statement.push_str("::*");
+    } else if use_.source.rsplit("::").next() != Some(use_.name.as_str()) {
+        statement.push_str(&format!(" as {}", use_.name));
+    }
+    statement
+}
+
+/// Heading text `format_item_with_path` gives an item.
+fn item_heading(item: &Item, full_path: &[String]) -> String {
+    match (&item.inner, &item.name) {
+        (ItemEnum::Module(_), Some(name)) => format!("Module: {}", name),
+        _ => full_path.join("::"),
+    }
+}
+
+/// Items whose [`item_heading`] anchor matches another's in the same module.
+///
+/// Names in different namespaces (`struct Foo` and `fn foo`) or differing only in
+/// case map to the same anchor, since anchors are lowercased.
+fn ambiguous_items(modules: &HashMap<String, Vec<(Id, Item)>>) -> HashSet<Id> {
+    let mut ambiguous = HashSet::new();
+    for (module_name, items) in modules {
+        let mut by_anchor: HashMap<String, Vec<Id>> = HashMap::new();
+        for (id, item) in items {
+            if let Some(name) = &item.name {
+                let full_path = format!("{}::{}", module_name, name);
+                let anchor = heading_anchor(&item_heading(item, std::slice::from_ref(&full_path)));
+                by_anchor.entry(anchor).or_default().push(*id);
             }
+        }
+        ambiguous.extend(
+            by_anchor
+                .into_values()
+                .filter(|ids| ids.len() > 1)
+                .flatten(),
+        );
+    }
+    ambiguous
+}
 
-            if !e.variants.is_empty() {
-                output.push_str("**Variants:**\n");
-                for variant_id in &e.variants {
-                    if let Some(variant) = crate_data.index.get(variant_id) {
-                        if let Some(variant_name) = &variant.name {
-                            let variant_kind = if let ItemEnum::Variant(v) = &variant.inner {
-                                match &v.kind {
-                                    rustdoc_types::VariantKind::Plain => None,
-                                    rustdoc_types::VariantKind::Tuple(fields) => {
-                                        let types: Vec<_> = fields
-                                            .iter()
-                                            .map(|field_id| {
-                                                if let Some(id) = field_id {
-                                                    if let Some(field_item) =
-                                                        crate_data.index.get(id)
-                                                    {
-                                                        if let ItemEnum::StructField(ty) =
-                                                            &field_item.inner
-                                                        {
-                                                            return format_type(ty);
-                                                        }
-                                                    }
-                                                }
-                                                "?".to_string()
-                                            })
-                                            .collect();
-                                        Some(format!("({})", types.join(", ")))
-                                    }
-                                    rustdoc_types::VariantKind::Struct { fields, .. } => {
-                                        let field_list: Vec<String> = fields
-                                            .iter()
-                                            .filter_map(|field_id| {
-                                                crate_data.index.get(field_id).and_then(|f| {
-                                                    f.name.as_ref().map(|name| {
-                                                        let field_type =
-                                                            if let ItemEnum::StructField(ty) =
-                                                                &f.inner
-                                                            {
-                                                                format_type(ty)
-                                                            } else {
-                                                                "?".to_string()
-                                                            };
-                                                        format!("{}: {}", name, field_type)
-                                                    })
-                                                })
-                                            })
-                                            .collect();
-                                        Some(format!("{{ {} }}", field_list.join(", ")))
-                                    }
-                                }
-                            } else {
-                                None
-                            };
+/// Items of `crate_data` whose heading has their kind appended because another item
+/// in their module would otherwise get the same anchor, as `(path, kind)` pairs.
+///
+/// Other crates linking into this one need these to find the right heading, which
+/// their own rustdoc JSON can't tell them; pass them as
+/// [`MarkdownOptions::disambiguated_items`].
+pub fn disambiguated_items(
+    crate_data: &Crate,
+    options: &MarkdownOptions,
+) -> BTreeSet<(String, String)> {
+    let item_paths = build_path_map(crate_data);
+    let modules = group_by_module(crate_data, &item_paths, options);
+    disambiguated(crate_data, &item_paths, &ambiguous_items(&modules))
+}
 
-                            output.push_str("- `");
-                            output.push_str(variant_name);
-                            if let Some(kind) = variant_kind {
-                                output.push_str(&kind);
-                            }
-                            output.push('`');
+fn disambiguated(
+    crate_data: &Crate,
+    item_paths: &HashMap<Id, Vec<String>>,
+    ambiguous_items: &HashSet<Id>,
+) -> BTreeSet<(String, String)> {
+    ambiguous_items
+        .iter()
+        .filter_map(|id| {
+            let kind = item_kind_name(crate_data.index.get(id)?)?;
+            Some((item_paths.get(id)?.join("::"), kind.to_string()))
+        })
+        .collect()
+}
 
-                            if let Some(docs) = &variant.docs {
-                                let first_line = docs.lines().next().unwrap_or("").trim();
-                                if !first_line.is_empty() {
-                                    output.push_str(&format!(" - {}", first_line));
-                                }
-                            }
-                            output.push('\n');
-                        }
-                    }
+/// Anchor GitHub generates for a heading: lowercase, punctuation dropped, spaces as hyphens.
+fn heading_anchor(heading: &str) -> String {
+    heading
+        .trim()
+        .to_lowercase()
+        .chars()
+        .filter_map(|c| match c {
+            ' ' => Some('-'),
+            '-' | '_' => Some(c),
+            c if c.is_alphanumeric() => Some(c),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Remove the indentation shared by every non-blank line, like rustdoc does.
+///
+/// Docs from `/** */` comments or `#[doc = "    ..."]` attributes can arrive indented
+/// as a whole, which would turn every paragraph into a code block and push fences
+/// deep enough that they stop being fences. Indentation beyond the common amount,
+/// as in four-space code blocks, is kept.
+fn unindent_docs(docs: &str) -> String {
+    let indent = docs
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.len() - line.trim_start().len())
+        .min()
+        .unwrap_or(0);
+    if indent == 0 {
+        return docs.to_string();
+    }
+
+    docs.lines()
+        .map(|line| line.get(indent..).unwrap_or_else(|| line.trim_start()))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Drop the lines of Rust code blocks that rustdoc hides, like `# use my_crate::Thing;`,
+/// and turn the `##` that escapes a visible `#` back into `#`.
+///
+/// A code block is Rust when its fence has no language, says `rust`, or only has
+/// doctest attributes like `no_run`; blocks in other languages are left alone.
+fn strip_hidden_lines(docs: &str) -> String {
+    let mut output = String::with_capacity(docs.len());
+    let mut fence: Option<(&str, bool)> = None;
+
+    for line in docs.split_inclusive('\n') {
+        let trimmed = line.trim_start();
+        let marker = ["```", "~~~"].into_iter().find(|m| trimmed.starts_with(m));
+        match (fence, marker) {
+            (None, Some(marker)) => {
+                let info = trimmed.trim_start_matches(marker.chars().next().unwrap_or('`'));
+                fence = Some((marker, is_rust_info_string(info)));
+            }
+            (Some((open, _)), Some(marker)) if open == marker => fence = None,
+            (Some((_, true)), _) => {
+                let code = trimmed.trim_end();
+                if code == "#" || code.starts_with("# ") || code.starts_with("#\t") {
+                    continue;
+                }
+                if code.starts_with("##") {
+                    let indent = line.len() - trimmed.len();
+                    output.push_str(&line[..indent]);
+                    output.push_str(&trimmed[1..]);
+                    continue;
                 }
-                output.push('\n');
             }
+            _ => {}
+        }
+        output.push_str(line);
+    }
+
+    output
+}
+
+/// Whether a code block's info string makes it a Rust doctest, as rustdoc decides.
+fn is_rust_info_string(info: &str) -> bool {
+    let mut tokens = info
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|token| !token.is_empty());
+    tokens.all(|token| {
+        matches!(
+            token,
+            "rust" | "ignore" | "should_panic" | "no_run" | "compile_fail" | "test_harness"
+        ) || token.starts_with("ignore-")
+            || token.starts_with("edition")
+            || (token.len() > 1
+                && token.starts_with('E')
+                && token[1..].chars().all(|c| c.is_ascii_digit()))
+    })
+}
+
+/// `**Returns:** ...` notes and the like for the `# Returns`, `# Errors`, and
+/// `# Panics` sections of `docs`, in that order.
+///
+/// A section that is one paragraph becomes one line; anything else, like a list,
+/// follows its label as a block.
+fn extracted_sections(docs: &str) -> String {
+    let mut output = String::new();
+    for title in ["Returns", "Errors", "Panics"] {
+        let Some(body) = doc_section(docs, title) else {
+            continue;
+        };
+        let is_paragraph = !body.contains("\n\n")
+            && body.lines().all(|line| {
+                let line = line.trim_start();
+                !line.starts_with(['-', '*', '+', '>', '|', '`', '~', '#'])
+                    && !line.split_once(". ").is_some_and(|(marker, _)| {
+                        !marker.is_empty() && marker.chars().all(|c| c.is_ascii_digit())
+                    })
+            });
+        if is_paragraph {
+            let line: Vec<&str> = body.lines().map(str::trim).collect();
+            output.push_str(&format!("**{}:** {}\n\n", title, line.join(" ")));
+        } else {
+            output.push_str(&format!("**{}:**\n\n{}\n\n", title, body));
+        }
+    }
+    output
+}
+
+/// The body of the section of `docs` under the heading `title`, e.g. `# Errors`, at
+/// any level, up to the next heading at the same level or above.
+fn doc_section(docs: &str, title: &str) -> Option<String> {
+    let mut in_code_block = false;
+    let mut level = None;
+    let mut body = Vec::new();
+
+    for line in docs.lines() {
+        let trimmed = line.trim_start();
+        let is_fence = trimmed.starts_with("```") || trimmed.starts_with("~~~");
+        in_code_block ^= is_fence;
+
+        let hashes = line.chars().take_while(|&c| c == '#').count();
+        let heading = (!in_code_block && !is_fence && (1..=6).contains(&hashes))
+            .then(|| line[hashes..].strip_prefix(' '))
+            .flatten()
+            .map(|text| text.trim().trim_end_matches('#').trim_end());
+        match (level, heading) {
+            (None, Some(text)) if text.eq_ignore_ascii_case(title) => level = Some(hashes),
+            (Some(level), Some(_)) if hashes <= level => break,
+            (Some(_), _) => body.push(line),
+            (None, _) => {}
+        }
+    }
+
+    level?;
+    let body = body.join("\n");
+    let body = body.trim_matches('\n').trim_end();
+    (!body.is_empty()).then(|| body.to_string())
+}
 
-            let (inherent_impls, trait_impls) = collect_impls_for_type(item_id, crate_data);
+/// Rewrite markdown links whose destination `resolve` recognizes.
+///
+/// Handles inline (`[text](dest)`), reference (`[text][dest]`), shorthand
+/// (`[dest]`) links and reference definitions, leaving code untouched. Intra-doc
+/// links that don't resolve (to private, excluded, or missing items) lose their
+/// brackets instead of rendering as broken links.
+fn resolve_doc_links(docs: &str, resolve: impl Fn(&str) -> Option<String>) -> String {
+    let prose_lines = || {
+        let mut in_code_block = false;
+        docs.split_inclusive('\n').map(move |line| {
+            let trimmed = line.trim_start();
+            let is_fence = trimmed.starts_with("```") || trimmed.starts_with("~~~");
+            in_code_block ^= is_fence;
+            (line, !in_code_block && !is_fence)
+        })
+    };
+
+    // Labels whose reference definition is still a working link afterwards
+    let defined: HashSet<&str> = prose_lines()
+        .filter(|(_, is_prose)| *is_prose)
+        .filter_map(|(line, _)| reference_definition(line))
+        .filter(|(_, dest)| resolve(dest).is_some() || !is_intra_doc_path(dest))
+        .map(|(label, _)| label)
+        .collect();
 
-            if !inherent_impls.is_empty() {
-                output.push_str("**Methods:**\n\n");
-                for impl_block in inherent_impls {
-                    output.push_str(&format_impl_methods(impl_block, crate_data));
+    let mut output = String::with_capacity(docs.len());
+    for (line, is_prose) in prose_lines() {
+        if !is_prose {
+            output.push_str(line);
+        } else if let Some((label, dest)) = reference_definition(line) {
+            match resolve(dest) {
+                Some(target) => {
+                    let indent = &line[..line.len() - line.trim_start().len()];
+                    let newline = if line.ends_with('\n') { "\n" } else { "" };
+                    output.push_str(&format!("{}[{}]: {}{}", indent, label, target, newline));
                 }
-                output.push('\n');
+                // Nothing links to it any more, see `resolve_inline_links`
+                None if is_intra_doc_path(dest) => {}
+                None => output.push_str(line),
             }
+        } else {
+            output.push_str(&resolve_inline_links(line, &resolve, &defined));
+        }
+    }
 
-            if !trait_impls.is_empty() {
-                let user_impls: Vec<_> = trait_impls
-                    .iter()
-                    .filter(|impl_block| {
-                        !impl_block.is_synthetic && impl_block.blanket_impl.is_none()
-                    })
-                    .collect();
+    output
+}
 
-                if !user_impls.is_empty() {
-                    let mut derives = Vec::new();
-                    let mut trait_with_methods = Vec::new();
+/// The label and destination of a `[label]: dest` reference definition line.
+fn reference_definition(line: &str) -> Option<(&str, &str)> {
+    let (label, rest) = line.trim_start().strip_prefix('[')?.split_once("]:")?;
+    let dest = rest.split_whitespace().next()?;
+    Some((label, dest.trim_start_matches('<').trim_end_matches('>')))
+}
 
-                    for impl_block in user_impls {
-                        if let Some(trait_ref) = &impl_block.trait_ {
-                            let methods = format_impl_methods(impl_block, crate_data);
-                            if methods.is_empty() {
-                                derives.push(trait_ref.path.as_str());
-                            } else {
-                                trait_with_methods.push((trait_ref, methods));
-                            }
-                        }
+/// Whether a link destination reads as an intra-doc link to a Rust path, like
+/// `Foo`, `` `crate::io::Read` ``, `struct@Foo`, `Foo::new()` or `vec!`.
+fn is_intra_doc_path(dest: &str) -> bool {
+    let path = dest.trim_matches('`');
+    let path = path.split_once('@').map_or(path, |(_, path)| path);
+    let path = path
+        .strip_suffix("()")
+        .or_else(|| path.strip_suffix('!'))
+        .unwrap_or(path);
+
+    path.split("::").all(|segment| {
+        segment
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_alphabetic() || c == '_')
+            && segment.chars().all(|c| c.is_alphanumeric() || c == '_')
+    })
+}
+
+fn resolve_inline_links(
+    line: &str,
+    resolve: &impl Fn(&str) -> Option<String>,
+    defined: &HashSet<&str>,
+) -> String {
+    let mut output = String::with_capacity(line.len());
+    let mut rest = line;
+
+    while let Some(pos) = rest.find(['`', '[', '\\']) {
+        output.push_str(&rest[..pos]);
+        rest = &rest[pos..];
+
+        if rest.starts_with('\\') {
+            let escaped = rest.chars().nth(1).map_or(1, |c| 1 + c.len_utf8());
+            output.push_str(&rest[..escaped]);
+            rest = &rest[escaped..];
+        } else if rest.starts_with('`') {
+            let span = code_span_len(rest);
+            output.push_str(&rest[..span]);
+            rest = &rest[span..];
+        } else if let Some(text_len) = bracket_len(rest) {
+            let text = &rest[1..text_len - 1];
+            let after = &rest[text_len..];
+
+            let (dest, consumed) = if after.starts_with('(') {
+                match after.find(')') {
+                    Some(end) => {
+                        let inner = after[1..end].trim();
+                        (inner.split_whitespace().next().unwrap_or(""), end + 1)
+                    }
+                    None => ("", 0),
+                }
+            } else if after.starts_with('[') {
+                match bracket_len(after) {
+                    Some(len) if len == 2 => (text, len),
+                    Some(len) => (&after[1..len - 1], len),
+                    None => ("", 0),
+                }
+            } else if after.starts_with(':') {
+                ("", 0)
+            } else {
+                (text, 0)
+            };
+
+            // Shorthand links need code or a path to tell them from prose like `[x]`
+            let is_intra_doc = if after.starts_with('(') {
+                is_intra_doc_path(dest)
+            } else {
+                !defined.contains(dest)
+                    && is_intra_doc_path(dest)
+                    && (consumed > 0 || dest.contains('`') || dest.contains("::"))
+            };
+
+            match resolve(dest) {
+                Some(target) if !dest.is_empty() => {
+                    output.push_str(&format!("[{}]({})", text, target));
+                    rest = &after[consumed..];
+                }
+                None if !dest.is_empty() && is_intra_doc => {
+                    if text.contains('`') || !is_intra_doc_path(text) {
+                        output.push_str(text);
+                    } else {
+                        output.push_str(&format!("`{}`", text));
                     }
+                    rest = &after[consumed..];
+                }
+                _ => {
+                    output.push('[');
+                    rest = &rest[1..];
+                }
+            }
+        } else {
+            output.push('[');
+            rest = &rest[1..];
+        }
+    }
+
+    output.push_str(rest);
+    output
+}
+
+/// Rewrite links to markdown files, `[text](path.md#fragment)`, as Obsidian
+/// wikilinks, `[[path#fragment|text]]`.
+///
+/// `dir` is the directory of the file the markdown is for, relative to the vault
+/// root; link paths are resolved against it, since Obsidian resolves wikilink paths
+/// from the vault root. Links to other destinations, like web pages, and anything in
+/// code are left as they are. Fragments are used as they are, so they should already
+/// be heading text.
+pub fn wikilinks(markdown: &str, dir: &str) -> String {
+    rewrite_links(markdown, |s, text_len| markdown_file_link(s, text_len, dir))
+}
+
+/// Rewrite relative links to markdown files, `[text](../path.md#fragment)`, to start
+/// with `base_url`, `[text](/docs/api/path.md#fragment)`, the output root being
+/// served from there.
+///
+/// `dir` is the directory of the file the markdown is for, relative to the output
+/// root. Links to headings in the same file, to other destinations like web pages,
+/// and anything in code are left as they are.
+pub fn base_url_links(markdown: &str, dir: &str, base_url: &str) -> String {
+    let base_url = base_url.trim_end_matches('/');
+    rewrite_links(markdown, |s, text_len| {
+        let dest = link_destination(s, text_len)?;
+        let (path, fragment) = match dest.split_once('#') {
+            Some((path, fragment)) => (path, Some(fragment)),
+            None => (dest, None),
+        };
+        if path.is_empty() || path.starts_with('/') || dest.contains("://") {
+            return None;
+        }
+
+        let mut link = format!(
+            "{}({}/{}.md",
+            &s[..text_len],
+            base_url,
+            file_page(dir, path)?
+        );
+        if let Some(fragment) = fragment {
+            link.push('#');
+            link.push_str(fragment);
+        }
+        link.push(')');
+
+        Some(FileLink {
+            link,
+            len: text_len + 1 + dest.len() + 1,
+        })
+    })
+}
+
+/// Replace each inline link outside code that `rewrite` returns a link for.
+fn rewrite_links(markdown: &str, rewrite: impl Fn(&str, usize) -> Option<FileLink>) -> String {
+    let mut output = String::with_capacity(markdown.len());
+    let mut in_code_block = false;
+
+    for line in markdown.split_inclusive('\n') {
+        let trimmed = line.trim_start();
+        let is_fence = trimmed.starts_with("```") || trimmed.starts_with("~~~");
+        in_code_block ^= is_fence;
+        if in_code_block || is_fence {
+            output.push_str(line);
+            continue;
+        }
+
+        let mut rest = line;
+        while let Some(pos) = rest.find(['`', '[', '\\']) {
+            output.push_str(&rest[..pos]);
+            rest = &rest[pos..];
+
+            let skip = if rest.starts_with('\\') {
+                rest.chars().nth(1).map_or(1, |c| 1 + c.len_utf8())
+            } else if rest.starts_with('`') {
+                code_span_len(rest)
+            } else if let Some(link) = bracket_len(rest).and_then(|len| rewrite(rest, len)) {
+                output.push_str(&link.link);
+                rest = &rest[link.len..];
+                continue;
+            } else {
+                1
+            };
+            output.push_str(&rest[..skip]);
+            rest = &rest[skip..];
+        }
+        output.push_str(rest);
+    }
+
+    output
+}
+
+/// The destination of every inline link, `[text](destination)`, with its 1-based line
+/// number. Links in code are skipped.
+pub fn markdown_links(markdown: &str) -> Vec<(usize, String)> {
+    let mut links = Vec::new();
+    let mut in_code_block = false;
+
+    for (index, line) in markdown.lines().enumerate() {
+        let trimmed = line.trim_start();
+        let is_fence = trimmed.starts_with("```") || trimmed.starts_with("~~~");
+        in_code_block ^= is_fence;
+        if in_code_block || is_fence {
+            continue;
+        }
+
+        let mut rest = line;
+        while let Some(pos) = rest.find(['`', '[', '\\']) {
+            rest = &rest[pos..];
+            let skip = if rest.starts_with('\\') {
+                rest.chars().nth(1).map_or(1, |c| 1 + c.len_utf8())
+            } else if rest.starts_with('`') {
+                code_span_len(rest)
+            } else if let Some((dest, len)) =
+                bracket_len(rest).and_then(|len| link_destination(rest, len).map(|d| (d, len)))
+            {
+                links.push((index + 1, dest.to_string()));
+                len + 1 + dest.len() + 1
+            } else {
+                1
+            };
+            rest = &rest[skip..];
+        }
+    }
+
+    links
+}
+
+/// The anchor of every heading in `markdown`, as GitHub generates them, with `-1`,
+/// `-2`, ... appended to repeats.
+pub fn heading_anchors(markdown: &str) -> HashSet<String> {
+    let mut anchors = HashSet::new();
+    let mut in_code_block = false;
+
+    for line in markdown.lines() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_code_block = !in_code_block;
+            continue;
+        }
+        let level = line.len() - line.trim_start_matches('#').len();
+        if in_code_block || !(1..=6).contains(&level) || !line[level..].starts_with(' ') {
+            continue;
+        }
+
+        let anchor = heading_anchor(line[level..].trim_end_matches('#'));
+        let mut unique = anchor.clone();
+        let mut repeat = 0;
+        while !anchors.insert(unique.clone()) {
+            repeat += 1;
+            unique = format!("{}-{}", anchor, repeat);
+        }
+    }
+
+    anchors
+}
+
+struct FileLink {
+    link: String,
+    /// Length of the markdown link it replaces
+    len: usize,
+}
+
+/// The inline link starting at `s`, whose text is `text_len` long including brackets,
+/// if it points at a markdown file or a heading in this one.
+fn markdown_file_link(s: &str, text_len: usize, dir: &str) -> Option<FileLink> {
+    let dest = link_destination(s, text_len)?;
+    let dest_len = dest.len();
+
+    let (path, fragment) = match dest.split_once('#') {
+        Some((path, fragment)) => (path, Some(fragment)),
+        None => (dest, None),
+    };
+    let page = if path.is_empty() && fragment.is_some() {
+        String::new()
+    } else if dest.contains("://") {
+        return None;
+    } else {
+        file_page(dir, path)?
+    };
+
+    let mut wikilink = format!("[[{}", page);
+    if let Some(fragment) = fragment {
+        wikilink.push('#');
+        wikilink.push_str(fragment);
+    }
+    wikilink.push('|');
+    wikilink.push_str(&s[1..text_len - 1].replace('|', "\\|"));
+    wikilink.push_str("]]");
+
+    Some(FileLink {
+        link: wikilink,
+        len: text_len + 1 + dest_len + 1,
+    })
+}
+
+/// The path, without `.md`, relative to the output root, of the markdown file at
+/// `path` relative to `dir`; `None` if `path` isn't a markdown file.
+fn file_page(dir: &str, path: &str) -> Option<String> {
+    let mut page: Vec<&str> = dir.split('/').filter(|part| !part.is_empty()).collect();
+    for part in path.strip_suffix(".md")?.split('/') {
+        match part {
+            "." | "" => {}
+            ".." => {
+                page.pop();
+            }
+            _ => page.push(part),
+        }
+    }
+    Some(page.join("/"))
+}
+
+/// The destination in parentheses after the link text starting at `s`, whose text is
+/// `text_len` long including brackets.
+fn link_destination(s: &str, text_len: usize) -> Option<&str> {
+    let after = s[text_len..].strip_prefix('(')?;
+
+    // Fragments may be heading text, which may hold balanced parentheses
+    let mut depth = 0;
+    let dest_len = after.find(|c| {
+        match c {
+            '(' => depth += 1,
+            ')' if depth == 0 => return true,
+            ')' => depth -= 1,
+            _ => {}
+        }
+        false
+    })?;
+    Some(&after[..dest_len])
+}
+
+/// Length of the code span starting at `s`, or of its opening backticks if unclosed.
+fn code_span_len(s: &str) -> usize {
+    let ticks = s.len() - s.trim_start_matches('`').len();
+    let fence = &s[..ticks];
+    match s[ticks..].find(fence) {
+        Some(end) => ticks + end + ticks,
+        None => ticks,
+    }
+}
+
+/// Length of the bracketed text starting at `s`, including both brackets.
+fn bracket_len(s: &str) -> Option<usize> {
+    let mut depth = 0;
+    let mut chars = s.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\\' => {
+                chars.next();
+            }
+            '`' => {
+                let span = code_span_len(&s[i..]);
+                for _ in 1..s[i..i + span].chars().count() {
+                    chars.next();
+                }
+            }
+            '[' => depth += 1,
+            ']' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i + 1);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+pub(crate) fn group_by_module(
+    crate_data: &Crate,
+    item_paths: &HashMap<Id, Vec<String>>,
+    options: &MarkdownOptions,
+) -> HashMap<String, Vec<(Id, Item)>> {
+    let mut modules: HashMap<String, Vec<(Id, Item)>> = HashMap::new();
+
+    for (id, item) in &crate_data.index {
+        if id == &crate_data.root {
+            continue;
+        }
+
+        // Skip if we can't format this item type
+        if !can_format_item(item) {
+            continue;
+        }
+
+        // Get the module path (all elements except the last one)
+        let module_path = if let Some(path) = item_paths.get(id) {
+            if path.len() > 1 {
+                path[..path.len() - 1].join("::")
+            } else {
+                continue; // Skip root-level items without module
+            }
+        } else {
+            continue; // Skip items without path info
+        };
+
+        if !options.includes(item, &item_paths[id][..item_paths[id].len() - 1]) {
+            continue;
+        }
+
+        if is_excluded(&item_paths[id], &options.exclude) {
+            continue;
+        }
+
+        modules
+            .entry(module_path)
+            .or_default()
+            .push((*id, item.clone()));
+    }
+
+    for items in modules.values_mut() {
+        match options.order {
+            ItemOrder::Alpha => items.sort_by(|(_, a), (_, b)| {
+                let name_a = a.name.as_deref().unwrap_or("");
+                let name_b = b.name.as_deref().unwrap_or("");
+                name_a.cmp(name_b)
+            }),
+            // Names break ties between items sharing a span, e.g. from one macro call
+            ItemOrder::Source => items.sort_by_cached_key(|(_, item)| {
+                let span = item
+                    .span
+                    .as_ref()
+                    .map(|span| (span.filename.clone(), span.begin));
+                (span.is_none(), span, item.name.clone())
+            }),
+        }
+    }
+
+    modules
+}
+
+/// Whether an item path, or any module enclosing it, matches an exclude pattern.
+///
+/// A module also counts as excluded when a pattern matches everything inside it,
+/// so `my_crate::internal::*` drops the `internal` module itself.
+fn is_excluded(path: &[String], patterns: &[String]) -> bool {
+    if patterns.is_empty() {
+        return false;
+    }
+
+    (1..=path.len()).any(|len| {
+        let prefix = path[..len].join("::");
+        let contents = format!("{}::", prefix);
+        patterns
+            .iter()
+            .any(|pattern| glob_matches(pattern, &prefix) || glob_matches(pattern, &contents))
+    })
+}
+
+/// Match `text` against a glob where `*` matches any run of characters and `?` one.
+pub fn glob_matches(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack = None;
+
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                Some((star, star_t)) => {
+                    p = star + 1;
+                    t = star_t + 1;
+                    backtrack = Some((star, star_t + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+fn can_format_item(item: &Item) -> bool {
+    matches!(
+        item.inner,
+        ItemEnum::Struct(_)
+            | ItemEnum::Enum(_)
+            | ItemEnum::Function(_)
+            | ItemEnum::Trait(_)
+            | ItemEnum::Module(_)
+            | ItemEnum::Constant { .. }
+            | ItemEnum::TypeAlias(_)
+            | ItemEnum::Static(_)
+            | ItemEnum::Union(_)
+            | ItemEnum::Macro(_)
+            | ItemEnum::ProcMacro(_)
+            | ItemEnum::TraitAlias(_)
+    )
+}
+
+/// Stable, machine-readable name for an item's kind.
+pub(crate) fn item_kind_name(item: &Item) -> Option<&'static str> {
+    Some(match &item.inner {
+        ItemEnum::Struct(_) => "struct",
+        ItemEnum::Enum(_) => "enum",
+        ItemEnum::Function(_) => "function",
+        ItemEnum::Trait(_) => "trait",
+        ItemEnum::Constant { .. } => "constant",
+        ItemEnum::TypeAlias(_) => "type_alias",
+        ItemEnum::Module(_) => "module",
+        ItemEnum::Static(_) => "static",
+        ItemEnum::Union(_) => "union",
+        ItemEnum::Macro(_) => "macro",
+        ItemEnum::ProcMacro(_) => "proc_macro",
+        ItemEnum::TraitAlias(_) => "trait_alias",
+        _ => return None,
+    })
+}
+
+/// [`item_kind_name`] for an item of another crate, known only by its summary.
+fn summary_kind_name(kind: rustdoc_types::ItemKind) -> Option<&'static str> {
+    use rustdoc_types::ItemKind;
+
+    Some(match kind {
+        ItemKind::Struct => "struct",
+        ItemKind::Enum => "enum",
+        ItemKind::Function => "function",
+        ItemKind::Trait => "trait",
+        ItemKind::Constant => "constant",
+        ItemKind::TypeAlias => "type_alias",
+        ItemKind::Module => "module",
+        ItemKind::Static => "static",
+        ItemKind::Union => "union",
+        ItemKind::Macro => "macro",
+        ItemKind::ProcAttribute | ItemKind::ProcDerive => "proc_macro",
+        ItemKind::TraitAlias => "trait_alias",
+        _ => return None,
+    })
+}
+
+fn generate_toc(modules: &HashMap<String, Vec<(Id, Item)>>, ctx: &RenderContext) -> String {
+    let crate_name = ctx.crate_name;
+    let mut toc = String::new();
+
+    // Sort modules alphabetically
+    let mut module_names: Vec<_> = modules.keys().collect();
+    module_names.sort();
+
+    for module_name in module_names {
+        let items = &modules[module_name];
+
+        // Get the last component of the module path for display
+        let display_name = module_name
+            .strip_prefix(&format!("{}::", crate_name))
+            .unwrap_or(module_name);
+
+        toc.push_str(&format!("- **{}**\n", display_name));
+
+        for (_id, item) in items {
+            if let Some(name) = &item.name {
+                let full_path: Vec<String> = module_name
+                    .split("::")
+                    .chain([name.as_str()])
+                    .map(String::from)
+                    .collect();
+                let anchor = heading_anchor(&ctx.item_heading(item, &full_path));
+                toc.push_str(&format!("  - [{}](#{})\n", name, anchor));
+            }
+        }
+    }
+
+    toc
+}
+
+fn generate_content(modules: &HashMap<String, Vec<(Id, Item)>>, ctx: &RenderContext) -> String {
+    let mut output = String::new();
+
+    // Sort modules alphabetically
+    let mut module_names: Vec<_> = modules.keys().collect();
+    module_names.sort();
+
+    for module_name in module_names {
+        let items = &modules[module_name];
+
+        // Module header
+        output.push_str(&format!("# Module: `{}`\n\n", module_name));
+
+        // Generate content for each item in the module
+        for (id, item) in items {
+            if let Some(section) = format_item_with_path(id, item, ctx) {
+                output.push_str(&section);
+                output.push_str("\n\n");
+            }
+        }
+
+        output.push_str("---\n\n");
+    }
+
+    output
+}
+
+fn format_item_with_path(item_id: &Id, item: &Item, ctx: &RenderContext) -> Option<String> {
+    let full_path = ctx.item_paths.get(item_id)?;
+
+    let mut output = format_item(item_id, item, ctx)?;
+
+    // Replace the simple name header with the full path
+    if output.starts_with("## ") {
+        if let Some(heading_end) = output.find("\n\n") {
+            output.replace_range(
+                ..heading_end,
+                &format!("## {}", ctx.item_heading(item, full_path)),
+            );
+        }
+    }
+
+    if let Some(src) = ctx.src_link(item) {
+        if let Some(heading_end) = output.find("\n\n") {
+            output.insert_str(heading_end, &format!(" [[src]]({})", src));
+        }
+    }
+
+    Some(output)
+}
+
+/// Tuple fields labelled by position, skipping those stripped from the JSON.
+fn tuple_fields<'a>(fields: &[Option<Id>], crate_data: &'a Crate) -> Vec<(String, &'a Item)> {
+    fields
+        .iter()
+        .enumerate()
+        .filter_map(|(i, id)| Some((i.to_string(), crate_data.index.get(id.as_ref()?)?)))
+        .collect()
+}
+
+/// A bullet for each variant field with its name in bold, its type, and its first doc
+/// line, indented by `indent`. Tuple fields are named by position, like `.0`.
+fn variant_field_list(fields: &[(String, &Item)], indent: &str) -> String {
+    let mut output = String::new();
+    for (name, field) in fields {
+        let field_type = if let ItemEnum::StructField(ty) = &field.inner {
+            format_type(ty)
+        } else {
+            "?".to_string()
+        };
+        output.push_str(&format!("{}- **{}**: `{}`", indent, name, field_type));
+        if let Some(line) = field.docs.as_deref().and_then(|docs| docs.lines().next()) {
+            if !line.trim().is_empty() {
+                output.push_str(&format!(" - {}", line.trim()));
+            }
+        }
+        output.push('\n');
+    }
+    output
+}
+
+/// The visibility an item is declared with, e.g. `pub ` or `pub(crate) `; empty when
+/// private.
+fn visibility_prefix(item: &Item) -> String {
+    match &item.visibility {
+        Visibility::Public => "pub ".to_string(),
+        Visibility::Default => String::new(),
+        Visibility::Crate => "pub(crate) ".to_string(),
+        Visibility::Restricted { path, .. } => match path.strip_prefix("::") {
+            Some(path) => format!("pub(in crate::{}) ", path),
+            None => format!("pub(in {}) ", path),
+        },
+    }
+}
+
+/// How widely an item in `module` is visible. Items declared without `pub` are
+/// private, though rustdoc records them as `pub(in module)`.
+fn visibility_level(item: &Item, module: &[String]) -> VisibilityLevel {
+    match &item.visibility {
+        Visibility::Public => VisibilityLevel::Public,
+        Visibility::Crate => VisibilityLevel::Crate,
+        Visibility::Restricted { path, .. }
+            if path
+                .split("::")
+                .filter(|part| !part.is_empty())
+                .ne(module.iter().skip(1).map(String::as_str)) =>
+        {
+            VisibilityLevel::Restricted
+        }
+        Visibility::Restricted { .. } | Visibility::Default => VisibilityLevel::Private,
+    }
+}
+
+/// The visibility an item in `module` is declared with, like [`visibility_prefix`],
+/// but empty for private items.
+fn declared_visibility(item: &Item, module: &[String]) -> String {
+    match visibility_level(item, module) {
+        VisibilityLevel::Private => String::new(),
+        _ => visibility_prefix(item),
+    }
+}
+
+/// The visibility an item in `module` is declared with as a word, e.g. `pub(crate)`,
+/// or `private`.
+fn visibility_label(item: &Item, module: &[String]) -> String {
+    match declared_visibility(item, module).trim_end() {
+        "" => "private".to_string(),
+        visibility => visibility.to_string(),
+    }
+}
+
+pub(crate) fn is_public(item: &Item) -> bool {
+    matches!(item.visibility, Visibility::Public)
+}
+
+fn format_item(item_id: &rustdoc_types::Id, item: &Item, ctx: &RenderContext) -> Option<String> {
+    let crate_data = ctx.crate_data;
+    let name = item.name.as_ref()?;
+    let module = ctx
+        .item_paths
+        .get(item_id)
+        .map_or(&[][..], |path| &path[..path.len().saturating_sub(1)]);
+    let mut output = String::new();
+
+    match &item.inner {
+        ItemEnum::Struct(s) => {
+            output.push_str(&format!("## {}\n\n", name));
+            output.push_str("*Struct*\n\n");
+
+            let (inherent_impls, trait_impls, derived_impls) =
+                collect_impls_for_type(item_id, crate_data);
+            output.push_str(&format_derives(&derived_impls));
+
+            if let Some(docs) = ctx.docs(item) {
+                output.push_str(&format!("{}\n\n", docs));
+            }
+
+            output.push_str(&format_type_generics(&s.generics));
+
+            match &s.kind {
+                rustdoc_types::StructKind::Plain { fields, .. } => {
+                    if !fields.is_empty() {
+                        output.push_str("**Fields:**\n");
+                        for field_id in fields {
+                            if let Some(field) = crate_data
+                                .index
+                                .get(field_id)
+                                .filter(|field| ctx.options.includes(field, module))
+                            {
+                                if let Some(field_name) = &field.name {
+                                    let field_type = if let ItemEnum::StructField(ty) = &field.inner
+                                    {
+                                        format_type(ty)
+                                    } else {
+                                        "?".to_string()
+                                    };
+                                    output.push_str(&format!(
+                                        "- `{}{}: {}`",
+                                        ctx.shown_visibility(field, module),
+                                        field_name,
+                                        field_type
+                                    ));
+                                    if let Some(docs) = &field.docs {
+                                        let first_line = docs.lines().next().unwrap_or("").trim();
+                                        if !first_line.is_empty() {
+                                            output.push_str(&format!(" - {}", first_line));
+                                        }
+                                    }
+                                    output.push('\n');
+                                }
+                            }
+                        }
+                        output.push('\n');
+                    }
+                }
+                rustdoc_types::StructKind::Tuple(fields) => {
+                    // Stripped fields keep their place, so positions match the source
+                    let declared: Vec<String> = fields
+                        .iter()
+                        .map(|field_id| {
+                            match field_id
+                                .and_then(|id| crate_data.index.get(&id))
+                                .filter(|field| ctx.options.includes(field, module))
+                            {
+                                Some(
+                                    field @ Item {
+                                        inner: ItemEnum::StructField(ty),
+                                        ..
+                                    },
+                                ) => format!(
+                                    "{}{}",
+                                    declared_visibility(field, module),
+                                    format_type(ty)
+                                ),
+                                _ => "/* private field */".to_string(),
+                            }
+                        })
+                        .collect();
+                    output.push_str(&format!(
+                        "```rust\n{}struct {}{}({}){};\n```\n\n",
+                        declared_visibility(item, module),
+                        name,
+                        format_generic_params(&s.generics),
+                        declared.join(", "),
+                        format_where_clause(&s.generics)
+                    ));
+
+                    let mut fields = tuple_fields(fields, crate_data);
+                    fields.retain(|(_, field)| ctx.options.includes(field, module));
+                    if !fields.is_empty() {
+                        output.push_str("**Fields:**\n");
+                        for (position, field) in fields {
+                            let ItemEnum::StructField(ty) = &field.inner else {
+                                continue;
+                            };
+                            output.push_str(&format!(
+                                "- **.{}**: `{}` ({})",
+                                position,
+                                format_type(ty),
+                                visibility_label(field, module)
+                            ));
+                            if let Some(docs) = &field.docs {
+                                let first_line = docs.lines().next().unwrap_or("").trim();
+                                if !first_line.is_empty() {
+                                    output.push_str(&format!(" - {}", first_line));
+                                }
+                            }
+                            output.push('\n');
+                        }
+                        output.push('\n');
+                    }
+                }
+                rustdoc_types::StructKind::Unit => {
+                    output.push_str(&format!(
+                        "```rust\n{}struct {}{}{};\n```\n\n",
+                        declared_visibility(item, module),
+                        name,
+                        format_generic_params(&s.generics),
+                        format_where_clause(&s.generics)
+                    ));
+                }
+            }
+
+            output.push_str(&format_inherent_impls(
+                &inherent_impls,
+                crate_data,
+                ctx.options,
+                module,
+            ));
+
+            output.push_str(&format_trait_impls(
+                &trait_impls,
+                crate_data,
+                ctx.options.collapsible,
+            ));
+        }
+        ItemEnum::Enum(e) => {
+            output.push_str(&format!("## {}\n\n", name));
+            output.push_str("*Enum*\n\n");
+
+            let (inherent_impls, trait_impls, derived_impls) =
+                collect_impls_for_type(item_id, crate_data);
+            output.push_str(&format_derives(&derived_impls));
+
+            if let Some(docs) = ctx.docs(item) {
+                output.push_str(&format!("{}\n\n", docs));
+            }
+
+            output.push_str(&format_type_generics(&e.generics));
+
+            if !e.variants.is_empty() {
+                output.push_str("**Variants:**\n");
+                for variant_id in &e.variants {
+                    if let Some(variant) = crate_data.index.get(variant_id) {
+                        if let Some(variant_name) = &variant.name {
+                            output.push_str("- `");
+                            output.push_str(variant_name);
+                            if let ItemEnum::Variant(rustdoc_types::Variant {
+                                discriminant: Some(discriminant),
+                                ..
+                            }) = &variant.inner
+                            {
+                                output.push_str(&format!(" = {}", discriminant.expr));
+                            }
+                            output.push('`');
+
+                            if let Some(docs) = &variant.docs {
+                                let first_line = docs.lines().next().unwrap_or("").trim();
+                                if !first_line.is_empty() {
+                                    output.push_str(&format!(" - {}", first_line));
+                                }
+                            }
+                            output.push('\n');
+
+                            if let ItemEnum::Variant(v) = &variant.inner {
+                                let fields = match &v.kind {
+                                    rustdoc_types::VariantKind::Plain => Vec::new(),
+                                    rustdoc_types::VariantKind::Tuple(fields) => {
+                                        tuple_fields(fields, crate_data)
+                                            .into_iter()
+                                            .map(|(position, f)| (format!(".{}", position), f))
+                                            .collect()
+                                    }
+                                    rustdoc_types::VariantKind::Struct { fields, .. } => fields
+                                        .iter()
+                                        .filter_map(|id| crate_data.index.get(id))
+                                        .filter_map(|f| Some((f.name.clone()?, f)))
+                                        .collect(),
+                                };
+                                output.push_str(&variant_field_list(&fields, "  "));
+                            }
+                        }
+                    }
+                }
+                output.push('\n');
+            }
+
+            output.push_str(&format_inherent_impls(
+                &inherent_impls,
+                crate_data,
+                ctx.options,
+                module,
+            ));
+
+            output.push_str(&format_trait_impls(
+                &trait_impls,
+                crate_data,
+                ctx.options.collapsible,
+            ));
+        }
+        ItemEnum::Function(f) => {
+            output.push_str(&format!("## {}\n\n", name));
+            output.push_str("*Function*\n\n");
+
+            let docs = ctx.docs(item);
+            if let Some(docs) = &docs {
+                output.push_str(&format!("{}\n\n", docs));
+            }
+
+            output.push_str("```rust\n");
+            output.push_str(&format_function_signature(name, f));
+            for (i, predicate) in format_where_predicates(&f.generics).iter().enumerate() {
+                if i == 0 {
+                    output.push_str("\nwhere");
+                }
+                output.push_str(&format!("\n    {},", predicate));
+            }
+            output.push_str("\n```\n\n");
+            if ctx.options.extract_sections {
+                output.push_str(&extracted_sections(docs.as_deref().unwrap_or("")));
+            }
+
+            let signature_types: Vec<_> = f
+                .sig
+                .inputs
+                .iter()
+                .map(|(_, ty)| ty)
+                .chain(&f.sig.output)
+                .collect();
+            output.push_str(&ctx.signature_types(&signature_types));
+        }
+        ItemEnum::Trait(t) => {
+            output.push_str(&format!("## {}\n\n", name));
+            output.push_str(if t.is_unsafe {
+                "*Unsafe Trait*\n\n"
+            } else {
+                "*Trait*\n\n"
+            });
+            if is_sealed_trait(t, crate_data) {
+                output.push_str(
+                    "*This trait is sealed and cannot be implemented outside this crate.*\n\n",
+                );
+            }
+
+            if let Some(docs) = ctx.docs(item) {
+                output.push_str(&format!("{}\n\n", docs));
+            }
+
+            // A plain `trait Name` would add nothing over the heading
+            let where_predicates = format_where_predicates(&t.generics);
+            let declared_params = format_generic_params(&t.generics);
+            if !t.bounds.is_empty() || !declared_params.is_empty() || !where_predicates.is_empty() {
+                output.push_str(&format!(
+                    "```rust\n{}trait {}{}{}",
+                    if t.is_unsafe { "unsafe " } else { "" },
+                    name,
+                    declared_params,
+                    format_supertraits(&t.bounds)
+                ));
+                if where_predicates.is_empty() {
+                    output.push(' ');
+                } else {
+                    output.push_str("\nwhere");
+                    for predicate in &where_predicates {
+                        output.push_str(&format!("\n    {},", predicate));
+                    }
+                    output.push('\n');
+                }
+                output.push_str("{ ... }\n```\n\n");
+            }
+
+            let trait_items: Vec<&Item> = t
+                .items
+                .iter()
+                .filter_map(|id| crate_data.index.get(id))
+                .collect();
+
+            output.push_str(&format_associated_items(&trait_items));
+
+            // Like rustdoc, methods without a default body come first
+            let (required, provided): (Vec<&Item>, Vec<&Item>) = trait_items
+                .iter()
+                .copied()
+                .filter(|item| matches!(item.inner, ItemEnum::Function(_)))
+                .partition(|item| matches!(&item.inner, ItemEnum::Function(f) if !f.has_body));
+
+            for (title, methods) in [
+                ("Required Methods", required),
+                ("Provided Methods", provided),
+            ] {
+                if methods.is_empty() {
+                    continue;
+                }
+                output.push_str(&format!("**{}:**\n\n", title));
+                for method in methods {
+                    if let Some(method_name) = &method.name {
+                        output.push_str(&format!("- `{}`", method_name));
+                        if let Some(method_docs) = &method.docs {
+                            output.push_str(&format!(
+                                ": {}",
+                                method_docs.lines().next().unwrap_or("").trim()
+                            ));
+                        }
+                        output.push('\n');
+                    }
+                }
+                output.push('\n');
+            }
+        }
+        ItemEnum::Module(_) => {
+            output.push_str(&format!("## Module: {}\n\n", name));
+
+            if let Some(docs) = ctx.docs(item) {
+                output.push_str(&format!("{}\n\n", docs));
+            }
+        }
+        ItemEnum::Constant { type_, const_ } => {
+            output.push_str(&format!("## {}\n\n", name));
+            output.push_str("*Constant*\n\n");
+
+            if let Some(docs) = ctx.docs(item) {
+                output.push_str(&format!("{}\n\n", docs));
+            }
+
+            // Like rustdoc, only literals are shown; other initializers become `_`
+            let value = if const_.is_literal {
+                const_.expr.as_str()
+            } else {
+                "_"
+            };
+            output.push_str("```rust\n");
+            output.push_str(&format!(
+                "const {}: {} = {};",
+                name,
+                format_type(type_),
+                value
+            ));
+            output.push_str("\n```\n\n");
+            output.push_str(&ctx.signature_types(&[type_]));
+        }
+        ItemEnum::TypeAlias(ta) => {
+            output.push_str(&format!("## {}\n\n", name));
+            output.push_str("*Type Alias*\n\n");
+
+            if let Some(docs) = ctx.docs(item) {
+                output.push_str(&format!("{}\n\n", docs));
+            }
+
+            output.push_str("```rust\n");
+            output.push_str(&format!(
+                "type {}{}",
+                name,
+                format_generic_params(&ta.generics)
+            ));
+            for (i, predicate) in format_where_predicates(&ta.generics).iter().enumerate() {
+                if i == 0 {
+                    output.push_str("\nwhere");
+                }
+                output.push_str(&format!("\n    {},", predicate));
+            }
+            output.push_str(&format!(" = {};", format_type(&ta.type_)));
+            output.push_str("\n```\n\n");
+            output.push_str(&ctx.signature_types(&[&ta.type_]));
+        }
+        ItemEnum::Static(s) => {
+            output.push_str(&format!("## {}\n\n", name));
+            output.push_str("*Static*\n\n");
+
+            if let Some(docs) = ctx.docs(item) {
+                output.push_str(&format!("{}\n\n", docs));
+            }
+
+            output.push_str("```rust\n");
+            output.push_str(&format!(
+                "{}static {}{}: {}",
+                if s.is_unsafe { "unsafe " } else { "" },
+                if s.is_mutable { "mut " } else { "" },
+                name,
+                format_type(&s.type_)
+            ));
+            output.push_str("\n```\n\n");
+            output.push_str(&ctx.signature_types(&[&s.type_]));
+        }
+        ItemEnum::Union(u) => {
+            output.push_str(&format!("## {}\n\n", name));
+            output.push_str("*Union*\n\n");
+
+            let (inherent_impls, trait_impls, derived_impls) =
+                collect_impls_for_type(item_id, crate_data);
+            output.push_str(&format_derives(&derived_impls));
+
+            if let Some(docs) = ctx.docs(item) {
+                output.push_str(&format!("{}\n\n", docs));
+            }
+
+            output.push_str(&format_type_generics(&u.generics));
+
+            if !u.fields.is_empty() {
+                output.push_str("**Fields:**\n");
+                for field_id in &u.fields {
+                    if let Some(field) = crate_data
+                        .index
+                        .get(field_id)
+                        .filter(|field| ctx.options.includes(field, module))
+                    {
+                        if let Some(field_name) = &field.name {
+                            let field_type = if let ItemEnum::StructField(ty) = &field.inner {
+                                format_type(ty)
+                            } else {
+                                "?".to_string()
+                            };
+                            output.push_str(&format!(
+                                "- `{}{}: {}`",
+                                ctx.shown_visibility(field, module),
+                                field_name,
+                                field_type
+                            ));
+                            if let Some(docs) = &field.docs {
+                                let first_line = docs.lines().next().unwrap_or("").trim();
+                                if !first_line.is_empty() {
+                                    output.push_str(&format!(" - {}", first_line));
+                                }
+                            }
+                            output.push('\n');
+                        }
+                    }
+                }
+                output.push('\n');
+            }
+
+            output.push_str(&format_inherent_impls(
+                &inherent_impls,
+                crate_data,
+                ctx.options,
+                module,
+            ));
+
+            output.push_str(&format_trait_impls(
+                &trait_impls,
+                crate_data,
+                ctx.options.collapsible,
+            ));
+        }
+        ItemEnum::Macro(m) => {
+            output.push_str(&format!("## {}\n\n", name));
+            output.push_str("*Declarative Macro*\n\n");
+
+            if let Some(docs) = ctx.docs(item) {
+                output.push_str(&format!("{}\n\n", docs));
+            }
+
+            if !m.is_empty() {
+                output.push_str("```rust\n");
+                output.push_str(m);
+                output.push_str("\n```\n\n");
+            } else {
+                output.push_str("```rust\n");
+                output.push_str(&format!("{}!(...)", name));
+                output.push_str("\n```\n\n");
+            }
+        }
+        ItemEnum::ProcMacro(pm) => {
+            output.push_str(&format!("## {}\n\n", name));
+
+            let kind_str = match &pm.kind {
+                rustdoc_types::MacroKind::Bang => "Function-like Macro",
+                rustdoc_types::MacroKind::Attr => "Attribute Macro",
+                rustdoc_types::MacroKind::Derive => "Derive Macro",
+            };
+            output.push_str(&format!("*{}*\n\n", kind_str));
+
+            if let Some(docs) = ctx.docs(item) {
+                output.push_str(&format!("{}\n\n", docs));
+            }
+
+            output.push_str("```rust\n");
+            match &pm.kind {
+                rustdoc_types::MacroKind::Bang => {
+                    output.push_str(&format!("{}!(...)", name));
+                }
+                rustdoc_types::MacroKind::Attr => {
+                    output.push_str(&format!("#[{}]", name));
+                }
+                rustdoc_types::MacroKind::Derive => {
+                    output.push_str(&format!("#[derive({})]", name));
+                }
+            }
+            output.push_str("\n```\n\n");
+        }
+        ItemEnum::TraitAlias(ta) => {
+            output.push_str(&format!("## {}\n\n", name));
+            output.push_str("*Trait Alias*\n\n");
+
+            if let Some(docs) = ctx.docs(item) {
+                output.push_str(&format!("{}\n\n", docs));
+            }
+
+            output.push_str("```rust\n");
+            output.push_str(&format!("trait {}", name));
+
+            if !ta.generics.params.is_empty() {
+                output.push('<');
+                let params: Vec<String> = ta
+                    .generics
+                    .params
+                    .iter()
+                    .map(format_generic_param)
+                    .collect();
+                output.push_str(&params.join(", "));
+                output.push('>');
+            }
+
+            output.push_str(" = ");
+
+            let bounds: Vec<String> = ta.params.iter().map(format_generic_bound).collect();
+
+            output.push_str(&bounds.join(" + "));
+            output.push_str("\n```\n\n");
+        }
+        _ => {
+            return None;
+        }
+    }
+
+    // Annotations sit directly under the item heading
+    let annotations = format_item_annotations(item, ctx.options);
+    if !annotations.is_empty() {
+        if let Some(heading_end) = output.find("\n\n") {
+            output.insert_str(heading_end + 2, &annotations);
+        }
+    }
+
+    Some(output)
+}
+
+fn format_item_annotations(item: &Item, options: &MarkdownOptions) -> String {
+    let mut output = String::new();
+
+    match item_stability(item) {
+        Some(Stability::Stable { since })
+            if options
+                .since
+                .as_deref()
+                .is_none_or(|threshold| version_key(&since) > version_key(threshold)) =>
+        {
+            output.push_str(&format!("*Stable since {}*\n\n", since));
+        }
+        Some(Stability::Unstable { feature }) => {
+            output.push_str(&format!("*Unstable (feature `{}`)*\n\n", feature));
+        }
+        _ => {}
+    }
+
+    if let Some(deprecation) = &item.deprecation {
+        output.push_str("> ⚠️ **Deprecated**");
+        if let Some(note) = &deprecation.note {
+            output.push_str(&format!(": {}", note));
+        }
+        if let Some(since) = &deprecation.since {
+            output.push_str(&format!(" (since {})", since));
+        }
+        output.push_str("\n\n");
+    }
+
+    if let Some(cfg) = item_cfg_prose(item) {
+        output.push_str(&format!("*Available on **{}** only.*\n\n", cfg));
+    }
+
+    let must_use = item.attrs.iter().find_map(|attr| match attr {
+        rustdoc_types::Attribute::MustUse { reason } => Some(reason),
+        _ => None,
+    });
+    match must_use {
+        Some(Some(reason)) => output.push_str(&format!("*`#[must_use]`: {}*\n\n", reason)),
+        Some(None) => output.push_str("*`#[must_use]`*\n\n"),
+        None => {}
+    }
+
+    let aliases = item_aliases(item);
+    if !aliases.is_empty() {
+        let aliases: Vec<String> = aliases.iter().map(|alias| format!("`{}`", alias)).collect();
+        output.push_str(&format!("*Aliases: {}*\n\n", aliases.join(", ")));
+    }
+
+    output
+}
+
+enum Stability {
+    Stable { since: String },
+    Unstable { feature: String },
+}
+
+/// Stability from `#[stable]` and `#[unstable]` attributes, as used by the standard
+/// library and other crates built with `staged_api`.
+///
+/// rustdoc reports them as rustc's parsed attribute, e.g. `#[attr = Stability
+/// {stability: Stability {level: Stable {since: Version(RustcVersion { major: 1, minor:
+/// 2, patch: 0 })}, feature: "x"}}]`; the attributes as written are understood too.
+fn item_stability(item: &Item) -> Option<Stability> {
+    item.attrs.iter().find_map(|attr| {
+        let rustdoc_types::Attribute::Other(text) = attr else {
+            return None;
+        };
+        let quoted_after = |key: &str| {
+            let rest = &text[text.find(key)? + key.len()..];
+            let rest = rest.trim_start_matches([' ', '=', ':']).strip_prefix('"')?;
+            Some(rest[..rest.find('"')?].to_string())
+        };
+
+        if text.starts_with("#[attr = Stability") {
+            if text.contains("level: Unstable") {
+                return Some(Stability::Unstable {
+                    feature: quoted_after("feature")?,
+                });
+            }
+            let number = |key: &str| {
+                let rest = &text[text.find(key)? + key.len()..];
+                let digits: String = rest.chars().take_while(char::is_ascii_digit).collect();
+                (!digits.is_empty()).then_some(digits)
+            };
+            let since = format!(
+                "{}.{}.{}",
+                number("major: ")?,
+                number("minor: ")?,
+                number("patch: ")?
+            );
+            return Some(Stability::Stable { since });
+        }
+        if text.starts_with("#[unstable(") {
+            return Some(Stability::Unstable {
+                feature: quoted_after("feature")?,
+            });
+        }
+        if text.starts_with("#[stable(") {
+            return Some(Stability::Stable {
+                since: quoted_after("since")?,
+            });
+        }
+        None
+    })
+}
+
+/// A version's numeric components, for comparing `1.10.0` after `1.9.0`.
+fn version_key(version: &str) -> Vec<u64> {
+    version
+        .split('.')
+        .map(|part| part.parse().unwrap_or(0))
+        .collect()
+}
+
+/// Search aliases from `#[doc(alias = "...")]` and `#[doc(alias("...", ...))]`.
+fn item_aliases(item: &Item) -> Vec<String> {
+    item.attrs
+        .iter()
+        .filter_map(|attr| match attr {
+            rustdoc_types::Attribute::Other(text) => {
+                text.strip_prefix("#[doc(alias")?.strip_suffix(")]")
+            }
+            _ => None,
+        })
+        .flat_map(|args| {
+            let args = args.trim_start();
+            let list = args
+                .strip_prefix('=')
+                .or_else(|| args.strip_prefix('(')?.strip_suffix(')'))
+                .unwrap_or("");
+            list.split(',')
+                .filter_map(|alias| {
+                    let alias = alias.trim().strip_prefix('"')?.strip_suffix('"')?;
+                    Some(alias.to_string())
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+/// Readable form of an item's `#[doc(cfg(...))]` gate, e.g. "Unix and feature `x`".
+fn item_cfg_prose(item: &Item) -> Option<String> {
+    let predicate = item.attrs.iter().find_map(|attr| match attr {
+        rustdoc_types::Attribute::Other(text) => {
+            text.strip_prefix("#[doc(cfg(")?.strip_suffix("))]")
+        }
+        _ => None,
+    })?;
+
+    let mut parser = CfgParser { rest: predicate };
+    let cfg = parser.parse()?;
+    Some(cfg.prose(false))
+}
+
+/// A parsed `cfg` predicate.
+enum Cfg {
+    Option { name: String, value: Option<String> },
+    All(Vec<Cfg>),
+    Any(Vec<Cfg>),
+    Not(Box<Cfg>),
+}
+
+impl Cfg {
+    /// Phrase the predicate the way docs.rs does; `nested` wraps combinations in parentheses.
+    fn prose(&self, nested: bool) -> String {
+        let (parts, separator) = match self {
+            Cfg::Option { name, value } => return cfg_option_prose(name, value.as_deref()),
+            Cfg::Not(inner) => {
+                return match inner.as_ref() {
+                    Cfg::Option { .. } => format!("non-{}", inner.prose(true)),
+                    _ => format!("not ({})", inner.prose(false)),
+                };
+            }
+            Cfg::All(parts) => (parts, " and "),
+            Cfg::Any(parts) => (parts, " or "),
+        };
+
+        let phrases: Vec<String> = parts.iter().map(|part| part.prose(true)).collect();
+        if nested && phrases.len() > 1 {
+            format!("({})", phrases.join(separator))
+        } else {
+            phrases.join(separator)
+        }
+    }
+}
+
+fn cfg_option_prose(name: &str, value: Option<&str>) -> String {
+    match (name, value) {
+        ("feature", Some(feature)) => format!("feature `{}`", feature),
+        ("unix", None) => "Unix".to_string(),
+        ("windows", None) => "Windows".to_string(),
+        ("target_os" | "target_family", Some(os)) => match os {
+            "linux" => "Linux",
+            "macos" => "macOS",
+            "ios" => "iOS",
+            "windows" => "Windows",
+            "android" => "Android",
+            "freebsd" => "FreeBSD",
+            "wasi" => "WASI",
+            "unix" => "Unix",
+            "wasm" => "WebAssembly",
+            other => other,
+        }
+        .to_string(),
+        (name, Some(value)) => format!("`{} = \"{}\"`", name, value),
+        (name, None) => format!("`{}`", name),
+    }
+}
+
+/// Recursive-descent parser for `cfg` predicates like `all(unix, feature = "x")`.
+struct CfgParser<'a> {
+    rest: &'a str,
+}
+
+impl<'a> CfgParser<'a> {
+    fn parse(&mut self) -> Option<Cfg> {
+        let name = self.identifier()?;
+
+        if self.eat('(') {
+            let mut parts = Vec::new();
+            while !self.eat(')') {
+                parts.push(self.parse()?);
+                self.eat(',');
+            }
+            return match name {
+                "all" => Some(Cfg::All(parts)),
+                "any" => Some(Cfg::Any(parts)),
+                "not" if parts.len() == 1 => parts.pop().map(|part| Cfg::Not(Box::new(part))),
+                _ => None,
+            };
+        }
+
+        let value = if self.eat('=') {
+            self.rest = self.rest.trim_start().strip_prefix('"')?;
+            let (value, rest) = self.rest.split_once('"')?;
+            self.rest = rest;
+            Some(value.to_string())
+        } else {
+            None
+        };
+
+        Some(Cfg::Option {
+            name: name.to_string(),
+            value,
+        })
+    }
+
+    fn identifier(&mut self) -> Option<&'a str> {
+        let rest = self.rest.trim_start();
+        let len = rest
+            .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .unwrap_or(rest.len());
+        let (ident, rest_after) = rest.split_at(len);
+        self.rest = rest_after;
+        (!ident.is_empty()).then_some(ident)
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        match self.rest.trim_start().strip_prefix(c) {
+            Some(rest) => {
+                self.rest = rest;
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+fn format_generic_param(param: &rustdoc_types::GenericParamDef) -> String {
+    match &param.kind {
+        rustdoc_types::GenericParamDefKind::Lifetime { outlives } => {
+            // Lifetime names already include the ' prefix in rustdoc JSON
+            if outlives.is_empty() {
+                param.name.clone()
+            } else {
+                format!("{}: {}", param.name, outlives.join(" + "))
+            }
+        }
+        rustdoc_types::GenericParamDefKind::Type {
+            bounds, default, ..
+        } => {
+            let mut result = param.name.clone();
+            if !bounds.is_empty() {
+                let bounds: Vec<String> = bounds.iter().map(format_generic_bound).collect();
+                result.push_str(&format!(": {}", bounds.join(" + ")));
+            }
+            if let Some(default) = default {
+                result.push_str(&format!(" = {}", format_type(default)));
+            }
+            result
+        }
+        rustdoc_types::GenericParamDefKind::Const { type_, default } => {
+            let mut result = format!("const {}: {}", param.name, format_type(type_));
+            if let Some(default) = default {
+                result.push_str(&format!(" = {}", default));
+            }
+            result
+        }
+    }
+}
+
+/// `<...>` list of the declared generic parameters, or empty if there are none.
+///
+/// Compiler-generated lifetimes and `impl Trait` argument types are left out.
+fn format_generic_params(generics: &rustdoc_types::Generics) -> String {
+    let params: Vec<String> = generics
+        .params
+        .iter()
+        .filter(|p| match &p.kind {
+            rustdoc_types::GenericParamDefKind::Lifetime { .. } => !is_synthetic_lifetime(&p.name),
+            rustdoc_types::GenericParamDefKind::Type { is_synthetic, .. } => !is_synthetic,
+            rustdoc_types::GenericParamDefKind::Const { .. } => true,
+        })
+        .map(format_generic_param)
+        .collect();
+
+    if params.is_empty() {
+        String::new()
+    } else {
+        format!("<{}>", params.join(", "))
+    }
+}
+
+/// `**Generic Parameters:**` and `**Where Clauses:**` lists for a type definition.
+fn format_type_generics(generics: &rustdoc_types::Generics) -> String {
+    let mut output = String::new();
+
+    let params: Vec<_> = generics
+        .params
+        .iter()
+        .filter(|p| {
+            !matches!(&p.kind, rustdoc_types::GenericParamDefKind::Lifetime { .. })
+                || !is_synthetic_lifetime(&p.name)
+        })
+        .collect();
+    if !params.is_empty() {
+        output.push_str("**Generic Parameters:**\n");
+        for param in params {
+            output.push_str(&format!("- {}\n", format_generic_param(param)));
+        }
+        output.push('\n');
+    }
+
+    let predicates = format_where_predicates(generics);
+    if !predicates.is_empty() {
+        output.push_str("**Where Clauses:**\n");
+        for predicate in predicates {
+            output.push_str(&format!("- `{}`\n", predicate));
+        }
+        output.push('\n');
+    }
+
+    output
+}
+
+/// Each predicate of a `where` clause, e.g. `T: Clone + Send`.
+fn format_where_predicates(generics: &rustdoc_types::Generics) -> Vec<String> {
+    use rustdoc_types::WherePredicate;
+
+    generics
+        .where_predicates
+        .iter()
+        .map(|predicate| match predicate {
+            WherePredicate::BoundPredicate {
+                type_,
+                bounds,
+                generic_params,
+            } => {
+                let bounds: Vec<String> = bounds.iter().map(format_generic_bound).collect();
+                let binder = if generic_params.is_empty() {
+                    String::new()
+                } else {
+                    let params: Vec<String> =
+                        generic_params.iter().map(format_generic_param).collect();
+                    format!("for<{}> ", params.join(", "))
+                };
+                format!("{}{}: {}", binder, format_type(type_), bounds.join(" + "))
+            }
+            WherePredicate::LifetimePredicate { lifetime, outlives } => {
+                format!("{}: {}", lifetime, outlives.join(" + "))
+            }
+            WherePredicate::EqPredicate { lhs, rhs } => {
+                format!("{} = {}", format_type(lhs), format_term(rhs))
+            }
+        })
+        .collect()
+}
+
+/// Single-line ` where ...` suffix, or empty if there are no predicates.
+fn format_where_clause(generics: &rustdoc_types::Generics) -> String {
+    let predicates = format_where_predicates(generics);
+    if predicates.is_empty() {
+        String::new()
+    } else {
+        format!(" where {}", predicates.join(", "))
+    }
+}
+
+/// `impl` header for blocks that constrain their generics, e.g. `impl<T: Clone> Foo<T>`,
+/// that are `unsafe impl`s, or whose trait or type has arguments of note, like
+/// `impl<T> From<T> for Foo<T>` or `impl Foo<u8>`.
+///
+/// Unconstrained impls like `impl<T> Foo<T>` add nothing over the type itself.
+fn format_impl_header(impl_block: &rustdoc_types::Impl, crate_data: &Crate) -> Option<String> {
+    let has_bounds = impl_block.generics.params.iter().any(|p| match &p.kind {
+        rustdoc_types::GenericParamDefKind::Lifetime { outlives } => !outlives.is_empty(),
+        rustdoc_types::GenericParamDefKind::Type { bounds, .. } => !bounds.is_empty(),
+        rustdoc_types::GenericParamDefKind::Const { .. } => false,
+    });
+    let has_trait_args = impl_block
+        .trait_
+        .as_ref()
+        .and_then(|trait_| trait_.args.as_deref())
+        .is_some_and(|args| !format_generic_args(args).is_empty());
+    if !has_bounds
+        && !has_trait_args
+        && impl_block.generics.where_predicates.is_empty()
+        && is_plain_self_type(impl_block)
+        && !is_unsafe_impl(impl_block, crate_data)
+    {
+        return None;
+    }
+
+    Some(format_impl_signature(impl_block, crate_data))
+}
+
+/// Whether an impl is for its type with the impl's own generic parameters, in order,
+/// as in `impl<T, U> Pair<T, U>`, rather than for one instantiation like `Pair<u8, T>`.
+fn is_plain_self_type(impl_block: &rustdoc_types::Impl) -> bool {
+    let rustdoc_types::Type::ResolvedPath(path) = &impl_block.for_ else {
+        return false;
+    };
+    let params: Vec<&str> = impl_block
+        .generics
+        .params
+        .iter()
+        .filter(|p| match &p.kind {
+            rustdoc_types::GenericParamDefKind::Lifetime { .. } => !is_synthetic_lifetime(&p.name),
+            rustdoc_types::GenericParamDefKind::Type { is_synthetic, .. } => !is_synthetic,
+            rustdoc_types::GenericParamDefKind::Const { .. } => true,
+        })
+        .map(|p| p.name.as_str())
+        .collect();
+    let args = path
+        .args
+        .as_deref()
+        .map(format_generic_args)
+        .unwrap_or_default();
+    if params.is_empty() {
+        args.is_empty()
+    } else {
+        args == format!("<{}>", params.join(", "))
+    }
+}
+
+/// Whether an impl is an `unsafe impl`.
+///
+/// rustdoc doesn't always set `is_unsafe` on impls, but implementing an unsafe trait
+/// requires `unsafe impl`, so the trait's own safety decides for written impls.
+fn is_unsafe_impl(impl_block: &rustdoc_types::Impl, crate_data: &Crate) -> bool {
+    if impl_block.is_unsafe {
+        return true;
+    }
+    if impl_block.is_synthetic || impl_block.is_negative {
+        return false;
+    }
+    let Some(trait_ref) = &impl_block.trait_ else {
+        return false;
+    };
+
+    match crate_data.index.get(&trait_ref.id).map(|item| &item.inner) {
+        Some(ItemEnum::Trait(t)) => t.is_unsafe,
+        // External traits carry no safety information, so the standard library's
+        // unsafe traits are recognized by path
+        _ => crate_data.paths.get(&trait_ref.id).is_some_and(|summary| {
+            matches!(
+                summary.path.join("::").as_str(),
+                "core::marker::Send" | "core::marker::Sync" | "core::alloc::global::GlobalAlloc"
+            )
+        }),
+    }
+}
+
+/// Whether a trait is sealed: it has a supertrait that code outside the crate can't name.
+///
+/// Only fires for the usual pattern of a local marker trait (no items or generic
+/// parameters) that is private or sits in a private module and isn't re-exported.
+fn is_sealed_trait(t: &rustdoc_types::Trait, crate_data: &Crate) -> bool {
+    t.bounds.iter().any(|bound| match bound {
+        rustdoc_types::GenericBound::TraitBound { trait_, .. } => {
+            is_private_marker_trait(&trait_.id, crate_data)
+        }
+        _ => false,
+    })
+}
+
+fn is_private_marker_trait(id: &Id, crate_data: &Crate) -> bool {
+    let Some(item) = crate_data.index.get(id) else {
+        return false;
+    };
+    let ItemEnum::Trait(t) = &item.inner else {
+        return false;
+    };
+    if !t.items.is_empty() || !t.generics.params.is_empty() {
+        return false;
+    }
+    if !is_public(item) {
+        return true;
+    }
+
+    let reexported = crate_data.index.values().any(|item| {
+        matches!(&item.inner, ItemEnum::Use(use_) if use_.id == Some(*id)) && is_public(item)
+    });
+    let Some(summary) = crate_data.paths.get(id) else {
+        return false;
+    };
+    let parents = &summary.path[..summary.path.len().saturating_sub(1)];
+    // The crate root is always reachable
+    !reexported && (2..=parents.len()).any(|len| !is_public_module(&parents[..len], crate_data))
+}
+
+/// Whether the crate has a public module at `path`.
+fn is_public_module(path: &[String], crate_data: &Crate) -> bool {
+    crate_data.paths.iter().any(|(id, summary)| {
+        summary.crate_id == 0
+            && summary.kind == rustdoc_types::ItemKind::Module
+            && summary.path == path
+            && crate_data.index.get(id).is_some_and(is_public)
+    })
+}
+
+/// Full `impl<..> Trait for Type where ..` line of an impl block.
+fn format_impl_signature(impl_block: &rustdoc_types::Impl, crate_data: &Crate) -> String {
+    let params = format_generic_params(&impl_block.generics);
+    let where_clause = format_where_clause(&impl_block.generics);
+
+    let trait_part = impl_block.trait_.as_ref().map_or(String::new(), |trait_| {
+        let mut path = trait_.path.clone();
+        if let Some(args) = &trait_.args {
+            path.push_str(&format_generic_args(args));
+        }
+        format!("{} for ", path)
+    });
+
+    format!(
+        "{}impl{} {}{}{}{}",
+        if is_unsafe_impl(impl_block, crate_data) {
+            "unsafe "
+        } else {
+            ""
+        },
+        params,
+        if impl_block.is_negative { "!" } else { "" },
+        trait_part,
+        format_type(&impl_block.for_),
+        where_clause
+    )
+}
+
+fn format_term(term: &rustdoc_types::Term) -> String {
+    match term {
+        rustdoc_types::Term::Type(ty) => format_type(ty),
+        rustdoc_types::Term::Constant(c) => c.expr.clone(),
+    }
+}
+
+fn is_synthetic_lifetime(name: &str) -> bool {
+    // Filter compiler-generated synthetic lifetimes
+    name == "'_"
+        || name.starts_with("'_") && name[2..].chars().all(|c| c.is_ascii_digit())
+        || name.starts_with("'life") && name[5..].chars().all(|c| c.is_ascii_digit())
+        || name == "'async_trait"
+}
+
+fn is_compiler_internal_trait(trait_name: &str) -> bool {
+    matches!(
+        trait_name,
+        "StructuralPartialEq"
+            | "StructuralEq"
+            | "Freeze"
+            | "Unpin"
+            | "RefUnwindSafe"
+            | "UnwindSafe"
+    )
+}
 
-                    let public_derives: Vec<_> = derives
-                        .into_iter()
-                        .filter(|t| !is_compiler_internal_trait(t))
-                        .collect();
+fn pluralize(count: usize, singular: &str, plural: &str) -> String {
+    if count == 1 {
+        format!("{} {}", count, singular)
+    } else {
+        format!("{} {}", count, plural)
+    }
+}
 
-                    if !public_derives.is_empty() {
-                        output.push_str("**Traits:** ");
-                        output.push_str(&public_derives.join(", "));
-                        output.push_str("\n\n");
-                    }
+/// A type's inherent impls, its trait impls, and the trait impls generated by
+/// `#[derive]`, the last in the order the derive lists them.
+///
+/// Trait impls for references to the type and for arrays, slices, and tuples of it,
+/// like `impl Trait for &Foo`, count as the type's own.
+fn collect_impls_for_type<'a>(
+    type_id: &rustdoc_types::Id,
+    crate_data: &'a Crate,
+) -> (
+    Vec<&'a rustdoc_types::Impl>,
+    Vec<&'a rustdoc_types::Impl>,
+    Vec<&'a rustdoc_types::Impl>,
+) {
+    let mut inherent_impls = Vec::new();
+    let mut trait_impls = Vec::new();
+    let mut derived_impls = Vec::new();
 
-                    if !trait_with_methods.is_empty() {
-                        output.push_str("**Trait Implementations:**\n\n");
-                        for (trait_ref, methods) in trait_with_methods {
-                            output.push_str(&format!("- **{}**\n", trait_ref.path));
-                            for line in methods.lines() {
-                                output.push_str(&format!("  {}\n", line));
-                            }
-                        }
-                        output.push('\n');
-                    }
+    for item in crate_data.index.values() {
+        if let ItemEnum::Impl(impl_block) = &item.inner {
+            if is_impl_for(&impl_block.for_, type_id) {
+                if impl_block.trait_.is_none() {
+                    inherent_impls.push(impl_block);
+                } else if item
+                    .attrs
+                    .contains(&rustdoc_types::Attribute::AutomaticallyDerived)
+                {
+                    // Each derived impl's span is its trait's name in the derive list
+                    let position = item.span.as_ref().map(|span| span.begin);
+                    derived_impls.push((position, impl_block));
+                } else {
+                    trait_impls.push(impl_block);
                 }
             }
         }
-        ItemEnum::Function(f) => {
-            output.push_str(&format!("## {}\n\n", name));
-            output.push_str("*Function*\n\n");
-
-            if let Some(docs) = &item.docs {
-                output.push_str(&format!("{}\n\n", docs));
-            }
-
-            output.push_str("```rust\n");
-            output.push_str(&format!("fn {}", name));
-
-            if !f.generics.params.is_empty() {
-                output.push('<');
-                let params: Vec<String> =
-                    f.generics.params.iter().map(format_generic_param).collect();
-                output.push_str(&params.join(", "));
-                output.push('>');
-            }
+    }
 
-            output.push('(');
-            let inputs: Vec<String> = f
-                .sig
-                .inputs
-                .iter()
-                .map(|(name, ty)| format!("{}: {}", name, format_type(ty)))
-                .collect();
-            output.push_str(&inputs.join(", "));
-            output.push(')');
+    derived_impls.sort_by(|(a, a_impl), (b, b_impl)| {
+        let trait_name =
+            |impl_block: &rustdoc_types::Impl| impl_block.trait_.as_ref().map(|t| t.path.clone());
+        (a, trait_name(a_impl)).cmp(&(b, trait_name(b_impl)))
+    });
+    let derived_impls = derived_impls
+        .into_iter()
+        .map(|(_, impl_block)| impl_block)
+        .collect();
 
-            if let Some(output_type) = &f.sig.output {
-                output.push_str(&format!(" -> {}", format_type(output_type)));
-            }
+    (inherent_impls, trait_impls, derived_impls)
+}
 
-            output.push_str("\n```\n\n");
-        }
-        ItemEnum::Trait(t) => {
-            output.push_str(&format!("## {}\n\n", name));
-            output.push_str("*Trait*\n\n");
+/// Whether an impl for `self_type` belongs with the type `type_id`: it is the type, or
+/// a reference, pointer, array, slice, or tuple holding it.
+fn is_impl_for(self_type: &rustdoc_types::Type, type_id: &rustdoc_types::Id) -> bool {
+    use rustdoc_types::Type;
 
-            if let Some(docs) = &item.docs {
-                output.push_str(&format!("{}\n\n", docs));
-            }
+    match self_type {
+        Type::ResolvedPath(path) => path.id == *type_id,
+        Type::BorrowedRef { type_, .. }
+        | Type::RawPointer { type_, .. }
+        | Type::Slice(type_)
+        | Type::Array { type_, .. } => is_impl_for(type_, type_id),
+        Type::Tuple(types) => types.iter().any(|type_| is_impl_for(type_, type_id)),
+        _ => false,
+    }
+}
 
-            if !t.items.is_empty() {
-                output.push_str("**Methods:**\n\n");
-                for method_id in &t.items {
-                    if let Some(method) = crate_data.index.get(method_id) {
-                        if let Some(method_name) = &method.name {
-                            output.push_str(&format!("- `{}`", method_name));
-                            if let Some(method_docs) = &method.docs {
-                                output.push_str(&format!(
-                                    ": {}",
-                                    method_docs.lines().next().unwrap_or("")
-                                ));
-                            }
-                            output.push('\n');
-                        }
-                    }
-                }
-                output.push('\n');
+/// The `*Derives: ...*` line under a type's heading, empty when nothing is derived.
+fn format_derives(derived_impls: &[&rustdoc_types::Impl]) -> String {
+    let mut traits: Vec<String> = Vec::new();
+    for impl_block in derived_impls {
+        if let Some(trait_ref) = &impl_block.trait_ {
+            let name = format!("`{}`", trait_ref.path);
+            if !is_compiler_internal_trait(&trait_ref.path) && !traits.contains(&name) {
+                traits.push(name);
             }
         }
-        ItemEnum::Module(_) => {
-            output.push_str(&format!("## Module: {}\n\n", name));
+    }
 
-            if let Some(docs) = &item.docs {
-                output.push_str(&format!("{}\n\n", docs));
-            }
-        }
-        ItemEnum::Constant { type_, .. } => {
-            output.push_str(&format!("## {}\n\n", name));
-            output.push_str(&format!("*Constant*: `{}`\n\n", format_type(type_)));
+    if traits.is_empty() {
+        return String::new();
+    }
+    format!("*Derives: {}*\n\n", traits.join(", "))
+}
 
-            if let Some(docs) = &item.docs {
-                output.push_str(&format!("{}\n\n", docs));
-            }
-        }
-        ItemEnum::TypeAlias(ta) => {
-            output.push_str(&format!("## {}\n\n", name));
-            output.push_str(&format!("*Type Alias*: `{}`\n\n", format_type(&ta.type_)));
+/// A type's trait impls, grouped like docs.rs: implementations written in the crate,
+/// then negative impls (`impl !Send for T`), then auto trait and blanket
+/// implementations collapsed in `<details>` blocks.
+///
+/// With `collapsible`, each written impl with items is a `<details>` block of its own.
+fn format_trait_impls(
+    trait_impls: &[&rustdoc_types::Impl],
+    crate_data: &Crate,
+    collapsible: bool,
+) -> String {
+    let mut output = String::new();
 
-            if let Some(docs) = &item.docs {
-                output.push_str(&format!("{}\n\n", docs));
+    let (negative_impls, user_impls): (Vec<&&rustdoc_types::Impl>, Vec<_>) = trait_impls
+        .iter()
+        .filter(|impl_block| !impl_block.is_synthetic && impl_block.blanket_impl.is_none())
+        .partition(|impl_block| impl_block.is_negative);
+
+    if !user_impls.is_empty() {
+        let mut derives = Vec::new();
+        let mut trait_with_methods = Vec::new();
+
+        for impl_block in user_impls {
+            if let Some(trait_ref) = &impl_block.trait_ {
+                let methods = format_impl_items(impl_block, crate_data);
+                if methods.is_empty() {
+                    // Impls for `&Foo` and the like are told apart from `Foo`'s own
+                    let for_type = match &impl_block.for_ {
+                        rustdoc_types::Type::ResolvedPath(_) => None,
+                        for_ => Some(format_type(for_)),
+                    };
+                    derives.push((
+                        trait_ref.path.as_str(),
+                        for_type,
+                        is_unsafe_impl(impl_block, crate_data),
+                    ));
+                } else {
+                    let header = format_impl_header(impl_block, crate_data);
+                    trait_with_methods.push((impl_block, trait_ref, header, methods));
+                }
             }
         }
-        ItemEnum::Static(s) => {
-            output.push_str(&format!("## {}\n\n", name));
-            output.push_str("*Static*\n\n");
 
-            if let Some(docs) = &item.docs {
-                output.push_str(&format!("{}\n\n", docs));
-            }
+        let public_derives: Vec<_> = derives
+            .into_iter()
+            .filter(|(t, _, _)| !is_compiler_internal_trait(t))
+            .map(|(t, for_type, is_unsafe)| {
+                let t = match for_type {
+                    Some(for_type) => format!("{} for `{}`", t, for_type),
+                    None => t.to_string(),
+                };
+                if is_unsafe {
+                    format!("unsafe {}", t)
+                } else {
+                    t
+                }
+            })
+            .collect();
 
-            output.push_str("```rust\n");
-            output.push_str(&format!(
-                "{}static {}{}: {}",
-                if s.is_unsafe { "unsafe " } else { "" },
-                if s.is_mutable { "mut " } else { "" },
-                name,
-                format_type(&s.type_)
-            ));
-            output.push_str("\n```\n\n");
+        if !public_derives.is_empty() {
+            output.push_str("**Traits:** ");
+            output.push_str(&public_derives.join(", "));
+            output.push_str("\n\n");
         }
-        ItemEnum::Union(u) => {
-            output.push_str(&format!("## {}\n\n", name));
-            output.push_str("*Union*\n\n");
-
-            if let Some(docs) = &item.docs {
-                output.push_str(&format!("{}\n\n", docs));
-            }
-
-            let non_synthetic_params: Vec<_> = u
-                .generics
-                .params
-                .iter()
-                .filter(|p| {
-                    !matches!(&p.kind, rustdoc_types::GenericParamDefKind::Lifetime { .. })
-                        || !is_synthetic_lifetime(&p.name)
-                })
-                .collect();
 
-            if !non_synthetic_params.is_empty() {
-                output.push_str("**Generic Parameters:**\n");
-                for param in non_synthetic_params {
-                    output.push_str(&format!("- {}\n", format_generic_param(param)));
+        if !trait_with_methods.is_empty() {
+            output.push_str("**Trait Implementations:**\n\n");
+            for (impl_block, trait_ref, header, methods) in trait_with_methods {
+                if collapsible {
+                    output.push_str(&format!(
+                        "{}\n{}\n</details>\n",
+                        details_open(&format_impl_signature(impl_block, crate_data)),
+                        methods
+                    ));
+                    continue;
                 }
-                output.push('\n');
-            }
-
-            if !u.fields.is_empty() {
-                output.push_str("**Fields:**\n");
-                for field_id in &u.fields {
-                    if let Some(field) = crate_data.index.get(field_id) {
-                        if let Some(field_name) = &field.name {
-                            let field_type = if let ItemEnum::StructField(ty) = &field.inner {
-                                format_type(ty)
-                            } else {
-                                "?".to_string()
-                            };
-                            output.push_str(&format!("- `{}: {}`", field_name, field_type));
-                            if let Some(docs) = &field.docs {
-                                let first_line = docs.lines().next().unwrap_or("").trim();
-                                if !first_line.is_empty() {
-                                    output.push_str(&format!(" - {}", first_line));
-                                }
-                            }
-                            output.push('\n');
-                        }
+                match header {
+                    Some(header) => {
+                        output.push_str(&format!("- **{}** (`{}`)\n", trait_ref.path, header))
                     }
+                    None => output.push_str(&format!("- **{}**\n", trait_ref.path)),
                 }
-                output.push('\n');
-            }
-
-            let (inherent_impls, trait_impls) = collect_impls_for_type(item_id, crate_data);
-
-            if !inherent_impls.is_empty() {
-                output.push_str("**Methods:**\n\n");
-                for impl_block in inherent_impls {
-                    output.push_str(&format_impl_methods(impl_block, crate_data));
+                for line in methods.lines() {
+                    output.push_str(&format!("  {}\n", line));
                 }
-                output.push('\n');
             }
+            output.push('\n');
+        }
+    }
 
-            if !trait_impls.is_empty() {
-                let user_impls: Vec<_> = trait_impls
-                    .iter()
-                    .filter(|impl_block| {
-                        !impl_block.is_synthetic && impl_block.blanket_impl.is_none()
-                    })
-                    .collect();
+    if !negative_impls.is_empty() {
+        let mut signatures: Vec<String> = negative_impls
+            .iter()
+            .map(|impl_block| format_impl_signature(impl_block, crate_data))
+            .collect();
+        signatures.sort();
+        output.push_str("**Negative Implementations:**\n\n");
+        for signature in signatures {
+            output.push_str(&format!("- `{}`\n", signature));
+        }
+        output.push('\n');
+    }
 
-                if !user_impls.is_empty() {
-                    let mut derives = Vec::new();
-                    let mut trait_with_methods = Vec::new();
+    let auto_impls: Vec<_> = trait_impls
+        .iter()
+        .filter(|impl_block| impl_block.is_synthetic)
+        .copied()
+        .collect();
+    let blanket_impls: Vec<_> = trait_impls
+        .iter()
+        .filter(|impl_block| !impl_block.is_synthetic && impl_block.blanket_impl.is_some())
+        .copied()
+        .collect();
 
-                    for impl_block in user_impls {
-                        if let Some(trait_ref) = &impl_block.trait_ {
-                            let methods = format_impl_methods(impl_block, crate_data);
-                            if methods.is_empty() {
-                                derives.push(trait_ref.path.as_str());
-                            } else {
-                                trait_with_methods.push((trait_ref, methods));
-                            }
-                        }
-                    }
+    output.push_str(&format_collapsed_impls(
+        "Auto Trait Implementations",
+        &auto_impls,
+        crate_data,
+    ));
+    output.push_str(&format_collapsed_impls(
+        "Blanket Implementations",
+        &blanket_impls,
+        crate_data,
+    ));
 
-                    let public_derives: Vec<_> = derives
-                        .into_iter()
-                        .filter(|t| !is_compiler_internal_trait(t))
-                        .collect();
+    output
+}
 
-                    if !public_derives.is_empty() {
-                        output.push_str("**Traits:** ");
-                        output.push_str(&public_derives.join(", "));
-                        output.push_str("\n\n");
-                    }
+/// Opening of a collapsed `<details>` block summarized by `signature` as code.
+fn details_open(signature: &str) -> String {
+    let escaped = signature
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;");
+    format!("<details>\n<summary><code>{}</code></summary>\n", escaped)
+}
 
-                    if !trait_with_methods.is_empty() {
-                        output.push_str("**Trait Implementations:**\n\n");
-                        for (trait_ref, methods) in trait_with_methods {
-                            output.push_str(&format!("- **{}**\n", trait_ref.path));
-                            for line in methods.lines() {
-                                output.push_str(&format!("  {}\n", line));
-                            }
-                        }
-                        output.push('\n');
-                    }
-                }
-            }
-        }
-        ItemEnum::Macro(m) => {
-            output.push_str(&format!("## {}\n\n", name));
-            output.push_str("*Declarative Macro*\n\n");
+/// A `<details>` block listing impl signatures, collapsed by default.
+fn format_collapsed_impls(
+    title: &str,
+    impls: &[&rustdoc_types::Impl],
+    crate_data: &Crate,
+) -> String {
+    if impls.is_empty() {
+        return String::new();
+    }
 
-            if let Some(docs) = &item.docs {
-                output.push_str(&format!("{}\n\n", docs));
-            }
+    // Blanket impls are shown over their generic self type, as written
+    let mut signatures: Vec<(&str, String)> = impls
+        .iter()
+        .map(|impl_block| {
+            let trait_name = impl_block.trait_.as_ref().map_or("", |t| t.path.as_str());
+            let signature = match &impl_block.blanket_impl {
+                Some(blanket) => format_impl_signature(
+                    &rustdoc_types::Impl {
+                        for_: blanket.clone(),
+                        ..(*impl_block).clone()
+                    },
+                    crate_data,
+                ),
+                None => format_impl_signature(impl_block, crate_data),
+            };
+            (trait_name, signature)
+        })
+        .collect();
+    signatures.sort();
+    signatures.dedup();
 
-            if !m.is_empty() {
-                output.push_str("```rust\n");
-                output.push_str(m);
-                output.push_str("\n```\n\n");
-            } else {
-                output.push_str("```rust\n");
-                output.push_str(&format!("{}!(...)", name));
-                output.push_str("\n```\n\n");
-            }
-        }
-        ItemEnum::ProcMacro(pm) => {
-            output.push_str(&format!("## {}\n\n", name));
+    let mut output = format!("<details>\n<summary>{}</summary>\n\n", title);
+    for (_, signature) in signatures {
+        output.push_str(&format!("- `{}`\n", signature));
+    }
+    output.push_str("\n</details>\n\n");
+    output
+}
 
-            let kind_str = match &pm.kind {
-                rustdoc_types::MacroKind::Bang => "Function-like Macro",
-                rustdoc_types::MacroKind::Attr => "Attribute Macro",
-                rustdoc_types::MacroKind::Derive => "Derive Macro",
-            };
-            output.push_str(&format!("*{}*\n\n", kind_str));
+/// Items of a type's inherent impls, grouped into associated constants, associated
+/// functions and methods, each constrained impl introduced by its header.
+///
+/// With `collapsible`, each impl block is instead a `<details>` block of its own,
+/// grouping just its items.
+fn format_inherent_impls(
+    impls: &[&rustdoc_types::Impl],
+    crate_data: &Crate,
+    options: &MarkdownOptions,
+    module: &[String],
+) -> String {
+    let mut output = String::new();
 
-            if let Some(docs) = &item.docs {
-                output.push_str(&format!("{}\n\n", docs));
+    if options.collapsible {
+        for impl_block in impls {
+            let groups = impl_item_groups(impl_block, crate_data, Some((options, module)));
+            if groups.is_empty() {
+                continue;
             }
-
-            output.push_str("```rust\n");
-            match &pm.kind {
-                rustdoc_types::MacroKind::Bang => {
-                    output.push_str(&format!("{}!(...)", name));
-                }
-                rustdoc_types::MacroKind::Attr => {
-                    output.push_str(&format!("#[{}]", name));
-                }
-                rustdoc_types::MacroKind::Derive => {
-                    output.push_str(&format!("#[derive({})]", name));
+            output.push_str(&details_open(&format_impl_signature(
+                impl_block, crate_data,
+            )));
+            output.push('\n');
+            for (group, entries) in groups {
+                output.push_str(&format!("**{}:**\n\n", group.title()));
+                for entry in entries {
+                    output.push_str(&format!("- {}\n", entry));
                 }
+                output.push('\n');
             }
-            output.push_str("\n```\n\n");
+            output.push_str("</details>\n\n");
         }
-        ItemEnum::TraitAlias(ta) => {
-            output.push_str(&format!("## {}\n\n", name));
-            output.push_str("*Trait Alias*\n\n");
-
-            if let Some(docs) = &item.docs {
-                output.push_str(&format!("{}\n\n", docs));
-            }
+        return output;
+    }
 
-            output.push_str("```rust\n");
-            output.push_str(&format!("trait {}", name));
+    // Unconstrained impls first, so their items are not read as part of a header's block
+    let mut impls = impls.to_vec();
+    impls.sort_by_key(|impl_block| format_impl_header(impl_block, crate_data).is_some());
+    let grouped: Vec<_> = impls
+        .iter()
+        .map(|impl_block| {
+            (
+                format_impl_header(impl_block, crate_data),
+                impl_item_groups(impl_block, crate_data, Some((options, module))),
+            )
+        })
+        .collect();
 
-            if !ta.generics.params.is_empty() {
-                output.push('<');
-                let params: Vec<String> = ta
-                    .generics
-                    .params
-                    .iter()
-                    .map(format_generic_param)
-                    .collect();
-                output.push_str(&params.join(", "));
-                output.push('>');
+    for group in ImplItemGroup::ALL {
+        let mut section = String::new();
+        for (header, groups) in &grouped {
+            let Some((_, entries)) = groups.iter().find(|(g, _)| *g == group) else {
+                continue;
+            };
+            if let Some(header) = header {
+                if !section.is_empty() {
+                    section.push('\n');
+                }
+                section.push_str(&format!("`{}`\n\n", header));
             }
+            for entry in entries {
+                section.push_str(&format!("- {}\n", entry));
+            }
+        }
+        if !section.is_empty() {
+            output.push_str(&format!("**{}:**\n\n{}\n", group.title(), section));
+        }
+    }
 
-            output.push_str(" = ");
+    output
+}
 
-            let bounds: Vec<String> = ta
-                .params
-                .iter()
-                .map(|bound| match bound {
-                    rustdoc_types::GenericBound::TraitBound { trait_, .. } => trait_.path.clone(),
-                    rustdoc_types::GenericBound::Outlives(lifetime) => lifetime.clone(),
-                    rustdoc_types::GenericBound::Use(_) => "use<...>".to_string(),
-                })
-                .collect();
+/// The kinds of items an impl block lists, in the order they are shown
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ImplItemGroup {
+    AssociatedTypes,
+    AssociatedConstants,
+    /// Functions without a `self` receiver, like constructors
+    AssociatedFunctions,
+    /// Functions taking `self`, `&self`, `&mut self` or a typed `self`
+    Methods,
+}
 
-            output.push_str(&bounds.join(" + "));
-            output.push_str("\n```\n\n");
-        }
-        _ => {
-            return None;
+impl ImplItemGroup {
+    const ALL: [ImplItemGroup; 4] = [
+        ImplItemGroup::AssociatedTypes,
+        ImplItemGroup::AssociatedConstants,
+        ImplItemGroup::AssociatedFunctions,
+        ImplItemGroup::Methods,
+    ];
+
+    fn title(self) -> &'static str {
+        match self {
+            ImplItemGroup::AssociatedTypes => "Associated Types",
+            ImplItemGroup::AssociatedConstants => "Associated Constants",
+            ImplItemGroup::AssociatedFunctions => "Associated Functions",
+            ImplItemGroup::Methods => "Methods",
         }
     }
 
-    Some(output)
+    fn of(item: &Item) -> Option<ImplItemGroup> {
+        match &item.inner {
+            ItemEnum::AssocType { .. } => Some(ImplItemGroup::AssociatedTypes),
+            ItemEnum::AssocConst { .. } => Some(ImplItemGroup::AssociatedConstants),
+            // rustdoc names the receiver parameter `self` whatever its type
+            ItemEnum::Function(f) if f.sig.inputs.first().is_some_and(|(n, _)| n == "self") => {
+                Some(ImplItemGroup::Methods)
+            }
+            ItemEnum::Function(_) => Some(ImplItemGroup::AssociatedFunctions),
+            _ => None,
+        }
+    }
 }
 
-fn format_generic_param(param: &rustdoc_types::GenericParamDef) -> String {
-    match &param.kind {
-        rustdoc_types::GenericParamDefKind::Lifetime { .. } => {
-            // Lifetime names already include the ' prefix in rustdoc JSON
-            param.name.clone()
+/// An impl block's items as list entries with their first doc line, by group. Only
+/// non-empty groups are returned.
+///
+/// For inherent impls, `visibility` holds the options and the module of the impl's
+/// type, which decide which items are included and whether each shows its
+/// visibility.
+fn impl_item_groups(
+    impl_block: &rustdoc_types::Impl,
+    crate_data: &Crate,
+    visibility: Option<(&MarkdownOptions, &[String])>,
+) -> Vec<(ImplItemGroup, Vec<String>)> {
+    let mut groups: Vec<(ImplItemGroup, Vec<String>)> = Vec::new();
+    let self_type = SelfType::of_trait_impl(impl_block, crate_data);
+
+    for item in impl_block
+        .items
+        .iter()
+        .filter_map(|id| crate_data.index.get(id))
+    {
+        let Some(group) = ImplItemGroup::of(item) else {
+            continue;
+        };
+        if visibility.is_some_and(|(options, module)| !options.includes(item, module)) {
+            continue;
+        }
+        let decl = match &item.inner {
+            ItemEnum::Function(f) => item.name.as_ref().map(|name| {
+                let f = match &self_type {
+                    Some(self_type) => &self_type.apply_to_function(f),
+                    None => f,
+                };
+                format!(
+                    "{}{}",
+                    format_function_signature(name, f),
+                    format_where_clause(&f.generics)
+                )
+            }),
+            _ => format_associated_item(item),
+        };
+        let Some(decl) = decl else {
+            continue;
+        };
+
+        let visibility = match visibility {
+            Some((options, module)) if options.includes_non_public() => {
+                declared_visibility(item, module)
+            }
+            _ => String::new(),
+        };
+        let mut entry = format!("`{}{}`", visibility, decl);
+        if let Some(docs) = &item.docs {
+            let first_line = docs.lines().next().unwrap_or("").trim();
+            if !first_line.is_empty() {
+                entry.push_str(&format!(" - {}", first_line));
+            }
         }
-        rustdoc_types::GenericParamDefKind::Type { .. } => param.name.clone(),
-        rustdoc_types::GenericParamDefKind::Const { .. } => {
-            format!("const {}", param.name)
+
+        match groups.iter_mut().find(|(g, _)| *g == group) {
+            Some((_, entries)) => entries.push(entry),
+            None => groups.push((group, vec![entry])),
         }
     }
-}
 
-fn is_synthetic_lifetime(name: &str) -> bool {
-    // Filter compiler-generated synthetic lifetimes
-    name == "'_"
-        || name.starts_with("'_") && name[2..].chars().all(|c| c.is_ascii_digit())
-        || name.starts_with("'life") && name[5..].chars().all(|c| c.is_ascii_digit())
-        || name == "'async_trait"
+    groups.sort_by_key(|(group, _)| ImplItemGroup::ALL.iter().position(|g| g == group));
+    groups
 }
 
-fn is_compiler_internal_trait(trait_name: &str) -> bool {
-    matches!(
-        trait_name,
-        "StructuralPartialEq"
-            | "StructuralEq"
-            | "Freeze"
-            | "Unpin"
-            | "RefUnwindSafe"
-            | "UnwindSafe"
-    )
+/// The concrete type a trait impl is for, to show in place of `Self` in its methods.
+struct SelfType<'a> {
+    for_: &'a rustdoc_types::Type,
+    /// The impl's associated types, which `Self::Name` resolves to
+    assoc_types: HashMap<&'a str, &'a rustdoc_types::Type>,
 }
 
-fn pluralize(count: usize, singular: &str, plural: &str) -> String {
-    if count == 1 {
-        format!("{} {}", count, singular)
-    } else {
-        format!("{} {}", count, plural)
+impl<'a> SelfType<'a> {
+    fn of_trait_impl(impl_block: &'a rustdoc_types::Impl, crate_data: &'a Crate) -> Option<Self> {
+        impl_block.trait_.as_ref()?;
+        let assoc_types = impl_block
+            .items
+            .iter()
+            .filter_map(|id| crate_data.index.get(id))
+            .filter_map(|item| match &item.inner {
+                ItemEnum::AssocType {
+                    type_: Some(ty), ..
+                } => Some((item.name.as_deref()?, ty)),
+                _ => None,
+            })
+            .collect();
+        Some(SelfType {
+            for_: &impl_block.for_,
+            assoc_types,
+        })
     }
-}
 
-fn collect_impls_for_type<'a>(
-    type_id: &rustdoc_types::Id,
-    crate_data: &'a Crate,
-) -> (Vec<&'a rustdoc_types::Impl>, Vec<&'a rustdoc_types::Impl>) {
-    use rustdoc_types::Type;
+    /// `f` with `Self` replaced in its parameter and return types.
+    ///
+    /// Receivers are written as declared, `&self` rather than `self: &Type` and
+    /// `self: Box<Self>` rather than `self: Box<Type>`.
+    fn apply_to_function(&self, f: &rustdoc_types::Function) -> rustdoc_types::Function {
+        let mut f = f.clone();
+        for (name, ty) in &mut f.sig.inputs {
+            if name != "self" {
+                *ty = self.apply(ty);
+            }
+        }
+        if let Some(output) = &mut f.sig.output {
+            *output = self.apply(output);
+        }
+        f
+    }
 
-    let mut inherent_impls = Vec::new();
-    let mut trait_impls = Vec::new();
+    fn apply(&self, ty: &rustdoc_types::Type) -> rustdoc_types::Type {
+        use rustdoc_types::Type;
 
-    for item in crate_data.index.values() {
-        if let ItemEnum::Impl(impl_block) = &item.inner {
-            let matches = match &impl_block.for_ {
-                Type::ResolvedPath(path) => path.id == *type_id,
-                _ => false,
-            };
+        match ty {
+            Type::Generic(name) if name == "Self" => self.for_.clone(),
+            Type::ResolvedPath(path) => Type::ResolvedPath(self.apply_to_path(path)),
+            Type::DynTrait(dt) => {
+                let mut dt = dt.clone();
+                for poly in &mut dt.traits {
+                    poly.trait_ = self.apply_to_path(&poly.trait_);
+                }
+                Type::DynTrait(dt)
+            }
+            Type::FunctionPointer(fp) => {
+                let mut fp = fp.clone();
+                for (_, input) in &mut fp.sig.inputs {
+                    *input = self.apply(input);
+                }
+                if let Some(output) = &mut fp.sig.output {
+                    *output = self.apply(output);
+                }
+                Type::FunctionPointer(fp)
+            }
+            Type::Tuple(types) => Type::Tuple(types.iter().map(|ty| self.apply(ty)).collect()),
+            Type::Slice(type_) => Type::Slice(Box::new(self.apply(type_))),
+            Type::Array { type_, len } => Type::Array {
+                type_: Box::new(self.apply(type_)),
+                len: len.clone(),
+            },
+            Type::Pat {
+                type_,
+                __pat_unstable_do_not_use,
+            } => Type::Pat {
+                type_: Box::new(self.apply(type_)),
+                __pat_unstable_do_not_use: __pat_unstable_do_not_use.clone(),
+            },
+            Type::ImplTrait(bounds) => Type::ImplTrait(
+                bounds
+                    .iter()
+                    .map(|bound| self.apply_to_bound(bound))
+                    .collect(),
+            ),
+            Type::RawPointer { is_mutable, type_ } => Type::RawPointer {
+                is_mutable: *is_mutable,
+                type_: Box::new(self.apply(type_)),
+            },
+            Type::BorrowedRef {
+                lifetime,
+                is_mutable,
+                type_,
+            } => Type::BorrowedRef {
+                lifetime: lifetime.clone(),
+                is_mutable: *is_mutable,
+                type_: Box::new(self.apply(type_)),
+            },
+            Type::QualifiedPath {
+                name,
+                args,
+                self_type,
+                trait_,
+            } => {
+                let assoc_type = matches!(self_type.as_ref(), Type::Generic(s) if s == "Self")
+                    .then(|| self.assoc_types.get(name.as_str()))
+                    .flatten();
+                match assoc_type {
+                    Some(assoc_type) => self.apply(assoc_type),
+                    None => Type::QualifiedPath {
+                        name: name.clone(),
+                        args: args.clone(),
+                        self_type: Box::new(self.apply(self_type)),
+                        trait_: trait_.clone(),
+                    },
+                }
+            }
+            Type::Generic(_) | Type::Primitive(_) | Type::Infer => ty.clone(),
+        }
+    }
 
-            if matches {
-                if impl_block.trait_.is_some() {
-                    trait_impls.push(impl_block);
-                } else {
-                    inherent_impls.push(impl_block);
+    fn apply_to_path(&self, path: &rustdoc_types::Path) -> rustdoc_types::Path {
+        use rustdoc_types::{AssocItemConstraintKind, GenericArg, GenericArgs, Term};
+
+        let mut path = path.clone();
+        match path.args.as_deref_mut() {
+            Some(GenericArgs::AngleBracketed { args, constraints }) => {
+                for arg in args {
+                    if let GenericArg::Type(ty) = arg {
+                        *ty = self.apply(ty);
+                    }
+                }
+                for constraint in constraints {
+                    match &mut constraint.binding {
+                        AssocItemConstraintKind::Equality(Term::Type(ty)) => *ty = self.apply(ty),
+                        AssocItemConstraintKind::Equality(Term::Constant(_)) => {}
+                        AssocItemConstraintKind::Constraint(bounds) => {
+                            for bound in bounds {
+                                *bound = self.apply_to_bound(bound);
+                            }
+                        }
+                    }
+                }
+            }
+            Some(GenericArgs::Parenthesized { inputs, output }) => {
+                for ty in inputs.iter_mut().chain(output) {
+                    *ty = self.apply(ty);
                 }
             }
+            Some(GenericArgs::ReturnTypeNotation) | None => {}
+        }
+        path
+    }
+
+    fn apply_to_bound(&self, bound: &rustdoc_types::GenericBound) -> rustdoc_types::GenericBound {
+        match bound {
+            rustdoc_types::GenericBound::TraitBound {
+                trait_,
+                generic_params,
+                modifier,
+            } => rustdoc_types::GenericBound::TraitBound {
+                trait_: self.apply_to_path(trait_),
+                generic_params: generic_params.clone(),
+                modifier: *modifier,
+            },
+            bound => bound.clone(),
         }
     }
+}
 
-    (inherent_impls, trait_impls)
+/// A trait impl's items as nested lists, one per group.
+fn format_impl_items(impl_block: &rustdoc_types::Impl, crate_data: &Crate) -> String {
+    let mut output = String::new();
+    for (group, entries) in impl_item_groups(impl_block, crate_data, None) {
+        output.push_str(&format!("- *{}:*\n", group.title()));
+        for entry in entries {
+            output.push_str(&format!("  - {}\n", entry));
+        }
+    }
+    output
 }
 
-fn format_impl_methods(impl_block: &rustdoc_types::Impl, crate_data: &Crate) -> String {
+/// "Associated Types" and "Associated Constants" subsections for a trait.
+fn format_associated_items(items: &[&Item]) -> String {
+    let (types, consts): (Vec<&Item>, Vec<&Item>) = items
+        .iter()
+        .filter(|item| {
+            matches!(
+                item.inner,
+                ItemEnum::AssocType { .. } | ItemEnum::AssocConst { .. }
+            )
+        })
+        .partition(|item| matches!(item.inner, ItemEnum::AssocType { .. }));
+
     let mut output = String::new();
 
-    for method_id in &impl_block.items {
-        if let Some(method) = crate_data.index.get(method_id) {
-            if let ItemEnum::Function(f) = &method.inner {
-                if let Some(method_name) = &method.name {
-                    let sig = format_function_signature(method_name, f);
-                    output.push_str(&format!("- `{}`", sig));
-
-                    if let Some(docs) = &method.docs {
-                        let first_line = docs.lines().next().unwrap_or("").trim();
-                        if !first_line.is_empty() {
-                            output.push_str(&format!(" - {}", first_line));
-                        }
+    for (title, group) in [
+        ("Associated Types", types),
+        ("Associated Constants", consts),
+    ] {
+        if group.is_empty() {
+            continue;
+        }
+
+        output.push_str(&format!("**{}:**\n\n", title));
+        for item in group {
+            if let Some(decl) = format_associated_item(item) {
+                output.push_str(&format!("- `{}`", decl));
+                if let Some(docs) = &item.docs {
+                    let first_line = docs.lines().next().unwrap_or("").trim();
+                    if !first_line.is_empty() {
+                        output.push_str(&format!(" - {}", first_line));
                     }
-                    output.push('\n');
                 }
+                output.push('\n');
             }
         }
+        output.push('\n');
     }
 
     output
 }
 
-fn format_function_signature(name: &str, f: &rustdoc_types::Function) -> String {
-    let mut sig = format!("fn {}", name);
+/// Declaration of an associated type or constant, e.g. `type Item: Clone;` or
+/// `type Item<'a>: Clone where Self: 'a;`.
+fn format_associated_item(item: &Item) -> Option<String> {
+    let name = item.name.as_ref()?;
 
-    let non_synthetic_params: Vec<String> = f
-        .generics
-        .params
-        .iter()
-        .filter(|p| {
-            !matches!(&p.kind, rustdoc_types::GenericParamDefKind::Lifetime { .. })
-                || !is_synthetic_lifetime(&p.name)
-        })
-        .map(format_generic_param)
-        .collect();
+    match &item.inner {
+        ItemEnum::AssocType {
+            generics,
+            bounds,
+            type_,
+        } => {
+            let mut decl = format!("type {}{}", name, format_generic_params(generics));
+            if !bounds.is_empty() {
+                let bounds: Vec<String> = bounds.iter().map(format_generic_bound).collect();
+                decl.push_str(&format!(": {}", bounds.join(" + ")));
+            }
+            if let Some(ty) = type_ {
+                decl.push_str(&format!(" = {}", format_type(ty)));
+            }
+            // Both in traits and in impls, the where clause follows any `= Type`
+            decl.push_str(&format_where_clause(generics));
+            decl.push(';');
+            Some(decl)
+        }
+        ItemEnum::AssocConst { type_, value } => {
+            let mut decl = format!("const {}: {}", name, format_type(type_));
+            if let Some(value) = value {
+                decl.push_str(&format!(" = {}", value));
+            }
+            decl.push(';');
+            Some(decl)
+        }
+        _ => None,
+    }
+}
+
+/// `: A + B + 'a` for a trait's supertraits, or empty if it has none.
+fn format_supertraits(bounds: &[rustdoc_types::GenericBound]) -> String {
+    if bounds.is_empty() {
+        return String::new();
+    }
+    let bounds: Vec<String> = bounds.iter().map(format_generic_bound).collect();
+    format!(": {}", bounds.join(" + "))
+}
+
+fn format_generic_bound(bound: &rustdoc_types::GenericBound) -> String {
+    match bound {
+        rustdoc_types::GenericBound::TraitBound {
+            trait_,
+            generic_params,
+            modifier,
+        } => {
+            let mut result = format_higher_ranked(generic_params);
+            if let rustdoc_types::TraitBoundModifier::Maybe = modifier {
+                result.push('?');
+            }
+            // Derive macros name traits through `$crate::`; show just the trait
+            match trait_.path.strip_prefix("$crate::") {
+                Some(path) => result.push_str(path.rsplit("::").next().unwrap_or(path)),
+                None => result.push_str(&trait_.path),
+            }
+            if let Some(args) = &trait_.args {
+                result.push_str(&format_generic_args(args));
+            }
+            result
+        }
+        rustdoc_types::GenericBound::Outlives(lifetime) => lifetime.clone(),
+        rustdoc_types::GenericBound::Use(args) => {
+            let args: Vec<&str> = args
+                .iter()
+                .map(|arg| match arg {
+                    rustdoc_types::PreciseCapturingArg::Lifetime(name)
+                    | rustdoc_types::PreciseCapturingArg::Param(name) => name.as_str(),
+                })
+                .collect();
+            format!("use<{}>", args.join(", "))
+        }
+    }
+}
+
+/// `for<'a> ` binder for higher-ranked bounds, or empty if there is none.
+fn format_higher_ranked(generic_params: &[rustdoc_types::GenericParamDef]) -> String {
+    if generic_params.is_empty() {
+        return String::new();
+    }
+    let params: Vec<String> = generic_params.iter().map(format_generic_param).collect();
+    format!("for<{}> ", params.join(", "))
+}
 
-    if !non_synthetic_params.is_empty() {
-        sig.push('<');
-        sig.push_str(&non_synthetic_params.join(", "));
-        sig.push('>');
+fn format_function_signature(name: &str, f: &rustdoc_types::Function) -> String {
+    let mut sig = String::new();
+    if f.header.is_const {
+        sig.push_str("const ");
+    }
+    if f.header.is_async {
+        sig.push_str("async ");
     }
+    if f.header.is_unsafe {
+        sig.push_str("unsafe ");
+    }
+    if let Some(abi) = format_abi(&f.header.abi) {
+        sig.push_str(&format!("extern \"{}\" ", abi));
+    }
+    sig.push_str(&format!("fn {}", name));
+
+    sig.push_str(&format_generic_params(&f.generics));
 
     sig.push('(');
     let inputs: Vec<String> = f
         .sig
         .inputs
         .iter()
-        .map(|(name, ty)| format!("{}: {}", name, format_type(ty)))
+        .map(|(name, ty)| format_fn_input(name, ty))
         .collect();
     sig.push_str(&inputs.join(", "));
+    if f.sig.is_c_variadic {
+        sig.push_str(if inputs.is_empty() { "..." } else { ", ..." });
+    }
     sig.push(')');
 
     if let Some(output_type) = &f.sig.output {
@@ -977,6 +4374,54 @@ fn format_function_signature(name: &str, f: &rustdoc_types::Function) -> String
     sig
 }
 
+/// The name of a non-Rust ABI, as written in `extern "..."`.
+fn format_abi(abi: &rustdoc_types::Abi) -> Option<String> {
+    use rustdoc_types::Abi;
+
+    let (name, unwind) = match abi {
+        Abi::Rust => return None,
+        Abi::C { unwind } => ("C", unwind),
+        Abi::Cdecl { unwind } => ("cdecl", unwind),
+        Abi::Stdcall { unwind } => ("stdcall", unwind),
+        Abi::Fastcall { unwind } => ("fastcall", unwind),
+        Abi::Aapcs { unwind } => ("aapcs", unwind),
+        Abi::Win64 { unwind } => ("win64", unwind),
+        Abi::SysV64 { unwind } => ("sysv64", unwind),
+        Abi::System { unwind } => ("system", unwind),
+        Abi::Other(name) => return Some(name.trim_matches('"').to_string()),
+    };
+    Some(if *unwind {
+        format!("{}-unwind", name)
+    } else {
+        name.to_string()
+    })
+}
+
+/// One function parameter, with `self`, `&self`, and `&mut self` receivers in short form.
+fn format_fn_input(name: &str, ty: &rustdoc_types::Type) -> String {
+    use rustdoc_types::Type;
+
+    let is_self = |ty: &Type| matches!(ty, Type::Generic(name) if name == "Self");
+    if name == "self" {
+        match ty {
+            ty if is_self(ty) => return "self".to_string(),
+            Type::BorrowedRef {
+                lifetime,
+                is_mutable,
+                type_,
+            } if is_self(type_) => {
+                let lifetime = lifetime
+                    .as_deref()
+                    .map_or(String::new(), |lifetime| format!("{} ", lifetime));
+                let mutability = if *is_mutable { "mut " } else { "" };
+                return format!("&{}{}self", lifetime, mutability);
+            }
+            _ => {}
+        }
+    }
+    format!("{}: {}", name, format_type(ty))
+}
+
 fn format_type(ty: &rustdoc_types::Type) -> String {
     use rustdoc_types::Type;
     match ty {
@@ -988,11 +4433,20 @@ fn format_type(ty: &rustdoc_types::Type) -> String {
             result
         }
         Type::DynTrait(dt) => {
-            if let Some(first) = dt.traits.first() {
-                format!("dyn {}", first.trait_.path)
-            } else {
-                "dyn Trait".to_string()
-            }
+            let mut bounds: Vec<String> = dt
+                .traits
+                .iter()
+                .map(|poly| {
+                    let mut bound = format_higher_ranked(&poly.generic_params);
+                    bound.push_str(&poly.trait_.path);
+                    if let Some(args) = &poly.trait_.args {
+                        bound.push_str(&format_generic_args(args));
+                    }
+                    bound
+                })
+                .collect();
+            bounds.extend(dt.lifetime.clone());
+            format!("dyn {}", bounds.join(" + "))
         }
         Type::Generic(name) => name.clone(),
         Type::Primitive(name) => name.clone(),
@@ -1004,7 +4458,10 @@ fn format_type(ty: &rustdoc_types::Type) -> String {
         Type::Slice(inner) => format!("[{}]", format_type(inner)),
         Type::Array { type_, len } => format!("[{}; {}]", format_type(type_), len),
         Type::Pat { type_, .. } => format_type(type_),
-        Type::ImplTrait(_bounds) => "impl Trait".to_string(),
+        Type::ImplTrait(bounds) => {
+            let bounds: Vec<String> = bounds.iter().map(format_generic_bound).collect();
+            format!("impl {}", bounds.join(" + "))
+        }
         Type::Infer => "_".to_string(),
         Type::RawPointer { is_mutable, type_ } => {
             if *is_mutable {
@@ -1018,13 +4475,18 @@ fn format_type(ty: &rustdoc_types::Type) -> String {
             is_mutable,
             type_,
         } => {
-            let lifetime_str = lifetime.as_deref().unwrap_or("");
-            let space = if lifetime_str.is_empty() { "" } else { " " };
-            if *is_mutable {
-                format!("&{}{} mut {}", lifetime_str, space, format_type(type_))
-            } else {
-                format!("&{}{}{}", lifetime_str, space, format_type(type_))
-            }
+            let lifetime_str = lifetime
+                .as_deref()
+                .map_or(String::new(), |lifetime| format!("{} ", lifetime));
+            let mutability = if *is_mutable { "mut " } else { "" };
+            // `&dyn A + B` would parse as `(&dyn A) + B`
+            let pointee = match type_.as_ref() {
+                Type::DynTrait(dt) if dt.traits.len() > 1 || dt.lifetime.is_some() => {
+                    format!("({})", format_type(type_))
+                }
+                _ => format_type(type_),
+            };
+            format!("&{}{}{}", lifetime_str, mutability, pointee)
         }
         Type::QualifiedPath {
             name,
@@ -1032,7 +4494,7 @@ fn format_type(ty: &rustdoc_types::Type) -> String {
             trait_,
             ..
         } => {
-            if let Some(trait_) = trait_ {
+            if let Some(trait_) = trait_.as_ref().filter(|trait_| !trait_.path.is_empty()) {
                 format!("<{} as {}>::{}", format_type(self_type), trait_.path, name)
             } else {
                 format!("{}::{}", format_type(self_type), name)
@@ -1041,28 +4503,121 @@ fn format_type(ty: &rustdoc_types::Type) -> String {
     }
 }
 
+/// A named type a signature refers to.
+enum TypeRef<'a> {
+    Path(&'a str, &'a Id),
+    Primitive(&'a str),
+}
+
+/// Named types and traits in `ty`, outermost first.
+fn collect_type_refs<'a>(ty: &'a rustdoc_types::Type, refs: &mut Vec<TypeRef<'a>>) {
+    use rustdoc_types::Type;
+    match ty {
+        Type::ResolvedPath(path) => collect_path_refs(path, refs),
+        Type::DynTrait(dt) => {
+            for poly in &dt.traits {
+                collect_path_refs(&poly.trait_, refs);
+            }
+        }
+        Type::Primitive(name) => refs.push(TypeRef::Primitive(name)),
+        Type::FunctionPointer(fp) => {
+            for (_, input) in &fp.sig.inputs {
+                collect_type_refs(input, refs);
+            }
+            if let Some(output) = &fp.sig.output {
+                collect_type_refs(output, refs);
+            }
+        }
+        Type::Tuple(types) => {
+            for ty in types {
+                collect_type_refs(ty, refs);
+            }
+        }
+        Type::Slice(type_)
+        | Type::Array { type_, .. }
+        | Type::Pat { type_, .. }
+        | Type::RawPointer { type_, .. }
+        | Type::BorrowedRef { type_, .. } => collect_type_refs(type_, refs),
+        Type::ImplTrait(bounds) => {
+            for bound in bounds {
+                if let rustdoc_types::GenericBound::TraitBound { trait_, .. } = bound {
+                    collect_path_refs(trait_, refs);
+                }
+            }
+        }
+        Type::QualifiedPath {
+            self_type, trait_, ..
+        } => {
+            collect_type_refs(self_type, refs);
+            if let Some(trait_) = trait_ {
+                collect_path_refs(trait_, refs);
+            }
+        }
+        Type::Generic(_) | Type::Infer => {}
+    }
+}
+
+fn collect_path_refs<'a>(path: &'a rustdoc_types::Path, refs: &mut Vec<TypeRef<'a>>) {
+    use rustdoc_types::{AssocItemConstraintKind, GenericArg, GenericArgs, Term};
+
+    refs.push(TypeRef::Path(&path.path, &path.id));
+    match path.args.as_deref() {
+        Some(GenericArgs::AngleBracketed { args, constraints }) => {
+            for arg in args {
+                if let GenericArg::Type(ty) = arg {
+                    collect_type_refs(ty, refs);
+                }
+            }
+            for constraint in constraints {
+                if let AssocItemConstraintKind::Equality(Term::Type(ty)) = &constraint.binding {
+                    collect_type_refs(ty, refs);
+                }
+            }
+        }
+        Some(GenericArgs::Parenthesized { inputs, output }) => {
+            for ty in inputs.iter().chain(output) {
+                collect_type_refs(ty, refs);
+            }
+        }
+        Some(GenericArgs::ReturnTypeNotation) | None => {}
+    }
+}
+
 fn format_generic_args(args: &rustdoc_types::GenericArgs) -> String {
-    use rustdoc_types::{GenericArg, GenericArgs};
+    use rustdoc_types::{AssocItemConstraintKind, GenericArg, GenericArgs};
     match args {
-        GenericArgs::AngleBracketed { args, .. } => {
-            if args.is_empty() {
+        GenericArgs::AngleBracketed { args, constraints } => {
+            let mut formatted: Vec<String> = args
+                .iter()
+                .filter_map(|arg| match arg {
+                    GenericArg::Lifetime(lt) if lt != "'_" => Some(lt.clone()),
+                    GenericArg::Lifetime(_) => None,
+                    GenericArg::Type(ty) => Some(format_type(ty)),
+                    GenericArg::Const(c) => Some(c.expr.clone()),
+                    GenericArg::Infer => Some("_".to_string()),
+                })
+                .collect();
+
+            for constraint in constraints {
+                let mut name = constraint.name.clone();
+                if let Some(args) = &constraint.args {
+                    name.push_str(&format_generic_args(args));
+                }
+                formatted.push(match &constraint.binding {
+                    AssocItemConstraintKind::Equality(term) => {
+                        format!("{} = {}", name, format_term(term))
+                    }
+                    AssocItemConstraintKind::Constraint(bounds) => {
+                        let bounds: Vec<String> = bounds.iter().map(format_generic_bound).collect();
+                        format!("{}: {}", name, bounds.join(" + "))
+                    }
+                });
+            }
+
+            if formatted.is_empty() {
                 String::new()
             } else {
-                let formatted: Vec<String> = args
-                    .iter()
-                    .filter_map(|arg| match arg {
-                        GenericArg::Lifetime(lt) if lt != "'_" => Some(lt.clone()),
-                        GenericArg::Lifetime(_) => None,
-                        GenericArg::Type(ty) => Some(format_type(ty)),
-                        GenericArg::Const(c) => Some(c.expr.clone()),
-                        GenericArg::Infer => Some("_".to_string()),
-                    })
-                    .collect();
-                if formatted.is_empty() {
-                    String::new()
-                } else {
-                    format!("<{}>", formatted.join(", "))
-                }
+                format!("<{}>", formatted.join(", "))
             }
         }
         GenericArgs::Parenthesized { inputs, output } => {
@@ -1078,15 +4633,16 @@ fn format_generic_args(args: &rustdoc_types::GenericArgs) -> String {
 }
 
 fn generate_crate_index(
-    crate_name: &str,
     root_item: &Item,
     modules: &HashMap<String, Vec<(Id, Item)>>,
+    ctx: &RenderContext,
 ) -> String {
+    let crate_name = ctx.crate_name;
     let mut output = String::new();
 
     output.push_str(&format!("# {}\n\n", crate_name));
 
-    if let Some(docs) = &root_item.docs {
+    if let Some(docs) = ctx.docs(root_item) {
         output.push_str(&format!("{}\n\n", docs));
     }
 
@@ -1103,7 +4659,18 @@ fn generate_crate_index(
             .strip_prefix(&format!("{}::", crate_name))
             .unwrap_or(module_name);
 
-        let module_file = format!("{}.md", display_name.replace("::", "/"));
+        let components = module_components(module_name);
+        let placement = module_placement(&components, crate_name, ctx.options);
+        let module_file = if !placement.inlined {
+            placement.file
+        } else {
+            let section = ctx.fragment(&module_section_heading(&components, crate_name));
+            if placement.file == ctx.current_file {
+                format!("#{}", section)
+            } else {
+                format!("{}#{}", placement.file, section)
+            }
+        };
 
         // Count item types
         let mut counts = HashMap::new();
@@ -1141,30 +4708,94 @@ fn generate_crate_index(
     output
 }
 
-fn generate_module_file(
-    module_name: &str,
-    items: &[(Id, Item)],
-    crate_data: &Crate,
-    item_paths: &HashMap<Id, Vec<String>>,
-    crate_name: &str,
-) -> String {
+/// The path from the crate down to `module_name`, with every ancestor linked, e.g.
+/// `[my_crate](../index.md) › [io](../io.md) › reader`.
+///
+/// The crate links to its index. Links are relative to `ctx.current_file`, and to a
+/// section where an ancestor is inlined into a file.
+fn module_breadcrumb(module_name: &str, ctx: &RenderContext) -> String {
+    let components = module_components(module_name);
+    let index_file = index_file_name(ctx.crate_name, ctx.options);
+    let link = |file: &str, heading: String| {
+        if file == ctx.current_file {
+            format!("#{}", ctx.fragment(&heading))
+        } else {
+            relative_file_path(&ctx.current_file, file)
+        }
+    };
+
+    let mut crumbs = Vec::with_capacity(components.len() + 1);
+    if components.is_empty() {
+        crumbs.push(ctx.crate_name.to_string());
+    } else {
+        let crate_link = link(&index_file, ctx.crate_name.to_string());
+        crumbs.push(format!("[{}]({})", ctx.crate_name, crate_link));
+    }
+    for depth in 1..=components.len() {
+        let name = &components[depth - 1];
+        if depth == components.len() {
+            crumbs.push(name.clone());
+            continue;
+        }
+        let ancestor = &components[..depth];
+        let placement = module_placement(ancestor, ctx.crate_name, ctx.options);
+        let target = if placement.inlined {
+            let fragment = ctx.fragment(&module_section_heading(ancestor, ctx.crate_name));
+            if placement.file == ctx.current_file {
+                format!("#{}", fragment)
+            } else {
+                format!(
+                    "{}#{}",
+                    relative_file_path(&ctx.current_file, &placement.file),
+                    fragment
+                )
+            }
+        } else {
+            link(
+                &placement.file,
+                module_section_heading(ancestor, ctx.crate_name),
+            )
+        };
+        crumbs.push(format!("[{}]({})", name, target));
+    }
+    crumbs.join(" › ")
+}
+
+/// Path to the file `to` from the file `from`, both relative to the crate directory,
+/// e.g. `../inner.md` from `nested/inner/deep.md` to `nested/inner.md`.
+fn relative_file_path(from: &str, to: &str) -> String {
+    let mut from_dir: Vec<&str> = from.split('/').collect();
+    from_dir.pop();
+    let to_parts: Vec<&str> = to.split('/').collect();
+    let common = from_dir
+        .iter()
+        .zip(&to_parts[..to_parts.len() - 1])
+        .take_while(|(a, b)| a == b)
+        .count();
+    format!(
+        "{}{}",
+        "../".repeat(from_dir.len() - common),
+        to_parts[common..].join("/")
+    )
+}
+
+fn generate_module_file(module_name: &str, items: &[(Id, Item)], ctx: &RenderContext) -> String {
+    let crate_name = ctx.crate_name;
     let mut output = String::new();
 
     let display_name = module_name
         .strip_prefix(&format!("{}::", crate_name))
         .unwrap_or(module_name);
 
-    // Breadcrumb
-    let breadcrumb = module_name.replace("::", " > ");
-    output.push_str(&format!("**{}**\n\n", breadcrumb));
+    output.push_str(&format!("**{}**\n\n", module_breadcrumb(module_name, ctx)));
 
     output.push_str(&format!("# Module: {}\n\n", display_name));
 
     // Table of contents for this module
     output.push_str("## Contents\n\n");
 
-    let mut by_type: HashMap<&str, Vec<&Item>> = HashMap::new();
-    for (_id, item) in items {
+    let mut by_type: HashMap<&str, Vec<(&Id, &Item)>> = HashMap::new();
+    for (id, item) in items {
         let type_name = match &item.inner {
             ItemEnum::Struct(_) => "Structs",
             ItemEnum::Enum(_) => "Enums",
@@ -1180,7 +4811,14 @@ fn generate_module_file(
             ItemEnum::TraitAlias(_) => "Trait Aliases",
             _ => continue,
         };
-        by_type.entry(type_name).or_default().push(item);
+        by_type.entry(type_name).or_default().push((id, item));
+    }
+
+    let reexports = format_reexports(module_name, ctx);
+    if !reexports.is_empty() {
+        output.push_str("**Re-exports**\n\n");
+        output.push_str(&reexports);
+        output.push('\n');
     }
 
     let type_order = [
@@ -1200,12 +4838,20 @@ fn generate_module_file(
     for type_name in &type_order {
         if let Some(items_of_type) = by_type.get(type_name) {
             output.push_str(&format!("**{}**\n\n", type_name));
-            for item in items_of_type {
+            for (id, item) in items_of_type {
                 if let Some(name) = &item.name {
-                    let anchor = name.to_lowercase();
-                    output.push_str(&format!("- [`{}`](#{})", name, anchor));
+                    let full_path = ctx.item_paths.get(*id).map_or(&[][..], |path| &path[..]);
+                    let anchor = ctx.item_fragment(item, full_path);
+                    if item.deprecation.is_some() {
+                        output.push_str(&format!("- [~~`{}`~~](#{})", name, anchor));
+                    } else {
+                        output.push_str(&format!("- [`{}`](#{})", name, anchor));
+                    }
+                    if let Some(cfg) = item_cfg_prose(item) {
+                        output.push_str(&format!(" *({})*", cfg));
+                    }
                     if let Some(docs) = &item.docs {
-                        if let Some(first_line) = docs.lines().next() {
+                        if let Some(first_line) = docs.lines().next().map(str::trim) {
                             if !first_line.is_empty() {
                                 output.push_str(&format!(" - {}", first_line));
                             }
@@ -1222,7 +4868,16 @@ fn generate_module_file(
 
     // Generate content for each item
     for (id, item) in items {
-        if let Some(section) = format_item_with_path(id, item, crate_data, item_paths) {
+        // Inlined submodules get a section of their own instead of a stub
+        if matches!(item.inner, ItemEnum::Module(_))
+            && ctx
+                .item_paths
+                .get(id)
+                .is_some_and(|path| ctx.is_inlined(path))
+        {
+            continue;
+        }
+        if let Some(section) = format_item_with_path(id, item, ctx) {
             output.push_str(&section);
             output.push_str("\n\n");
         }
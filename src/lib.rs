@@ -9,25 +9,31 @@
 //! use cargo_doc_md::{convert_json_file, ConversionOptions};
 //! use std::path::Path;
 //!
-//! let options = ConversionOptions {
-//!     input_path: Path::new("target/doc/my_crate.json"),
-//!     output_dir: Path::new("docs"),
-//!     include_private: false,
-//! };
+//! let options = ConversionOptions::new(
+//!     Path::new("target/doc/my_crate.json"),
+//!     Path::new("docs"),
+//! );
 //!
 //! convert_json_file(&options).expect("Conversion failed");
 //! ```
 
 pub mod converter;
+pub mod model;
 pub mod parser;
 pub mod writer;
 
+pub use model::DocModel;
 pub use rustdoc_types;
 
-use anyhow::Result;
-use std::path::Path;
+use anyhow::{Context, Result, bail};
+use converter::{ItemOrder, MarkdownOptions, MarkdownOutput, VisibilityLevel};
+use std::path::{Path, PathBuf};
 
 /// Options for converting rustdoc JSON to markdown.
+///
+/// Create with [`ConversionOptions::new`] and set the fields you need; new options
+/// may be added without a breaking change.
+#[non_exhaustive]
 pub struct ConversionOptions<'a> {
     /// Path to the input rustdoc JSON file
     pub input_path: &'a Path,
@@ -35,6 +41,97 @@ pub struct ConversionOptions<'a> {
     pub output_dir: &'a Path,
     /// Whether to include private items in the output
     pub include_private: bool,
+    /// The least visible items to include, unless `include_private` includes them all
+    pub visibility: VisibilityLevel,
+    /// Whether to prepend YAML frontmatter to each generated file
+    pub frontmatter: bool,
+    /// Crates documented in the same run, as `(crate name, directory relative to
+    /// output_dir)` pairs, so that intra-doc links into them can point at their
+    /// markdown files
+    pub documented_crates: &'a [(String, String)],
+    /// Items of those crates whose heading has their kind appended, as `(path, kind)`
+    /// pairs from [`converter::disambiguated_items`]
+    pub disambiguated_items: &'a [(String, String)],
+    /// Glob patterns for item paths to leave out (e.g. `my_crate::internal::*`)
+    pub exclude: &'a [String],
+    /// Base URL for `[src]` links next to item headings, if any
+    pub src_base: Option<&'a str>,
+    /// Directory under `output_dir` for this crate's files, instead of the crate name
+    pub crate_dir: Option<&'a str>,
+    /// Write the crate as one `{crate_dir}.md` file in `output_dir` instead of a directory
+    pub single_file: bool,
+    /// Inline modules nested deeper than this into their ancestor's file
+    pub max_depth: Option<usize>,
+    /// Link between markdown files with Obsidian `[[wikilinks]]`
+    pub wikilinks: bool,
+    /// URL or path `output_dir` is served from, which links between files start with
+    pub base_url: Option<&'a str>,
+    /// Only note stabilization versions newer than this one
+    pub since: Option<&'a str>,
+    /// Order items by name or as declared in the source
+    pub order: ItemOrder,
+    /// Wrap each impl block in a collapsed `<details>` element
+    pub collapsible: bool,
+    /// Keep the lines rustdoc hides in Rust examples
+    pub keep_hidden_lines: bool,
+    /// Repeat functions' `# Returns`, `# Errors`, and `# Panics` sections under their
+    /// signatures
+    pub extract_sections: bool,
+    /// Keep each file's generated markdown in the returned [`MarkdownOutput::files`]
+    /// rather than only writing it
+    pub keep_content: bool,
+}
+
+impl<'a> ConversionOptions<'a> {
+    /// Options for converting `input_path` into `output_dir`, with every other
+    /// setting at its default: public items only, one file per module, no extras.
+    pub fn new(input_path: &'a Path, output_dir: &'a Path) -> Self {
+        ConversionOptions {
+            input_path,
+            output_dir,
+            include_private: false,
+            visibility: VisibilityLevel::Public,
+            frontmatter: false,
+            documented_crates: &[],
+            disambiguated_items: &[],
+            exclude: &[],
+            src_base: None,
+            crate_dir: None,
+            single_file: false,
+            max_depth: None,
+            wikilinks: false,
+            base_url: None,
+            since: None,
+            order: ItemOrder::Alpha,
+            collapsible: false,
+            keep_hidden_lines: false,
+            extract_sections: false,
+            keep_content: false,
+        }
+    }
+
+    /// The rendering options passed to the converter.
+    pub fn markdown_options(&self) -> MarkdownOptions {
+        MarkdownOptions {
+            include_private: self.include_private,
+            visibility: self.visibility,
+            frontmatter: self.frontmatter,
+            documented_crates: self.documented_crates.iter().cloned().collect(),
+            disambiguated_items: self.disambiguated_items.iter().cloned().collect(),
+            crate_dir: self.crate_dir.map(String::from),
+            exclude: self.exclude.to_vec(),
+            src_base: self.src_base.map(String::from),
+            single_file: self.single_file,
+            max_depth: self.max_depth,
+            wikilinks: self.wikilinks,
+            base_url: self.base_url.map(String::from),
+            since: self.since.map(String::from),
+            order: self.order,
+            collapsible: self.collapsible,
+            keep_hidden_lines: self.keep_hidden_lines,
+            extract_sections: self.extract_sections,
+        }
+    }
 }
 
 /// Convert a rustdoc JSON file to markdown (multi-file output).
@@ -47,7 +144,10 @@ pub struct ConversionOptions<'a> {
 ///
 /// # Returns
 ///
-/// Returns `Ok(())` on success, or an error if the conversion fails.
+/// Returns the generated output, which has already been written to `output_dir`,
+/// or an error if the conversion fails. Its `files` are empty unless
+/// [`keep_content`](ConversionOptions::keep_content) is set; `file_sizes` lists every
+/// file written.
 ///
 /// # Example
 ///
@@ -55,22 +155,345 @@ pub struct ConversionOptions<'a> {
 /// use cargo_doc_md::{convert_json_file, ConversionOptions};
 /// use std::path::Path;
 ///
-/// let options = ConversionOptions {
-///     input_path: Path::new("target/doc/my_crate.json"),
-///     output_dir: Path::new("docs"),
-///     include_private: false,
-/// };
+/// let mut options = ConversionOptions::new(
+///     Path::new("target/doc/my_crate.json"),
+///     Path::new("docs"),
+/// );
+/// options.include_private = true;
 ///
 /// convert_json_file(&options).expect("Conversion failed");
 /// ```
-pub fn convert_json_file(options: &ConversionOptions) -> Result<()> {
-    let crate_data = parser::load_rustdoc_json(options.input_path)?;
-    let output = converter::convert_to_markdown_multifile(&crate_data, options.include_private)?;
-
-    // Write to crate-specific subdirectory
-    let crate_output_dir = options.output_dir.join(&output.crate_name);
-    writer::write_markdown_multifile(&crate_output_dir, &output)?;
-    Ok(())
+pub fn convert_json_file(options: &ConversionOptions) -> Result<MarkdownOutput> {
+    convert_json_file_with_writer(options, |_path, content| content.to_string())
+}
+
+/// Convert a rustdoc JSON file to markdown, post-processing each file before it is written.
+///
+/// Behaves like [`convert_json_file`], but `transform` is called with every file's path
+/// (relative to `output_dir`, e.g. `my_crate/index.md`) and generated markdown, and its
+/// return value is what gets written. Each file is written as soon as it is generated, to a
+/// hidden directory next to the crate's that replaces it once conversion succeeds.
+/// With [`keep_content`](ConversionOptions::keep_content) set, the returned
+/// [`MarkdownOutput`] holds the untransformed markdown; otherwise each file is dropped
+/// once written.
+///
+/// # Example
+///
+/// ```no_run
+/// use cargo_doc_md::{convert_json_file_with_writer, ConversionOptions};
+/// use std::path::Path;
+///
+/// let options = ConversionOptions::new(
+///     Path::new("target/doc/my_crate.json"),
+///     Path::new("docs"),
+/// );
+///
+/// convert_json_file_with_writer(&options, |path, markdown| {
+///     format!("<!-- generated from {} -->\n{}", path.display(), markdown)
+/// })
+/// .expect("Conversion failed");
+/// ```
+pub fn convert_json_file_with_writer<F>(
+    options: &ConversionOptions,
+    mut transform: F,
+) -> Result<MarkdownOutput>
+where
+    F: FnMut(&Path, &str) -> String,
+{
+    let path = options.input_path;
+    let model = parse_json_file(path)?;
+    let crate_dir = options.crate_dir.unwrap_or(model.name());
+
+    let target = if options.single_file {
+        PathBuf::from(format!("{}.md", crate_dir))
+    } else {
+        PathBuf::from(crate_dir)
+    };
+    let staged = writer::StagedOutput::new(options.output_dir, &target)?;
+
+    let mut files = std::collections::HashMap::new();
+    let mut output = converter::convert_to_markdown_streaming(
+        model.crate_data(),
+        &options.markdown_options(),
+        |file, content| {
+            let (relative_path, staged_path) = if options.single_file {
+                (target.clone(), staged.relative_path().to_path_buf())
+            } else {
+                (target.join(file), staged.relative_path().join(file))
+            };
+            writer::write_file(
+                options.output_dir,
+                &staged_path,
+                &transform(&relative_path, &content),
+            )?;
+            if options.keep_content {
+                files.insert(file.to_string(), content);
+            }
+            Ok(())
+        },
+    )
+    .with_context(|| format!("Failed to convert: {}", path.display()))?;
+    staged.commit()?;
+    output.files = files;
+    Ok(output)
+}
+
+/// Parse a rustdoc JSON file, which may be gzip-compressed, into a [`DocModel`].
+///
+/// Converting a file is parsing it and rendering the model; use this to traverse the
+/// crate's modules and items yourself instead.
+///
+/// # Example
+///
+/// ```no_run
+/// use std::path::Path;
+///
+/// let model = cargo_doc_md::parse_json_file(Path::new("target/doc/my_crate.json"))
+///     .expect("Failed to parse");
+///
+/// for item in model.items().filter(|item| item.is_public() && item.is_async()) {
+///     println!("{}", item.path());
+/// }
+/// ```
+pub fn parse_json_file(path: &Path) -> Result<DocModel> {
+    read_rustdoc_json(path).map(DocModel::new)
+}
+
+/// Parse rustdoc JSON held in memory into a [`DocModel`].
+pub fn parse_json_str(json: &str) -> Result<DocModel> {
+    parser::parse_rustdoc_json(json).map(DocModel::new)
+}
+
+/// Convert every rustdoc JSON file in a directory, with a master index linking them.
+///
+/// Each `*.json` or `*.json.gz` file in `input_dir` (not its subdirectories) replaces
+/// a directory of `output_dir` named after its crate, as [`convert_json_file`] does, and
+/// `output_dir/index.md` lists them all. The crates are added to `documented_crates`,
+/// so intra-doc links between them become relative links.
+///
+/// # Returns
+///
+/// Returns the output for each crate, in file name order, or an error if
+/// `input_dir` has no JSON files, two of them document the same crate, or any of them
+/// fails to convert.
+///
+/// # Example
+///
+/// ```no_run
+/// use cargo_doc_md::converter::MarkdownOptions;
+/// use std::path::Path;
+///
+/// let outputs = cargo_doc_md::convert_directory(
+///     Path::new("artifacts/rustdoc"),
+///     Path::new("docs"),
+///     &MarkdownOptions::default(),
+/// )
+/// .expect("Conversion failed");
+/// println!("Converted {} crates", outputs.len());
+/// ```
+pub fn convert_directory(
+    input_dir: &Path,
+    output_dir: &Path,
+    options: &MarkdownOptions,
+) -> Result<Vec<MarkdownOutput>> {
+    let json_paths = parser::json_files_in(input_dir)?;
+    let crates: Vec<(PathBuf, rustdoc_types::Crate)> = json_paths
+        .into_iter()
+        .map(|path| read_rustdoc_json(&path).map(|crate_data| (path, crate_data)))
+        .collect::<Result<_>>()?;
+
+    // Each crate is written to a directory named after it, which only one may have
+    let mut paths_by_name: std::collections::HashMap<&str, &Path> =
+        std::collections::HashMap::new();
+    for (path, crate_data) in &crates {
+        if let Some(other) = paths_by_name.insert(crate_name(crate_data), path) {
+            bail!(
+                "{} and {} both document crate `{}`",
+                other.display(),
+                path.display(),
+                crate_name(crate_data)
+            );
+        }
+    }
+
+    let mut options = options.clone();
+    options
+        .documented_crates
+        .extend(crates.iter().map(|(_, crate_data)| {
+            let name = crate_name(crate_data).to_string();
+            (name.clone(), name)
+        }));
+    for (_, crate_data) in &crates {
+        let disambiguated = converter::disambiguated_items(crate_data, &options);
+        options.disambiguated_items.extend(disambiguated);
+    }
+
+    let mut outputs = Vec::new();
+    for (path, crate_data) in &crates {
+        let output = converter::convert_to_markdown_multifile(crate_data, &options)
+            .with_context(|| format!("Failed to convert: {}", path.display()))?;
+        let target = if options.single_file {
+            PathBuf::from(format!("{}.md", output.crate_name))
+        } else {
+            PathBuf::from(&output.crate_name)
+        };
+        let staged = writer::StagedOutput::new(output_dir, &target)?;
+        for (file, content) in output.files_in_order() {
+            let staged_path = if options.single_file {
+                staged.relative_path().to_path_buf()
+            } else {
+                staged.relative_path().join(file)
+            };
+            writer::write_file(output_dir, &staged_path, content)?;
+        }
+        staged.commit()?;
+        outputs.push(output);
+    }
+
+    let mut index = String::new();
+    if options.frontmatter {
+        index.push_str(&converter::frontmatter("Documentation Index", "index", 1));
+    }
+    index.push_str("# Documentation Index\n\n");
+    index.push_str(&format!("## Crates ({})\n\n", outputs.len()));
+    for output in &outputs {
+        let target = if options.single_file {
+            format!("{}.md", output.crate_name)
+        } else {
+            format!("{}/index.md", output.crate_name)
+        };
+        index.push_str(&format!("- [`{}`]({})", output.crate_name, target));
+        if let Some(summary) = &output.crate_summary {
+            index.push_str(&format!(" - {}", summary));
+        }
+        index.push('\n');
+    }
+    if options.wikilinks {
+        index = converter::wikilinks(&index, "");
+    }
+    if let Some(base_url) = &options.base_url {
+        index = converter::base_url_links(&index, "", base_url);
+    }
+    writer::write_file(output_dir, Path::new("index.md"), &index)?;
+
+    Ok(outputs)
+}
+
+/// Convert rustdoc JSON held in memory to markdown without touching the filesystem.
+///
+/// Runs the same pipeline as [`convert_json_file`], but returns the output instead
+/// of writing it.
+///
+/// # Arguments
+///
+/// * `json` - The rustdoc JSON as a string
+/// * `options` - Rendering options for the conversion
+///
+/// # Returns
+///
+/// Returns `(relative path, markdown)` pairs in reading order: the crate index, the
+/// root module, then the remaining modules depth-first. Paths are relative to the
+/// output directory and start with the crate name (e.g. `my_crate/index.md`). With
+/// [`MarkdownOptions::single_file`] there is one pair, `my_crate.md`.
+///
+/// # Example
+///
+/// ```no_run
+/// use cargo_doc_md::converter::MarkdownOptions;
+///
+/// let json = std::fs::read_to_string("target/doc/my_crate.json").unwrap();
+/// let files = cargo_doc_md::convert_json_str(&json, &MarkdownOptions::default())
+///     .expect("Conversion failed");
+///
+/// for (path, markdown) in &files {
+///     println!("{}: {} bytes", path.display(), markdown.len());
+/// }
+/// ```
+pub fn convert_json_str(json: &str, options: &MarkdownOptions) -> Result<Vec<(PathBuf, String)>> {
+    let output = parse_json_str(json)?.render(options)?;
+    Ok(crate_relative_files(
+        &output,
+        &output.crate_name,
+        options.single_file,
+    ))
+}
+
+/// Files in reading order, with paths prefixed by the crate directory.
+///
+/// Single-file output has no directory: its one file is named after `crate_dir`.
+fn crate_relative_files(
+    output: &MarkdownOutput,
+    crate_dir: &str,
+    single_file: bool,
+) -> Vec<(PathBuf, String)> {
+    if single_file {
+        return output
+            .files
+            .values()
+            .map(|content| (PathBuf::from(format!("{}.md", crate_dir)), content.clone()))
+            .collect();
+    }
+
+    let crate_dir = Path::new(crate_dir);
+    output
+        .files_in_order()
+        .into_iter()
+        .map(|(path, content)| (crate_dir.join(path), content.to_string()))
+        .collect()
+}
+
+/// Convert a rustdoc JSON file to a single markdown string instead of writing files.
+///
+/// Files are concatenated in the order returned by [`convert_json_str`], so the
+/// output is stable across runs.
+///
+/// # Arguments
+///
+/// * `options` - Configuration for the conversion (`output_dir` is not used)
+///
+/// # Returns
+///
+/// Returns the concatenated markdown, or an error if the conversion fails.
+pub fn convert_json_file_to_string(options: &ConversionOptions) -> Result<String> {
+    let mut markdown = String::new();
+    let output = convert_json_path(options)?;
+    for (_path, content) in crate_relative_files(&output, &output.crate_name, options.single_file) {
+        markdown.push_str(content.trim_end());
+        markdown.push_str("\n\n");
+    }
+    Ok(markdown)
+}
+
+/// Convert a rustdoc JSON file to the structured doc model instead of markdown.
+///
+/// See [`converter::convert_to_ir`] for the schema. Nothing is written; `output_dir`,
+/// `frontmatter`, `crate_dir`, and `single_file` are not used.
+pub fn convert_json_file_to_ir(options: &ConversionOptions) -> Result<serde_json::Value> {
+    let path = options.input_path;
+    let crate_data = read_rustdoc_json(path)?;
+    converter::convert_to_ir(&crate_data, &options.markdown_options())
+        .with_context(|| format!("Failed to convert: {}", path.display()))
+}
+
+fn convert_json_path(options: &ConversionOptions) -> Result<MarkdownOutput> {
+    let path = options.input_path;
+    let crate_data = read_rustdoc_json(path)?;
+    converter::convert_to_markdown_multifile(&crate_data, &options.markdown_options())
+        .with_context(|| format!("Failed to convert: {}", path.display()))
+}
+
+fn read_rustdoc_json(path: &Path) -> Result<rustdoc_types::Crate> {
+    let json = parser::read_json_file(path)?;
+    parser::parse_rustdoc_json(&json)
+        .with_context(|| format!("Failed to convert: {}", path.display()))
+}
+
+fn crate_name(crate_data: &rustdoc_types::Crate) -> &str {
+    crate_data
+        .index
+        .get(&crate_data.root)
+        .and_then(|item| item.name.as_deref())
+        .unwrap_or("unknown")
 }
 
 /// Convert rustdoc JSON data (already loaded) to markdown.
@@ -86,6 +509,6 @@ pub fn convert_json_file(options: &ConversionOptions) -> Result<()> {
 ///
 /// Returns the markdown as a String, or an error.
 pub fn convert_json_string(json_data: &str, include_private: bool) -> Result<String> {
-    let crate_data: rustdoc_types::Crate = serde_json::from_str(json_data)?;
+    let crate_data = parser::parse_rustdoc_json(json_data)?;
     converter::convert_to_markdown(&crate_data, include_private)
 }
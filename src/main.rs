@@ -1,9 +1,66 @@
 use anyhow::{Context, Result, bail};
 use cargo_doc_md::ConversionOptions;
-use clap::Parser;
-use std::collections::HashMap;
+use cargo_doc_md::converter::{
+    ItemOrder, MarkdownOutput, VisibilityLevel, base_url_links, frontmatter, glob_matches,
+    heading_anchors, markdown_links, wikilinks,
+};
+use clap::parser::ValueSource;
+use clap::{CommandFactory, FromArgMatches, Parser, ValueEnum, ValueHint};
+use serde_json::json;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::ffi::OsString;
+use std::num::NonZeroUsize;
 use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::time::Duration;
+
+/// Set in `--stdout` mode so progress messages go to stderr and stdout carries only markdown.
+static PROGRESS_TO_STDERR: AtomicBool = AtomicBool::new(false);
+
+/// Set by `--quiet` to drop progress messages; problems still reach stderr.
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+/// Set once any crate ends up in a run's failed list, for `--fail-on-warning`.
+static ANY_FAILED: AtomicBool = AtomicBool::new(false);
+
+/// Serializes progress output from concurrent dependency jobs.
+static PROGRESS_LOCK: Mutex<()> = Mutex::new(());
+
+/// Items whose headings have their kind appended, by the output directory of their
+/// crate, for crates converted in this run or recorded in an earlier run's manifest.
+static DISAMBIGUATED: Mutex<BTreeMap<String, Vec<(String, String)>>> = Mutex::new(BTreeMap::new());
+
+/// Crates written as markdown in this run, for [`relink_converted_crates`].
+static CONVERTED: Mutex<Vec<ConvertedCrate>> = Mutex::new(Vec::new());
+
+/// Print a progress message, keeping stdout clean in `--stdout` mode and silent with `--quiet`.
+macro_rules! progress {
+    ($($arg:tt)*) => {{
+        let _guard = lock(&PROGRESS_LOCK);
+        if !QUIET.load(Ordering::Relaxed) {
+            if PROGRESS_TO_STDERR.load(Ordering::Relaxed) {
+                eprintln!($($arg)*);
+            } else {
+                println!($($arg)*);
+            }
+        }
+    }};
+}
+
+/// Report a crate that failed or was only partly documented; unlike `progress!`,
+/// this still prints (to stderr) with `--quiet`.
+macro_rules! problem {
+    ($($arg:tt)*) => {{
+        if QUIET.load(Ordering::Relaxed) {
+            let _guard = lock(&PROGRESS_LOCK);
+            eprintln!($($arg)*);
+        } else {
+            progress!($($arg)*);
+        }
+    }};
+}
 
 #[derive(Parser)]
 #[command(name = "cargo-doc-md")]
@@ -18,7 +75,8 @@ use std::process::Command;
                   cargo doc-md --no-deps          # Document current crate only (no dependencies)\n  \
                   cargo doc-md -p tokio           # Document tokio + all its dependencies\n  \
                   cargo doc-md -p tokio -p serde  # Document multiple packages + their dependencies\n  \
-                  cargo doc-md --json file.json   # Convert existing rustdoc JSON"
+                  cargo doc-md --json file.json   # Convert existing rustdoc JSON\n  \
+                  cargo doc-md --stdout | less    # Print markdown instead of writing files"
 )]
 struct Cli {
     #[arg(
@@ -33,24 +91,82 @@ struct Cli {
     #[arg(
         short,
         long,
-        default_value = "target/doc-md",
-        help = "Output directory [default: target/doc-md]\n\
+        default_value_os_t = default_output_dir(),
+        help = "Output directory [default: $CARGO_DOC_MD_OUTPUT, or target/doc-md if unset]\n\
+                An explicit --output takes precedence over CARGO_DOC_MD_OUTPUT\n\
                 Creates: target/doc-md/index.md (master index), target/doc-md/crate_name/*.md (modules)"
     )]
     output: PathBuf,
 
-    #[arg(long, help = "Include private items in documentation")]
+    #[arg(
+        long,
+        help = "Include private items in documentation, noting each item's visibility\n\
+                (pub, pub(crate), or private)"
+    )]
     include_private: bool,
 
     #[arg(
         long,
-        help = "Convert existing rustdoc JSON file",
+        value_enum,
+        value_name = "LEVEL",
+        help = "Include items down to this visibility, noting each item's visibility\n\
+                Example: --visibility crate includes pub(crate) items but not private ones",
+        conflicts_with = "include_private"
+    )]
+    visibility: Option<Visibility>,
+
+    #[arg(
+        long,
+        help = "Convert existing rustdoc JSON file (optionally gzipped, e.g. tokio.json.gz)",
         conflicts_with = "package",
         conflicts_with = "workspace",
         conflicts_with = "no_deps"
     )]
     json: Option<PathBuf>,
 
+    #[arg(
+        long,
+        value_name = "DIR",
+        help = "Convert every rustdoc JSON file in DIR (*.json or *.json.gz), e.g. target/doc\n\
+                after `cargo +nightly doc` with --output-format json; runs neither cargo nor rustdoc",
+        conflicts_with_all = [
+            "json", "package", "workspace", "no_deps", "include_dev_deps", "bin", "index_only",
+            "target"
+        ]
+    )]
+    json_dir: Option<PathBuf>,
+
+    #[arg(
+        long,
+        value_name = "CRATE",
+        help = "Regenerate only these crates (can be repeated), whether the current crate,\n\
+                workspace members, or dependencies; other crates' output is left as is and the\n\
+                master index is rebuilt from every crate in the output directory",
+        conflicts_with_all = ["json", "json_dir", "package", "index_only", "stdout"]
+    )]
+    only: Vec<String>,
+
+    #[arg(
+        long,
+        value_name = "PATH",
+        help = "Path to the Cargo.toml of the crate or workspace to document, instead of the\n\
+                one in the current directory (passed to cargo metadata and cargo rustdoc)",
+        conflicts_with_all = ["json", "json_dir", "index_only"]
+    )]
+    manifest_path: Option<PathBuf>,
+
+    #[arg(
+        long,
+        value_name = "FILE",
+        help = "Read default options from this TOML file [default: cargo-doc-md.toml next to\n\
+                the crate's Cargo.toml, if there is one]. Keys are option names, e.g.\n\
+                `output = \"docs\"`, `include-private = true`, `exclude = [\"*::tests\"]`; relative\n\
+                paths are resolved from the file's directory. Options on the command line take\n\
+                precedence over the file, including ones that conflict with it, and so does\n\
+                CARGO_DOC_MD_OUTPUT over its `output`"
+    )]
+    config: Option<PathBuf>,
+
     #[arg(
         long,
         help = "Document all workspace members (idiomatic cargo pattern)",
@@ -64,1035 +180,3717 @@ struct Cli {
         conflicts_with = "json"
     )]
     no_deps: bool,
-}
 
-fn main() -> Result<()> {
-    // When invoked as `cargo doc-md`, cargo passes an extra "doc-md" argument
-    // Skip it if present to support both `cargo doc-md` and `cargo-doc-md` invocations
-    let args = std::env::args()
-        .enumerate()
-        .filter(|(i, arg)| !(*i == 1 && arg == "doc-md"))
-        .map(|(_, arg)| arg);
+    #[arg(
+        long,
+        help = "Also document the dev-dependencies of the documented crates (but not of\n\
+                their dependencies), and everything those depend on",
+        conflicts_with_all = ["json", "no_deps"]
+    )]
+    include_dev_deps: bool,
 
-    let cli = Cli::parse_from(args);
+    #[arg(
+        long,
+        help = "Print concatenated markdown to stdout instead of writing files",
+        conflicts_with = "output"
+    )]
+    stdout: bool,
 
-    // Verify nightly toolchain is available (unless only using --json mode)
-    if cli.json.is_none() {
-        check_nightly_toolchain()?;
-    }
+    #[arg(
+        short,
+        long,
+        help = "Suppress progress output; errors are still printed to stderr"
+    )]
+    quiet: bool,
 
-    // Validate output directory
-    validate_output_directory(&cli.output)?;
+    #[arg(
+        short,
+        long,
+        help = "Print cargo's full error output when a dependency fails to build"
+    )]
+    verbose: bool,
 
-    // Explicit JSON file - just convert that file
-    if let Some(json_path) = cli.json.as_ref() {
-        if !json_path.exists() {
-            bail!("JSON file not found: {}", json_path.display());
-        }
-        if !json_path.is_file() {
-            bail!("Path is not a file: {}", json_path.display());
-        }
+    #[arg(
+        long,
+        help = "Also write a SUMMARY.md for mdBook at the output root",
+        conflicts_with = "stdout"
+    )]
+    mdbook: bool,
 
-        // Load the JSON to extract the actual crate name from metadata
-        let crate_data = cargo_doc_md::parser::load_rustdoc_json(json_path)?;
-        let root_item = crate_data
-            .index
-            .get(&crate_data.root)
-            .context("Root item not found in rustdoc JSON")?;
-        let crate_name = root_item
-            .name
-            .as_deref()
-            .context("Crate name not found in rustdoc JSON")?;
-
-        // Remove existing crate directory to ensure clean documentation
-        let crate_output_dir = cli.output.join(crate_name);
-        if crate_output_dir.exists() {
-            std::fs::remove_dir_all(&crate_output_dir).with_context(|| {
-                format!(
-                    "Failed to remove existing crate directory: {}",
-                    crate_output_dir.display()
-                )
-            })?;
-        }
+    #[arg(
+        long,
+        help = "Link between files with Obsidian [[wikilinks]] instead of markdown links",
+        conflicts_with = "mdbook"
+    )]
+    wikilinks: bool,
 
-        let options = ConversionOptions {
-            input_path: json_path,
-            output_dir: &cli.output,
-            include_private: cli.include_private,
-        };
+    #[arg(
+        long,
+        visible_alias = "relative-to",
+        value_name = "URL",
+        help = "Start links between files with the URL or path the output directory is served\n\
+                from, instead of making them relative to the linking file\n\
+                Example: --base-url /docs/api",
+        conflicts_with_all = ["wikilinks", "stdout"]
+    )]
+    base_url: Option<String>,
 
-        cargo_doc_md::convert_json_file(&options)?;
+    #[arg(
+        long,
+        help = "Also write an llms.txt at the output root: each crate's modules and top-level\n\
+                items with one-line summaries and links to their markdown",
+        conflicts_with = "stdout"
+    )]
+    llms_txt: bool,
 
-        // Generate master index for consistency with other modes
-        generate_master_index(&cli.output, None, &[], &[crate_name.to_string()])?;
+    #[arg(
+        long,
+        help = "Also write an index.txt at the output root listing every generated markdown\n\
+                file's path relative to it, one per line, sorted",
+        conflicts_with = "stdout"
+    )]
+    file_list: bool,
 
-        return Ok(());
-    }
+    #[arg(
+        long,
+        help = "Prepend YAML frontmatter (title, slug, sidebar_position) to generated files"
+    )]
+    frontmatter: bool,
 
-    // Get cargo metadata once for all operations
-    let metadata = get_cargo_metadata()?;
+    #[arg(
+        long,
+        help = "Write each crate as one <crate>.md file with in-document links"
+    )]
+    single_file: bool,
 
-    // Workspace mode
-    if cli.workspace {
-        document_workspace(&metadata, &cli)?;
-        return Ok(());
-    }
+    #[arg(
+        long,
+        value_name = "N",
+        help = "Give only modules up to N levels below the crate root their own file\n\
+                Deeper modules become sections of their ancestor's file; 0 puts everything in index.md",
+        conflicts_with = "single_file"
+    )]
+    max_depth: Option<usize>,
 
-    // Specific packages requested
-    if !cli.package.is_empty() {
-        document_specific_packages(&metadata, &cli)?;
-        return Ok(());
-    }
+    #[arg(
+        long,
+        value_name = "VERSION",
+        help = "Only note \"Stable since\" versions newer than VERSION, e.g. your MSRV\n\
+                Items carrying #[stable]/#[unstable] attributes, as in std, get a stability note"
+    )]
+    since: Option<String>,
 
-    // Default: document current crate + all transitive dependencies (matches cargo doc)
-    if cli.no_deps {
-        println!("📚 Documenting current crate only...\n");
-        let current_crate = document_current_crate(&metadata, &cli)?;
-        generate_master_index(&cli.output, current_crate.as_deref(), &[], &[])?;
-    } else {
-        println!("📚 Documenting current crate and all transitive dependencies...\n");
-        let current_crate = document_current_crate(&metadata, &cli)?;
-        println!();
-        let documented_deps = document_all_dependencies(&metadata, &cli)?;
-        generate_master_index(&cli.output, current_crate.as_deref(), &[], &documented_deps)?;
-    }
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = Order::Alpha,
+        help = "How to order the items within each module's file and contents listing"
+    )]
+    order: Order,
 
-    Ok(())
-}
+    #[arg(
+        long,
+        help = "Wrap each impl block in a collapsed <details> section summarized by its\n\
+                signature, for renderers that support HTML in markdown, like GitHub"
+    )]
+    collapsible: bool,
 
-#[derive(Debug)]
-struct Dependency {
-    name: String,
-    version: String,
-}
+    #[arg(
+        long,
+        help = "Keep the `# ` lines rustdoc hides in Rust examples, so examples can be\n\
+                copied and run as they are"
+    )]
+    keep_hidden_lines: bool,
 
-fn get_cargo_metadata() -> Result<serde_json::Value> {
-    // Get current host platform for filtering platform-specific dependencies
-    let host_triple = std::env::var("CARGO_BUILD_TARGET").or_else(|_| {
-        let output = Command::new("rustc")
-            .args(["-vV"])
-            .output()
-            .context("Failed to run rustc")?;
+    #[arg(
+        long,
+        help = "Repeat the # Returns, # Errors, and # Panics sections of a function's docs as\n\
+                **Returns:**, **Errors:**, and **Panics:** notes under its signature"
+    )]
+    extract_sections: bool,
 
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        stdout
-            .lines()
-            .find(|line| line.starts_with("host:"))
-            .and_then(|line| line.split_whitespace().nth(1))
-            .map(String::from)
-            .context("Failed to parse host triple from rustc")
-    })?;
+    #[arg(
+        long,
+        help = "Also write <crate>/search.json listing every item's path, kind, summary, and anchor",
+        conflicts_with = "stdout"
+    )]
+    search_index: bool,
 
-    let output = Command::new("cargo")
-        .args([
-            "metadata",
-            "--format-version=1",
-            "--filter-platform",
-            &host_triple,
-        ])
-        .output()
-        .context("Failed to run 'cargo metadata'")?;
+    #[arg(
+        long,
+        help = "After generating, print how many structs, enums, traits, functions, constants,\n\
+                type aliases, and modules each crate's markdown documents, with totals",
+        conflicts_with = "stdout"
+    )]
+    stats: bool,
 
-    if !output.status.success() {
-        bail!(
-            "cargo metadata failed: {}",
-            String::from_utf8_lossy(&output.stderr)
-        );
-    }
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = Emit::Markdown,
+        value_name = "FORMAT",
+        help = "What to generate for each crate\n\
+                ir: one <crate>.json doc model per crate (see converter::convert_to_ir), without indexes"
+    )]
+    emit: Emit,
 
-    serde_json::from_slice(&output.stdout).context("Failed to parse cargo metadata")
-}
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = Layout::Flat,
+        help = "Where each crate's directory goes under the output directory"
+    )]
+    layout: Layout,
 
-fn validate_output_directory(output_dir: &Path) -> Result<()> {
-    if output_dir.exists() && output_dir.is_file() {
-        bail!(
-            "Output path exists but is a file, not a directory: {}\n\
-             Please specify a directory path or remove the file.",
-            output_dir.display()
-        );
-    }
+    #[arg(
+        short,
+        long,
+        value_name = "N",
+        help = "Number of dependencies to document in parallel [default: number of CPUs]"
+    )]
+    jobs: Option<NonZeroUsize>,
 
-    Ok(())
-}
+    #[arg(
+        long,
+        value_name = "N",
+        default_value_t = 2,
+        help = "Times to retry a dependency whose build failed waiting on cargo's file lock"
+    )]
+    retries: u32,
 
-fn check_nightly_toolchain() -> Result<()> {
-    let output = Command::new("cargo")
-        .args(["+nightly", "--version"])
-        .output()
-        .context("Failed to run cargo +nightly")?;
+    #[arg(
+        long,
+        help = "Reconvert every crate, ignoring the cache in <output>/.cache"
+    )]
+    no_cache: bool,
 
-    if !output.status.success() {
-        bail!(
-            "Nightly toolchain not installed or not available.\n\
-             This tool requires Rust nightly for unstable rustdoc features.\n\
-             Install with: rustup install nightly"
-        );
-    }
+    #[arg(
+        long,
+        value_name = "GLOB",
+        help = "Skip modules and items whose full path matches the pattern (can be repeated)\n\
+                With --workspace, also skips members whose name matches; they are still\n\
+                documented as dependencies of the members that use them\n\
+                Example: --exclude 'my_crate::internal::*' --exclude '*::tests' --exclude 'fuzz-*'"
+    )]
+    exclude: Vec<String>,
 
-    Ok(())
-}
+    #[arg(
+        long,
+        value_name = "URL",
+        help = "Link each item heading to its source under this URL\n\
+                Example: --src-base https://github.com/owner/repo/blob/main"
+    )]
+    src_base: Option<String>,
 
-fn document_specific_packages(metadata: &serde_json::Value, cli: &Cli) -> Result<()> {
-    println!(
-        "📦 Documenting {} specific package(s) and their dependencies...",
-        cli.package.len()
-    );
+    #[arg(
+        long,
+        value_name = "NAME",
+        num_args = 0..=1,
+        help = "Document the current crate's binary target instead of its library\n\
+                Without NAME, the crate must have exactly one binary",
+        conflicts_with_all = ["json", "workspace", "package"]
+    )]
+    bin: Option<Option<String>>,
 
-    let packages = metadata["packages"]
-        .as_array()
-        .context("Missing 'packages' in metadata")?;
+    #[arg(
+        long,
+        value_enum,
+        value_delimiter = ',',
+        value_name = "KINDS",
+        help = "Targets of the current crate to document, e.g. --targets lib,bins,examples\n\
+                Beyond the library, every target gets a directory under the crate's: lib/,\n\
+                bin_<name>/, and example_<name>/, linked from the crate's index.md [default: lib]",
+        conflicts_with_all = [
+            "json", "json_dir", "workspace", "package", "bin", "single_file", "index_only",
+            "stdout"
+        ]
+    )]
+    targets: Vec<TargetKind>,
 
-    let target_dir = PathBuf::from(metadata["target_directory"].as_str().unwrap_or("target"));
+    #[arg(
+        long,
+        help = "Only rebuild <output>/index.md from the crates already in the output directory\n\
+                Runs neither cargo nor the converter",
+        conflicts_with_all = ["json", "package", "workspace", "no_deps", "stdout", "bin"]
+    )]
+    index_only: bool,
 
-    // For -p mode, don't filter out workspace member dependencies
-    // User explicitly requested specific packages, so document them and ALL their deps
-    let workspace_member_ids: Vec<String> = Vec::new();
+    #[arg(
+        long,
+        help = "Don't write the master index, <output>/index.md\n\
+                Each crate's own index.md and module files are written as usual",
+        conflicts_with = "index_only"
+    )]
+    no_index: bool,
 
-    let mut successful_packages = Vec::new();
-    let mut failed_packages = Vec::new();
-    let mut all_deps = HashMap::new();
+    #[arg(
+        long,
+        help = "Leave the \"Generated with cargo-doc-md\" footer out of the master index"
+    )]
+    no_footer: bool,
 
-    // Document each specified package
-    for package_name in &cli.package {
-        println!("\n🔨 Generating docs for '{}'...", package_name);
+    #[arg(
+        long,
+        value_name = "OLD_DIR",
+        help = "After generating, print which markdown files differ from OLD_DIR and how\n\
+                Exits with status 1 when any file was added, removed, or modified",
+        conflicts_with = "stdout"
+    )]
+    diff: Option<PathBuf>,
 
-        // Find package in metadata
-        let package = packages
-            .iter()
-            .find(|p| p["name"].as_str() == Some(package_name));
+    #[arg(
+        long,
+        help = "After generating, check that every relative link in the output points at an\n\
+                existing file and heading; broken links are listed on stderr with their file\n\
+                and line, and the exit status is 1 if there are any. Web links are not checked",
+        conflicts_with_all = ["stdout", "wikilinks"]
+    )]
+    check_links: bool,
 
-        let dep = if let Some(pkg) = package {
-            let name = pkg["name"]
-                .as_str()
-                .context("Package missing 'name' field in metadata")?
-                .to_string();
-            let version = pkg["version"].as_str().unwrap_or("").to_string();
-            Dependency { name, version }
-        } else {
-            Dependency {
-                name: package_name.clone(),
-                version: String::new(),
-            }
-        };
+    #[arg(
+        long,
+        visible_alias = "strict",
+        help = "Exit with status 1 if any dependency, package, or workspace member failed to\n\
+                document. The rest are still documented and the summary lists every failure"
+    )]
+    fail_on_warning: bool,
 
-        match document_single_dependency(
-            &dep,
-            &cli.output,
-            &target_dir,
-            metadata,
-            cli.include_private,
-        ) {
-            Ok(true) => {
-                // Successfully documented
-                successful_packages.push(package_name.clone());
-                println!(
-                    "  ✓ {} → {}/{}/index.md",
-                    package_name,
-                    cli.output.display(),
-                    package_name.replace("-", "_")
-                );
+    #[arg(
+        long,
+        help = "After generating, open the master index with the platform's default\n\
+                application (xdg-open, open, or start)",
+        conflicts_with_all = ["stdout", "no_index"]
+    )]
+    open: bool,
 
-                // Get dependencies for this package if not --no-deps
-                if !cli.no_deps {
-                    if let Some(pkg) = package {
-                        if let Some(pkg_id) = pkg["id"].as_str() {
-                            match get_all_dependencies_recursive(
-                                metadata,
-                                pkg_id,
-                                &workspace_member_ids,
-                            ) {
-                                Ok(deps) => {
-                                    for (name, version) in deps {
-                                        if !successful_packages.contains(&name) {
-                                            all_deps.insert(name, version);
-                                        }
-                                    }
-                                }
-                                Err(e) => {
-                                    println!(
-                                        "  ⚠ Warning: Could not get dependencies for '{}': {}",
-                                        package_name, e
-                                    );
-                                }
-                            }
-                        }
-                    }
-                }
-            }
-            Ok(false) => {
-                // Skipped (e.g., binary-only crate)
-                println!("  ⊘ {} skipped", package_name);
-            }
-            Err(e) => {
-                failed_packages.push(package_name.clone());
-                println!("  ✗ Failed to document '{}': {}", package_name, e);
-            }
-        }
-    }
-
-    // Document dependencies if not --no-deps
-    if !cli.no_deps && !all_deps.is_empty() {
-        println!("\n📦 Documenting {} unique dependencies...", all_deps.len());
-        let mut deps_to_document: Vec<Dependency> = all_deps
-            .into_iter()
-            .map(|(name, version)| Dependency { name, version })
-            .collect();
-        deps_to_document.sort_by(|a, b| a.name.cmp(&b.name));
-
-        let (successful_deps, failed_deps) = try_document_dependencies(
-            &deps_to_document,
-            &cli.output,
-            &target_dir,
-            metadata,
-            cli.include_private,
-        );
-
-        print_documentation_summary(&successful_deps, &failed_deps);
+    #[arg(
+        long,
+        value_name = "TRIPLE",
+        help = "Document for this target instead of the host (e.g. x86_64-pc-windows-msvc)",
+        conflicts_with = "json"
+    )]
+    target: Option<String>,
 
-        generate_master_index(&cli.output, None, &successful_packages, &successful_deps)?;
-    } else {
-        println!("\n📊 Summary:");
-        println!("  ✓ Packages documented: {}", successful_packages.len());
-        if !failed_packages.is_empty() {
-            println!(
-                "  ✗ Failed: {} ({})",
-                failed_packages.len(),
-                failed_packages.join(", ")
-            );
-        }
+    /// Name and output directory of every crate this run documents, filled in after
+    /// parsing
+    #[arg(skip)]
+    documented_crates: Vec<(String, String)>,
+}
 
-        generate_master_index(&cli.output, None, &[], &successful_packages)?;
-    }
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Layout {
+    /// Every crate in its own directory at the output root
+    Flat,
+    /// Requested crates at the output root, dependencies under deps/
+    Nested,
+}
 
-    Ok(())
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Order {
+    /// Alphabetically by name
+    Alpha,
+    /// In declaration order, as they appear in the source
+    Source,
 }
 
-/// Get the library target name from a package (may differ from package name)
-fn get_lib_target_name(package: &serde_json::Value) -> Option<String> {
-    package["targets"]
-        .as_array()?
-        .iter()
-        .find(|target| {
-            target["kind"]
-                .as_array()
-                .map(|kinds| kinds.iter().any(|k| k.as_str() == Some("lib")))
-                .unwrap_or(false)
-        })
-        .and_then(|target| target["name"].as_str())
-        .map(String::from)
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Visibility {
+    /// Only `pub` items
+    Public,
+    /// `pub` and `pub(crate)` items
+    Crate,
+    /// `pub(super)` and `pub(in path)` items too
+    Restricted,
+    /// Every item, as with --include-private
+    Private,
 }
 
-fn document_current_crate(metadata: &serde_json::Value, cli: &Cli) -> Result<Option<String>> {
-    println!("🔨 Generating rustdoc JSON for current crate...");
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum TargetKind {
+    /// The library
+    Lib,
+    /// Every binary
+    Bins,
+    /// Every example
+    Examples,
+}
 
-    // Run cargo rustdoc to generate JSON
-    let mut args = vec![
-        "+nightly",
-        "rustdoc",
-        "--lib",
-        "--",
-        "--output-format=json",
-        "-Z",
-        "unstable-options",
-    ];
+/// One of the current crate's targets, as `cargo rustdoc` selects it
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum CrateTarget {
+    Lib,
+    Bin(String),
+    Example(String),
+}
 
-    if cli.include_private {
-        args.push("--document-private-items");
+impl CrateTarget {
+    fn cargo_args(&self) -> Vec<&str> {
+        match self {
+            CrateTarget::Lib => vec!["--lib"],
+            CrateTarget::Bin(name) => vec!["--bin", name],
+            CrateTarget::Example(name) => vec!["--example", name],
+        }
     }
 
-    let output = Command::new("cargo")
-        .args(&args)
-        .output()
-        .context("Failed to run cargo rustdoc")?;
-
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-
-        if stderr.contains("no library targets found") {
-            println!(
-                "⚠ No library target found in current crate, skipping current crate documentation"
-            );
-            return Ok(None);
+    /// Name of the target's rustdoc JSON file, without `.json`; `lib_name` is the
+    /// library's, which can differ from the package's
+    fn json_stem(&self, lib_name: &str) -> String {
+        match self {
+            CrateTarget::Lib => lib_name.replace('-', "_"),
+            CrateTarget::Bin(name) | CrateTarget::Example(name) => name.replace('-', "_"),
         }
+    }
 
-        bail!("cargo rustdoc failed:\n{}", stderr);
+    /// Directory of the target's output under the crate's, with `--targets`
+    fn dir(&self) -> String {
+        match self {
+            CrateTarget::Lib => "lib".to_string(),
+            CrateTarget::Bin(name) => format!("bin_{}", name.replace('-', "_")),
+            CrateTarget::Example(name) => format!("example_{}", name.replace('-', "_")),
+        }
     }
 
-    let packages = metadata["packages"]
-        .as_array()
-        .context("Missing 'packages' in metadata")?;
+    /// How the crate's index names the target
+    fn label(&self, lib_name: &str) -> String {
+        match self {
+            CrateTarget::Lib => format!("Library `{}`", lib_name),
+            CrateTarget::Bin(name) => format!("Binary `{}`", name),
+            CrateTarget::Example(name) => format!("Example `{}`", name),
+        }
+    }
+}
 
-    // Get the root package name using resolve.root
-    let Some(root_id) = metadata["resolve"]["root"].as_str() else {
-        bail!(
-            "Cannot document current crate from a virtual workspace root.\n\
-             Virtual workspaces have no root package.\n\
-             Use: cargo doc-md --workspace  (to document all workspace members)\n\
-             Or:  cargo doc-md -p <package>  (to document a specific package)"
-        );
-    };
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Emit {
+    /// Markdown files, plus the master index and manifest
+    Markdown,
+    /// The structured doc model as JSON
+    Ir,
+}
 
-    let root_package = packages
-        .iter()
-        .find(|p| p["id"].as_str() == Some(root_id))
-        .context("Root package not found in packages list")?;
+/// `--output` when it isn't passed: `CARGO_DOC_MD_OUTPUT` if set, else `target/doc-md`
+fn default_output_dir() -> PathBuf {
+    std::env::var_os("CARGO_DOC_MD_OUTPUT")
+        .filter(|dir| !dir.is_empty())
+        .map_or_else(|| PathBuf::from("target/doc-md"), PathBuf::from)
+}
 
-    let crate_name = root_package["name"]
-        .as_str()
-        .context("Root package missing name")?
-        .to_string();
+fn main() -> Result<()> {
+    // When invoked as `cargo doc-md`, cargo passes an extra "doc-md" argument
+    // Skip it if present to support both `cargo doc-md` and `cargo-doc-md` invocations
+    let args = std::env::args_os()
+        .enumerate()
+        .filter(|(i, arg)| !(*i == 1 && arg == "doc-md"))
+        .map(|(_, arg)| arg)
+        .collect();
 
-    // Get the library target name (may differ from package name)
-    let lib_target_name =
-        get_lib_target_name(root_package).unwrap_or_else(|| crate_name.replace("-", "_"));
+    let mut cli = parse_cli(args)?;
 
-    // Find the generated JSON file
-    let target_dir = metadata["target_directory"].as_str().unwrap_or("target");
-    let json_path = PathBuf::from(target_dir)
-        .join("doc")
-        .join(format!("{}.json", lib_target_name));
+    // Keep stdout for the output itself: markdown with --stdout, the diff with --diff
+    if cli.stdout || cli.diff.is_some() {
+        PROGRESS_TO_STDERR.store(true, Ordering::Relaxed);
+    }
+    QUIET.store(cli.quiet, Ordering::Relaxed);
 
-    if !json_path.exists() {
-        bail!("Generated JSON file not found at {}", json_path.display());
+    if let Some(old_dir) = &cli.diff {
+        if !old_dir.is_dir() {
+            bail!("Diff directory not found: {}", old_dir.display());
+        }
     }
 
-    println!("✓ JSON generated successfully");
-    println!("🔄 Converting to markdown...");
+    generate(&mut cli)?;
+    relink_converted_crates(&cli)?;
 
-    // Remove existing crate directory to ensure clean documentation
-    let crate_output_dir = cli.output.join(&lib_target_name);
-    if crate_output_dir.exists() {
-        std::fs::remove_dir_all(&crate_output_dir).with_context(|| {
-            format!(
-                "Failed to remove existing crate directory: {}",
-                crate_output_dir.display()
-            )
-        })?;
+    let mut failed = false;
+    if cli.check_links {
+        failed |= report_broken_links(&cli.output, cli.base_url.as_deref())?;
     }
+    if let Some(old_dir) = &cli.diff {
+        failed |= print_diff(old_dir, &cli.output)?;
+    }
+    if cli.fail_on_warning && ANY_FAILED.load(Ordering::Relaxed) {
+        problem!("\n✗ Some crates failed to document (--fail-on-warning)");
+        failed = true;
+    }
+    if failed {
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
 
-    // Convert to markdown
-    let options = ConversionOptions {
-        input_path: &json_path,
-        output_dir: &cli.output,
-        include_private: cli.include_private,
+/// File name of the config file found next to a crate's Cargo.toml
+const CONFIG_FILE_NAME: &str = "cargo-doc-md.toml";
+
+/// Parse the command line, with defaults for the options it leaves out taken from
+/// the config file, if there is one
+fn parse_cli(args: Vec<OsString>) -> Result<Cli> {
+    let matches = Cli::command().get_matches_from(&args);
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    let Some(config_path) = config_path(&cli)? else {
+        return Ok(cli);
     };
 
-    cargo_doc_md::convert_json_file(&options)?;
+    let explicit: HashSet<&str> = matches
+        .ids()
+        .map(|id| id.as_str())
+        .filter(|id| matches.value_source(id) == Some(ValueSource::CommandLine))
+        .collect();
+    let config_args = config_args(&config_path, &explicit)?;
 
-    println!(
-        "✓ Current crate documented: {}/{}/index.md",
-        cli.output.display(),
-        crate_name.replace("-", "_")
-    );
+    let mut args = args.into_iter();
+    let program = args.next();
+    Ok(Cli::parse_from(
+        program.into_iter().chain(config_args).chain(args),
+    ))
+}
 
-    Ok(Some(crate_name))
+/// The config file to read: `--config`, or else `cargo-doc-md.toml` next to the
+/// Cargo.toml cargo would use
+fn config_path(cli: &Cli) -> Result<Option<PathBuf>> {
+    if let Some(path) = &cli.config {
+        if !path.is_file() {
+            bail!("Config file not found: {}", path.display());
+        }
+        return Ok(Some(path.clone()));
+    }
+
+    let crate_root = match &cli.manifest_path {
+        Some(manifest) => manifest.parent().map(Path::to_path_buf),
+        None => std::env::current_dir().ok().and_then(|dir| {
+            dir.ancestors()
+                .find(|dir| dir.join("Cargo.toml").is_file())
+                .map(Path::to_path_buf)
+        }),
+    };
+    Ok(crate_root
+        .map(|root| root.join(CONFIG_FILE_NAME))
+        .filter(|path| path.is_file()))
 }
 
-fn try_document_dependencies(
-    deps_to_document: &[Dependency],
-    output_dir: &Path,
-    target_dir: &Path,
-    metadata: &serde_json::Value,
-    include_private: bool,
-) -> (Vec<String>, Vec<String>) {
-    let mut successful = Vec::new();
-    let mut failed = Vec::new();
+/// Command-line arguments for the options the config file at `path` sets, except
+/// those given on the command line, in `explicit`, or conflicting with one that is
+fn config_args(path: &Path, explicit: &HashSet<&str>) -> Result<Vec<OsString>> {
+    let content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read config file: {}", path.display()))?;
+    let entries = parse_config(&content)
+        .with_context(|| format!("Invalid config file: {}", path.display()))?;
+    let base_dir = path.parent().unwrap_or(Path::new(""));
+
+    let command = Cli::command();
+    let conflicts = |a: &clap::Arg, b: &clap::Arg| {
+        command
+            .get_arg_conflicts_with(a)
+            .iter()
+            .any(|arg| arg.get_id() == b.get_id())
+    };
+    let explicit_args: Vec<&clap::Arg> = command
+        .get_arguments()
+        .filter(|arg| explicit.contains(arg.get_id().as_str()))
+        .collect();
+    let output_from_env =
+        std::env::var_os("CARGO_DOC_MD_OUTPUT").is_some_and(|dir| !dir.is_empty());
+
+    let mut args = Vec::new();
+    for (line, key, value) in entries {
+        let id = key.replace('-', "_");
+        let Some((arg, long)) = command
+            .get_arguments()
+            .filter(|arg| arg.get_id() != "config")
+            .find(|arg| arg.get_id() == id.as_str())
+            .and_then(|arg| Some((arg, arg.get_long()?)))
+        else {
+            bail!("{}:{}: unknown option `{}`", path.display(), line, key);
+        };
 
-    for dep in deps_to_document {
-        match document_single_dependency(dep, output_dir, target_dir, metadata, include_private) {
-            Ok(true) => {
-                // Successfully documented
-                successful.push(dep.name.clone());
-                println!(
-                    "  ✓ {} → {}/{}/index.md",
-                    dep.name,
-                    output_dir.display(),
-                    dep.name.replace("-", "_")
-                );
+        let overridden = explicit_args.iter().any(|other| {
+            other.get_id() == arg.get_id() || conflicts(arg, other) || conflicts(other, arg)
+        });
+        if overridden || (id == "output" && output_from_env) {
+            continue;
+        }
+
+        let values = match value {
+            ConfigValue::Bool(true) => {
+                args.push(OsString::from(format!("--{}", long)));
+                continue;
             }
-            Ok(false) => {
-                // Skipped (e.g., binary-only crate) - not added to successful or failed
+            ConfigValue::Bool(false) if !arg.get_action().takes_values() => continue,
+            ConfigValue::Bool(false) => {
+                bail!(
+                    "{}:{}: `{}` takes a value, not false",
+                    path.display(),
+                    line,
+                    key
+                )
             }
-            Err(e) => {
-                failed.push(dep.name.clone());
-                println!("  ✗ {} - {}", dep.name, e);
+            ConfigValue::Scalar(value) => vec![value],
+            ConfigValue::Array(values) => values,
+        };
+        for value in values {
+            let mut arg_value = OsString::from(format!("--{}=", long));
+            if arg.get_value_hint() == ValueHint::AnyPath {
+                arg_value.push(base_dir.join(value));
+            } else {
+                arg_value.push(value);
             }
+            args.push(arg_value);
         }
     }
 
-    (successful, failed)
+    Ok(args)
 }
 
-fn print_documentation_summary(successful: &[String], failed: &[String]) {
-    println!("\n📊 Summary:");
-    println!("  ✓ Successful: {}", successful.len());
-    if !failed.is_empty() {
-        println!("  ✗ Failed: {} ({})", failed.len(), failed.join(", "));
+/// A value in the config file
+#[derive(Debug, PartialEq)]
+enum ConfigValue {
+    Bool(bool),
+    /// A string or number, as it would be written on the command line
+    Scalar(String),
+    Array(Vec<String>),
+}
+
+/// Parse the TOML a config file needs: top-level `key = value` lines whose values are
+/// strings, integers, booleans, or arrays of strings and integers, and `#` comments.
+/// Returns each key with its value and 1-based line number.
+fn parse_config(content: &str) -> Result<Vec<(usize, String, ConfigValue)>> {
+    let mut entries = Vec::new();
+    let mut lines = content.lines().enumerate();
+
+    while let Some((index, line)) = lines.next() {
+        let line_number = index + 1;
+        let line = strip_toml_comment(line).trim();
+        if line.is_empty() {
+            continue;
+        }
+        if line.starts_with('[') {
+            bail!(
+                "line {}: tables aren't supported; options go at the top level",
+                line_number
+            );
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            bail!("line {}: expected `key = value`", line_number);
+        };
+        let key = key.trim();
+        if key.is_empty()
+            || !key
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            bail!("line {}: invalid key `{}`", line_number, key);
+        }
+
+        // Arrays can span several lines
+        let mut value = value.trim().to_string();
+        while value.starts_with('[') && !value.ends_with(']') {
+            let Some((_, next)) = lines.next() else {
+                bail!("line {}: array for `{}` is never closed", line_number, key);
+            };
+            value.push(' ');
+            value.push_str(strip_toml_comment(next).trim());
+        }
+
+        let value = parse_config_value(&value)
+            .with_context(|| format!("line {}: invalid value for `{}`", line_number, key))?;
+        entries.push((line_number, key.to_string(), value));
     }
+
+    Ok(entries)
 }
 
-fn document_all_dependencies(metadata: &serde_json::Value, cli: &Cli) -> Result<Vec<String>> {
-    let deps_to_document = get_all_dependencies(metadata)?;
+/// `line` without its `# comment`; a `#` in a string is kept
+fn strip_toml_comment(line: &str) -> &str {
+    let mut quote = None;
+    let mut escaped = false;
+    for (i, c) in line.char_indices() {
+        match quote {
+            Some('"') if escaped => escaped = false,
+            Some('"') if c == '\\' => escaped = true,
+            Some(open) if c == open => quote = None,
+            Some(_) => {}
+            None if c == '"' || c == '\'' => quote = Some(c),
+            None if c == '#' => return &line[..i],
+            None => {}
+        }
+    }
+    line
+}
 
-    if deps_to_document.is_empty() {
-        println!("No dependencies found");
-        return Ok(Vec::new());
+fn parse_config_value(text: &str) -> Result<ConfigValue> {
+    match text {
+        "true" => return Ok(ConfigValue::Bool(true)),
+        "false" => return Ok(ConfigValue::Bool(false)),
+        _ => {}
     }
 
-    let target_dir = PathBuf::from(metadata["target_directory"].as_str().unwrap_or("target"));
+    let Some(mut rest) = text.strip_prefix('[') else {
+        let (value, rest) = parse_toml_scalar(text)?;
+        if !rest.trim().is_empty() {
+            bail!("unexpected `{}` after the value", rest.trim());
+        }
+        return Ok(ConfigValue::Scalar(value));
+    };
 
-    println!("📦 Documenting {} dependencies...", deps_to_document.len());
+    let mut values = Vec::new();
+    loop {
+        rest = rest.trim_start();
+        if let Some(after) = rest.strip_prefix(']') {
+            if !after.trim().is_empty() {
+                bail!("unexpected `{}` after the array", after.trim());
+            }
+            return Ok(ConfigValue::Array(values));
+        }
+        let (value, after) = parse_toml_scalar(rest)?;
+        values.push(value);
+        rest = after.trim_start();
+        if let Some(after) = rest.strip_prefix(',') {
+            rest = after;
+        } else if !rest.starts_with(']') {
+            bail!("expected `,` or `]` in the array");
+        }
+    }
+}
 
-    let (successful, failed) = try_document_dependencies(
-        &deps_to_document,
-        &cli.output,
-        &target_dir,
-        metadata,
-        cli.include_private,
-    );
+/// The string or integer at the start of `text`, and the text after it
+fn parse_toml_scalar(text: &str) -> Result<(String, &str)> {
+    // Literal strings have no escapes
+    if let Some(rest) = text.strip_prefix('\'') {
+        let end = rest.find('\'').context("unterminated string")?;
+        return Ok((rest[..end].to_string(), &rest[end + 1..]));
+    }
 
-    print_documentation_summary(&successful, &failed);
+    if let Some(rest) = text.strip_prefix('"') {
+        let mut value = String::new();
+        let mut chars = rest.char_indices();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '"' => return Ok((value, &rest[i + 1..])),
+                '\\' => match chars.next().map(|(_, c)| c) {
+                    Some('"') => value.push('"'),
+                    Some('\\') => value.push('\\'),
+                    Some('n') => value.push('\n'),
+                    Some('t') => value.push('\t'),
+                    Some(c) => bail!("unsupported escape `\\{}`", c),
+                    None => break,
+                },
+                c => value.push(c),
+            }
+        }
+        bail!("unterminated string");
+    }
 
-    Ok(successful)
+    let end = text
+        .find(|c: char| !(c.is_ascii_digit() || matches!(c, '+' | '-' | '_')))
+        .unwrap_or(text.len());
+    let number: i64 = text[..end].replace('_', "").parse().ok().with_context(|| {
+        format!(
+            "expected a string, integer, boolean, or array, found `{}`",
+            text
+        )
+    })?;
+    Ok((number.to_string(), &text[end..]))
 }
 
-fn document_workspace(metadata: &serde_json::Value, cli: &Cli) -> Result<()> {
-    let workspace_members = get_workspace_members(metadata)?;
+/// Document the selected crates and write the indexes
+fn generate(cli: &mut Cli) -> Result<()> {
+    if cli.index_only {
+        return regenerate_master_index(cli);
+    }
 
-    println!(
-        "📚 Documenting {} workspace member(s){}...\n",
-        workspace_members.len(),
-        if cli.no_deps {
-            " (without dependencies)"
-        } else {
-            " and their dependencies"
+    // Verify nightly toolchain is available (unless converting existing JSON)
+    if cli.json.is_none() && cli.json_dir.is_none() {
+        check_nightly_toolchain()?;
+        if let Some(target) = &cli.target {
+            check_target_installed(target)?;
         }
-    );
+    }
 
-    let target_dir = PathBuf::from(metadata["target_directory"].as_str().unwrap_or("target"));
+    if let Some(manifest_path) = &cli.manifest_path {
+        validate_manifest_path(manifest_path)?;
+    }
 
-    let workspace_member_ids: Vec<String> = metadata["workspace_members"]
-        .as_array()
-        .map(|members| {
-            members
-                .iter()
-                .filter_map(|v| v.as_str().map(String::from))
-                .collect()
-        })
-        .unwrap_or_default();
+    // Validate output directory
+    validate_output_directory(&cli.output)?;
 
-    let workspace_member_names: std::collections::HashSet<String> =
-        workspace_members.iter().map(|m| m.name.clone()).collect();
+    // Explicit JSON file - just convert that file
+    if let Some(json_path) = cli.json.as_ref() {
+        if !json_path.exists() {
+            bail!("JSON file not found: {}", json_path.display());
+        }
+        if !json_path.is_file() {
+            bail!("Path is not a file: {}", json_path.display());
+        }
 
-    let mut successful_members = Vec::new();
-    let mut failed_members = Vec::new();
-    let mut all_deps: HashMap<String, String> = HashMap::new();
+        let documented = load_json_crate(json_path)?;
+        convert_crate(json_path, &documented.dir, cli)?;
 
-    for member in &workspace_members {
-        println!(
-            "🔨 Generating docs for workspace member '{}'...",
-            member.name
-        );
+        // Generate master index for consistency with other modes
+        generate_indexes(cli, None, &[], &[documented])?;
 
-        match document_single_dependency(
-            member,
-            &cli.output,
-            &target_dir,
-            metadata,
-            cli.include_private,
-        ) {
-            Ok(true) => {
-                // Successfully documented
-                successful_members.push(member.name.clone());
-                println!(
-                    "  ✓ {} → {}/{}/index.md",
-                    member.name,
-                    cli.output.display(),
-                    member.name.replace("-", "_")
-                );
+        return Ok(());
+    }
 
-                if !cli.no_deps {
-                    match get_package_id(metadata, &member.name, &member.version) {
-                        Ok(member_id) => {
-                            match get_all_dependencies_recursive(
-                                metadata,
-                                &member_id,
-                                &workspace_member_ids,
-                            ) {
-                                Ok(member_deps) => {
-                                    for (name, version) in member_deps {
-                                        if !workspace_member_names.contains(&name) {
-                                            all_deps.insert(name, version);
-                                        }
-                                    }
-                                }
-                                Err(e) => {
-                                    println!(
-                                        "  ⚠ Warning: Could not get dependencies for '{}': {}",
-                                        member.name, e
+    if let Some(json_dir) = cli.json_dir.clone() {
+        return convert_json_dir(&json_dir, cli);
+    }
+
+    // Get cargo metadata once for all operations
+    let metadata = get_cargo_metadata(cli.target.as_deref(), cli.manifest_path.as_deref())?;
+    cli.documented_crates = planned_crate_dirs(&metadata, cli);
+
+    let packages = metadata["packages"].as_array().into_iter().flatten();
+    let package_names: HashSet<String> = packages
+        .filter_map(|package| package["name"].as_str())
+        .map(|name| name.replace('-', "_"))
+        .collect();
+    if let Some(unknown) = cli
+        .only
+        .iter()
+        .find(|only| !package_names.contains(&only.replace('-', "_")))
+    {
+        bail!(
+            "--only {}: no such package in the dependency graph",
+            unknown
+        );
+    }
+
+    // Workspace mode
+    if cli.workspace {
+        document_workspace(&metadata, cli)?;
+        return Ok(());
+    }
+
+    // Specific packages requested
+    if !cli.package.is_empty() {
+        document_specific_packages(&metadata, cli)?;
+        return Ok(());
+    }
+
+    // Default: document current crate + all transitive dependencies (matches cargo doc)
+    if cli.no_deps {
+        progress!("📚 Documenting current crate only...\n");
+        let current_crate = document_current_crate(&metadata, cli)?;
+        generate_indexes(cli, current_crate.as_ref(), &[], &[])?;
+    } else {
+        progress!("📚 Documenting current crate and all transitive dependencies...\n");
+        let current_crate = document_current_crate(&metadata, cli)?;
+        progress!();
+        let documented_deps = document_all_dependencies(&metadata, cli, current_crate.as_ref())?;
+        generate_indexes(cli, current_crate.as_ref(), &[], &documented_deps)?;
+    }
+
+    Ok(())
+}
+
+#[derive(Debug)]
+struct Dependency {
+    name: String,
+    version: String,
+    /// Another version of this crate is documented in the same run, so the
+    /// output directory carries a version suffix (e.g. `rand-0.8`)
+    versioned_dir: bool,
+    /// Pulled in as a dependency rather than requested, so `--layout nested`
+    /// puts it under `deps/`
+    dependency: bool,
+}
+
+/// A crate whose markdown was written (or kept) in this run
+#[derive(Debug, Clone)]
+struct DocumentedCrate {
+    name: String,
+    version: String,
+    /// Output directory name, relative to the output root
+    dir: String,
+}
+
+/// Where a crate's output directory `dir` goes under the chosen `--layout`
+fn layout_dir(cli: &Cli, dir: String, dependency: bool) -> String {
+    match cli.layout {
+        Layout::Nested if dependency => format!("deps/{}", dir),
+        _ => dir,
+    }
+}
+
+fn get_cargo_metadata(
+    target: Option<&str>,
+    manifest_path: Option<&Path>,
+) -> Result<serde_json::Value> {
+    // Filter platform-specific dependencies for the requested target, or the host platform
+    let host_triple = match target {
+        Some(target) => target.to_string(),
+        None => std::env::var("CARGO_BUILD_TARGET").or_else(|_| {
+            let output = Command::new("rustc")
+                .args(["-vV"])
+                .output()
+                .context("Failed to run rustc")?;
+
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            stdout
+                .lines()
+                .find(|line| line.starts_with("host:"))
+                .and_then(|line| line.split_whitespace().nth(1))
+                .map(String::from)
+                .context("Failed to parse host triple from rustc")
+        })?,
+    };
+
+    let mut command = Command::new("cargo");
+    command.args([
+        "metadata",
+        "--format-version=1",
+        "--filter-platform",
+        &host_triple,
+    ]);
+    if let Some(manifest_path) = manifest_path {
+        command.arg("--manifest-path").arg(manifest_path);
+    }
+    let output = command.output().context("Failed to run 'cargo metadata'")?;
+
+    if !output.status.success() {
+        bail!(
+            "cargo metadata failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+
+    serde_json::from_slice(&output.stdout).context("Failed to parse cargo metadata")
+}
+
+/// Check that `--manifest-path` names an existing `Cargo.toml`, as cargo requires
+fn validate_manifest_path(manifest_path: &Path) -> Result<()> {
+    if manifest_path.file_name() != Some("Cargo.toml".as_ref()) {
+        bail!(
+            "--manifest-path must point at a Cargo.toml file: {}",
+            manifest_path.display()
+        );
+    }
+    if !manifest_path.is_file() {
+        bail!("Manifest not found: {}", manifest_path.display());
+    }
+    // Passed on to cargo alongside the other string arguments
+    if manifest_path.to_str().is_none() {
+        bail!(
+            "Manifest path is not valid UTF-8: {}",
+            manifest_path.display()
+        );
+    }
+
+    Ok(())
+}
+
+fn validate_output_directory(output_dir: &Path) -> Result<()> {
+    if output_dir.exists() && output_dir.is_file() {
+        bail!(
+            "Output path exists but is a file, not a directory: {}\n\
+             Please specify a directory path or remove the file.",
+            output_dir.display()
+        );
+    }
+
+    Ok(())
+}
+
+/// Fail early, with the rustup command to fix it, if the nightly standard library
+/// for `target` is missing
+fn check_target_installed(target: &str) -> Result<()> {
+    let output = Command::new("rustc")
+        .args(["+nightly", "--print", "sysroot"])
+        .output()
+        .context("Failed to run rustc +nightly")?;
+
+    let sysroot = String::from_utf8_lossy(&output.stdout);
+    let target_lib = Path::new(sysroot.trim()).join("lib/rustlib").join(target);
+    if output.status.success() && !target_lib.is_dir() {
+        bail!(missing_target_message(target));
+    }
+
+    Ok(())
+}
+
+/// Whether cargo failed while another cargo process held the build lock, rather than
+/// because of a compile error
+fn is_lock_contention(stderr: &str) -> bool {
+    let waited_for_lock = stderr.contains("Blocking waiting for file lock")
+        || stderr.contains("failed to lock file")
+        || stderr.contains("failed to acquire package cache lock");
+    waited_for_lock && !stderr.contains("error[") && !stderr.contains("could not compile")
+}
+
+/// Whether rustc failed because the standard library for the target is missing
+fn is_missing_target(stderr: &str) -> bool {
+    stderr.contains("target may not be installed")
+}
+
+fn missing_target_message(target: &str) -> String {
+    format!(
+        "Target '{}' is not installed for the nightly toolchain.\n\
+         Install it with: rustup +nightly target add {}",
+        target, target
+    )
+}
+
+/// Arguments for `cargo rustdoc` producing JSON for `crate_target`, honoring `--target`
+/// and `--include-private`
+fn rustdoc_args<'a>(
+    package_spec: Option<&'a str>,
+    crate_target: &'a CrateTarget,
+    cli: &'a Cli,
+) -> Vec<&'a str> {
+    let mut args = vec!["+nightly", "rustdoc"];
+    if let Some(manifest_path) = cli.manifest_path.as_deref().and_then(Path::to_str) {
+        args.extend(["--manifest-path", manifest_path]);
+    }
+    if let Some(spec) = package_spec {
+        args.extend(["-p", spec]);
+    }
+    args.extend(crate_target.cargo_args());
+    if let Some(target) = &cli.target {
+        args.extend(["--target", target]);
+    }
+    args.extend(["--", "--output-format=json", "-Z", "unstable-options"]);
+
+    if documents_private_items(cli) {
+        args.push("--document-private-items");
+    }
+
+    args
+}
+
+/// `cargo rustdoc` arguments for a package that is only reachable through
+/// dev-dependencies
+///
+/// cargo only resolves such packages' features when a target that needs
+/// dev-dependencies is selected, and panics otherwise, so `--examples` is selected
+/// as well. That rules out extra rustdoc arguments, which cargo only passes to a
+/// single target, so JSON is requested with cargo's own `--output-format` and
+/// `--document-private-items` has to go through `RUSTDOCFLAGS`. Packages whose
+/// examples use their own dev-dependencies, which aren't resolved, still fail.
+fn dev_only_rustdoc_args<'a>(package_spec: &'a str, cli: &'a Cli) -> Vec<&'a str> {
+    let mut args = vec!["+nightly", "rustdoc"];
+    if let Some(manifest_path) = cli.manifest_path.as_deref().and_then(Path::to_str) {
+        args.extend(["--manifest-path", manifest_path]);
+    }
+    args.extend(["-p", package_spec, "--lib", "--examples"]);
+    if let Some(target) = &cli.target {
+        args.extend(["--target", target]);
+    }
+    args.extend(["--output-format", "json", "-Z", "unstable-options"]);
+    args
+}
+
+/// Whether cargo panicked resolving a package only reachable through dev-dependencies
+fn is_dev_only_resolve_failure(stderr: &str) -> bool {
+    stderr.contains("did not find features for")
+}
+
+/// Where `cargo rustdoc` writes JSON: `<target>/doc`, or `<target>/<triple>/doc` with `--target`
+fn rustdoc_json_dir(target_dir: &Path, cli: &Cli) -> PathBuf {
+    match &cli.target {
+        Some(triple) => target_dir.join(triple).join("doc"),
+        None => target_dir.join("doc"),
+    }
+}
+
+fn check_nightly_toolchain() -> Result<()> {
+    let output = Command::new("cargo")
+        .args(["+nightly", "--version"])
+        .output()
+        .context("Failed to run cargo +nightly")?;
+
+    if !output.status.success() {
+        bail!(
+            "Nightly toolchain not installed or not available.\n\
+             This tool requires Rust nightly for unstable rustdoc features.\n\
+             Install with: rustup install nightly"
+        );
+    }
+
+    Ok(())
+}
+
+fn document_specific_packages(metadata: &serde_json::Value, cli: &Cli) -> Result<()> {
+    progress!(
+        "📦 Documenting {} specific package(s) and their dependencies...",
+        cli.package.len()
+    );
+
+    let packages = metadata["packages"]
+        .as_array()
+        .context("Missing 'packages' in metadata")?;
+
+    let target_dir = PathBuf::from(metadata["target_directory"].as_str().unwrap_or("target"));
+
+    // For -p mode, don't filter out workspace member dependencies
+    // User explicitly requested specific packages, so document them and ALL their deps
+    let workspace_member_ids: Vec<String> = Vec::new();
+
+    let mut successful_packages = Vec::new();
+    let mut failed_packages = Vec::new();
+    let mut all_deps = HashSet::new();
+
+    // Document each specified package
+    for package_name in &cli.package {
+        progress!("\n🔨 Generating docs for '{}'...", package_name);
+
+        // Find package in metadata
+        let package = packages
+            .iter()
+            .find(|p| p["name"].as_str() == Some(package_name));
+
+        let dep = if let Some(pkg) = package {
+            let name = pkg["name"]
+                .as_str()
+                .context("Package missing 'name' field in metadata")?
+                .to_string();
+            let version = pkg["version"].as_str().unwrap_or("").to_string();
+            Dependency {
+                name,
+                version,
+                versioned_dir: false,
+                dependency: false,
+            }
+        } else {
+            Dependency {
+                name: package_name.clone(),
+                version: String::new(),
+                versioned_dir: false,
+                dependency: false,
+            }
+        };
+
+        match document_single_dependency(&dep, &target_dir, metadata, cli) {
+            Ok(outcome @ (DocOutcome::Documented | DocOutcome::Unchanged)) => {
+                let documented = dep.documented(metadata, cli);
+                report_documented(&documented, outcome, cli);
+                successful_packages.push(documented);
+
+                // Get dependencies for this package if not --no-deps
+                if !cli.no_deps {
+                    if let Some(pkg) = package {
+                        if let Some(pkg_id) = pkg["id"].as_str() {
+                            match get_all_dependencies_recursive(
+                                metadata,
+                                pkg_id,
+                                &workspace_member_ids,
+                                cli.include_dev_deps,
+                            ) {
+                                Ok(deps) => {
+                                    for (name, version) in deps {
+                                        if !successful_packages.iter().any(|p| p.name == name) {
+                                            all_deps.insert((name, version));
+                                        }
+                                    }
+                                }
+                                Err(e) => {
+                                    problem!(
+                                        "  ⚠ Warning: Could not get dependencies for '{}': {}",
+                                        package_name,
+                                        e
                                     );
                                 }
                             }
                         }
-                        Err(e) => {
-                            println!(
-                                "  ⚠ Warning: Could not find package ID for '{}': {}",
-                                member.name, e
-                            );
-                        }
                     }
                 }
             }
-            Ok(false) => {
+            Ok(DocOutcome::Skipped) => {
                 // Skipped (e.g., binary-only crate)
-                println!("  ⊘ {} skipped", member.name);
+                progress!("  ⊘ {} skipped", package_name);
             }
             Err(e) => {
-                failed_members.push(member.name.clone());
-                println!("  ✗ Failed to document '{}': {}", member.name, e);
+                failed_packages.push(package_name.clone());
+                problem!("  ✗ Failed to document '{}': {}", package_name, e);
             }
         }
     }
 
+    record_failures(&failed_packages);
+
+    // Document dependencies if not --no-deps
     if !cli.no_deps && !all_deps.is_empty() {
-        println!(
-            "\n📦 Documenting {} unique external dependencies...",
-            all_deps.len()
-        );
-        let mut deps_to_document: Vec<Dependency> = all_deps
-            .into_iter()
-            .map(|(name, version)| Dependency { name, version })
-            .collect();
-        deps_to_document.sort_by(|a, b| a.name.cmp(&b.name));
+        progress!("\n📦 Documenting {} unique dependencies...", all_deps.len());
+        let deps_to_document = dependencies_from_set(all_deps);
 
         let (successful_deps, failed_deps) = try_document_dependencies(
             &deps_to_document,
-            &cli.output,
+            &successful_packages,
             &target_dir,
             metadata,
-            cli.include_private,
+            cli,
         );
 
         print_documentation_summary(&successful_deps, &failed_deps);
 
-        generate_master_index(&cli.output, None, &successful_members, &successful_deps)?;
+        generate_indexes(cli, None, &successful_packages, &successful_deps)?;
     } else {
-        println!("\n📊 Summary:");
-        println!(
-            "  ✓ Workspace members documented: {}",
-            successful_members.len()
-        );
-        if !failed_members.is_empty() {
-            println!(
+        progress!("\n📊 Summary:");
+        progress!("  ✓ Packages documented: {}", successful_packages.len());
+        if !failed_packages.is_empty() {
+            progress!(
                 "  ✗ Failed: {} ({})",
-                failed_members.len(),
-                failed_members.join(", ")
+                failed_packages.len(),
+                failed_packages.join(", ")
             );
         }
 
-        generate_master_index(&cli.output, None, &successful_members, &[])?;
+        generate_indexes(cli, None, &[], &successful_packages)?;
     }
 
     Ok(())
 }
 
-fn get_package_id(metadata: &serde_json::Value, name: &str, version: &str) -> Result<String> {
-    let packages = metadata["packages"]
-        .as_array()
-        .context("Missing 'packages' in metadata")?;
+/// Names and output directories of the crates a run will document, used to resolve
+/// cross-crate links before every crate has been converted
+fn planned_crate_dirs(metadata: &serde_json::Value, cli: &Cli) -> Vec<(String, String)> {
+    let Some(packages) = metadata["packages"].as_array() else {
+        return Vec::new();
+    };
 
-    for pkg in packages {
-        if pkg["name"].as_str() == Some(name) && pkg["version"].as_str() == Some(version) {
-            return pkg["id"]
+    let workspace_members: Vec<&str> = metadata["workspace_members"]
+        .as_array()
+        .map(|members| members.iter().filter_map(|m| m.as_str()).collect())
+        .unwrap_or_default();
+    let root = metadata["resolve"]["root"].as_str();
+
+    let is_requested = |package: &serde_json::Value| {
+        let id = package["id"].as_str();
+        if cli.workspace {
+            id.is_some_and(|id| workspace_members.contains(&id))
+                && package["name"]
+                    .as_str()
+                    .is_some_and(|name| !is_excluded_member(name, cli))
+        } else if !cli.package.is_empty() {
+            package["name"]
                 .as_str()
-                .map(String::from)
-                .context("Package ID not found");
+                .is_some_and(|name| cli.package.iter().any(|p| p == name))
+        } else {
+            id.is_some() && id == root
         }
-    }
+    };
 
-    bail!("Package {} {} not found in metadata", name, version)
+    let mut dirs: Vec<(String, String)> = packages
+        .iter()
+        // With dependencies, every linkable crate is documented as well
+        .filter(|package| !cli.no_deps || is_requested(package))
+        .filter_map(|package| {
+            let is_root = package["id"].as_str().is_some_and(|id| Some(id) == root);
+            if let Some(requested) = &cli.bin {
+                if is_root {
+                    return select_bin_target(package, requested.as_deref())
+                        .ok()
+                        .map(|bin| bin.replace("-", "_"))
+                        .map(|name| (name.clone(), name));
+                }
+            }
+            get_lib_target_name(package)
+                .or_else(|| package["name"].as_str().map(String::from))
+                .map(|name| name.replace("-", "_"))
+                .map(|name| {
+                    let dir = if documents_target_dirs(cli) && is_root {
+                        format!("{}/{}", name, CrateTarget::Lib.dir())
+                    } else {
+                        layout_dir(cli, name.clone(), !is_requested(package))
+                    };
+                    (name, dir)
+                })
+        })
+        .collect();
+
+    // A crate documented in several versions gets versioned directories, and a
+    // link can't tell which version it means, so those fall back to docs.rs
+    dirs.sort();
+    dirs.chunk_by(|(a, _), (b, _)| a == b)
+        .filter(|same| same.len() == 1)
+        .map(|same| same[0].clone())
+        .collect()
 }
 
-fn build_normal_dependency_graph(
+/// Get the library target name from a package (may differ from package name)
+fn get_lib_target_name(package: &serde_json::Value) -> Option<String> {
+    package["targets"]
+        .as_array()?
+        .iter()
+        .find(|target| {
+            target["kind"]
+                .as_array()
+                .map(|kinds| kinds.iter().any(|k| k.as_str() == Some("lib")))
+                .unwrap_or(false)
+        })
+        .and_then(|target| target["name"].as_str())
+        .map(String::from)
+}
+
+fn document_current_crate(
     metadata: &serde_json::Value,
-) -> Result<HashMap<String, Vec<String>>> {
-    use std::collections::HashSet;
+    cli: &Cli,
+) -> Result<Option<DocumentedCrate>> {
+    let root_package = root_package(metadata)?;
 
-    let packages = metadata["packages"]
-        .as_array()
-        .context("Missing 'packages' in metadata")?;
+    let crate_name = root_package["name"]
+        .as_str()
+        .context("Root package missing name")?
+        .to_string();
 
-    let nodes = metadata["resolve"]["nodes"]
-        .as_array()
-        .context("Missing 'nodes' in resolve")?;
+    if !is_selected(cli, &crate_name) {
+        return Ok(None);
+    }
 
-    let mut normal_dep_graph: HashMap<String, Vec<String>> = HashMap::new();
+    if documents_target_dirs(cli) {
+        return document_current_crate_targets(metadata, root_package, crate_name, cli).map(Some);
+    }
 
-    // For each package, build a map of package_id -> Set of normal dependency names
-    let mut pkg_normal_deps: HashMap<String, HashSet<String>> = HashMap::new();
-    for pkg in packages {
-        let Some(pkg_id) = pkg["id"].as_str() else {
-            continue; // Skip packages without IDs
+    let crate_target = match &cli.bin {
+        Some(requested) => CrateTarget::Bin(select_bin_target(root_package, requested.as_deref())?),
+        None => CrateTarget::Lib,
+    };
+
+    progress!("🔨 Generating rustdoc JSON for current crate...");
+
+    let Some(json_path) = generate_current_crate_json(metadata, root_package, &crate_target, cli)?
+    else {
+        progress!(
+            "⚠ No library target found in current crate, skipping current crate documentation\n\
+             Use --bin to document a binary target instead"
+        );
+        return Ok(None);
+    };
+    let target_name = crate_target.json_stem(&lib_target_name(root_package, &crate_name));
+
+    progress!("✓ JSON generated successfully");
+    progress!("🔄 Converting to markdown...");
+
+    convert_crate(&json_path, &target_name, cli)?;
+
+    progress!(
+        "✓ Current crate documented: {}/{}",
+        cli.output.display(),
+        crate_index_path(cli, &target_name)
+    );
+
+    Ok(Some(DocumentedCrate {
+        name: crate_name,
+        version: root_package["version"].as_str().unwrap_or("").to_string(),
+        dir: target_name,
+    }))
+}
+
+/// The library's target name, falling back to the package name for crates without one
+fn lib_target_name(package: &serde_json::Value, crate_name: &str) -> String {
+    get_lib_target_name(package).unwrap_or_else(|| crate_name.replace("-", "_"))
+}
+
+/// Run `cargo rustdoc` on one of the current crate's targets, returning its rustdoc
+/// JSON, or `None` if the library was asked for and the crate has none
+fn generate_current_crate_json(
+    metadata: &serde_json::Value,
+    root_package: &serde_json::Value,
+    crate_target: &CrateTarget,
+    cli: &Cli,
+) -> Result<Option<PathBuf>> {
+    let output = Command::new("cargo")
+        .args(rustdoc_args(None, crate_target, cli))
+        .output()
+        .context("Failed to run cargo rustdoc")?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+
+        if stderr.contains("no library targets found") {
+            return Ok(None);
+        }
+
+        if let Some(target) = cli.target.as_deref().filter(|_| is_missing_target(&stderr)) {
+            bail!(missing_target_message(target));
+        }
+
+        bail!("cargo rustdoc failed:\n{}", stderr);
+    }
+
+    // The JSON file is named after the documented target (may differ from package name)
+    let crate_name = root_package["name"].as_str().unwrap_or_default();
+    let json_stem = crate_target.json_stem(&lib_target_name(root_package, crate_name));
+    let target_dir = PathBuf::from(metadata["target_directory"].as_str().unwrap_or("target"));
+    let json_path = rustdoc_json_dir(&target_dir, cli).join(format!("{}.json", json_stem));
+
+    if !json_path.exists() {
+        bail!("Generated JSON file not found at {}", json_path.display());
+    }
+    Ok(Some(json_path))
+}
+
+/// Whether `--targets` selects more than the library, which gives every target a
+/// directory of its own under the crate's
+fn documents_target_dirs(cli: &Cli) -> bool {
+    cli.targets.iter().any(|kind| *kind != TargetKind::Lib)
+}
+
+/// The current crate's targets that `--targets` selects: the library, then binaries
+/// and examples by name
+fn selected_targets(package: &serde_json::Value, cli: &Cli) -> Vec<CrateTarget> {
+    let mut targets: Vec<CrateTarget> = package["targets"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|target| {
+            let name = target["name"].as_str()?.to_string();
+            let kinds: Vec<&str> = target["kind"]
+                .as_array()?
+                .iter()
+                .filter_map(|kind| kind.as_str())
+                .collect();
+            let is_lib = kinds
+                .iter()
+                .any(|kind| ["lib", "rlib", "dylib", "proc-macro"].contains(kind));
+            if is_lib && cli.targets.contains(&TargetKind::Lib) {
+                Some(CrateTarget::Lib)
+            } else if kinds.contains(&"bin") && cli.targets.contains(&TargetKind::Bins) {
+                Some(CrateTarget::Bin(name))
+            } else if kinds.contains(&"example") && cli.targets.contains(&TargetKind::Examples) {
+                Some(CrateTarget::Example(name))
+            } else {
+                None
+            }
+        })
+        .collect();
+    targets.sort();
+    targets
+}
+
+/// Document each target `--targets` selects into its own directory under the crate's,
+/// then write the crate's index.md linking them
+///
+/// The crate's manifest merges the targets', so the master index and the files built
+/// from it cover every target.
+fn document_current_crate_targets(
+    metadata: &serde_json::Value,
+    root_package: &serde_json::Value,
+    crate_name: String,
+    cli: &Cli,
+) -> Result<DocumentedCrate> {
+    let lib_name = lib_target_name(root_package, &crate_name);
+    let targets = selected_targets(root_package, cli);
+    if targets.is_empty() {
+        bail!(
+            "{} has none of the targets selected by --targets",
+            crate_name
+        );
+    }
+
+    progress!(
+        "🔨 Documenting {} targets of the current crate...",
+        targets.len()
+    );
+
+    let mut documented = Vec::new();
+    for crate_target in targets {
+        let label = crate_target.label(&lib_name);
+        let json_path = generate_current_crate_json(metadata, root_package, &crate_target, cli)?
+            .with_context(|| format!("No JSON generated for {}", label))?;
+        let target_dir = format!("{}/{}", lib_name, crate_target.dir());
+        convert_crate(&json_path, &target_dir, cli)?;
+        progress!(
+            "  ✓ {} → {}/{}",
+            label,
+            cli.output.display(),
+            crate_index_path(cli, &target_dir)
+        );
+        documented.push((label, target_dir));
+    }
+
+    if cli.emit == Emit::Markdown {
+        write_targets_index(cli, &crate_name, &lib_name, &documented)?;
+    }
+
+    progress!(
+        "✓ Current crate documented: {}/{}",
+        cli.output.display(),
+        crate_index_path(cli, &lib_name)
+    );
+
+    Ok(DocumentedCrate {
+        name: crate_name,
+        version: root_package["version"].as_str().unwrap_or("").to_string(),
+        dir: lib_name,
+    })
+}
+
+/// Write a crate's index.md listing its targets' documentation, and the crate
+/// manifest merging the targets' own, then remove anything else left in the crate's
+/// directory by earlier runs
+fn write_targets_index(
+    cli: &Cli,
+    crate_name: &str,
+    crate_dir: &str,
+    targets: &[(String, String)],
+) -> Result<()> {
+    let read_manifest = |target_dir: &str| -> serde_json::Value {
+        std::fs::read_to_string(crate_manifest_path(cli, target_dir))
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_else(|| json!({}))
+    };
+
+    let mut content = String::new();
+    if cli.frontmatter {
+        let slug = format!("{}/index", crate_dir);
+        content.push_str(&frontmatter(crate_name, &slug, 1));
+    }
+    content.push_str(&format!("# {}\n\n", crate_name));
+    content.push_str("Each target of the crate is documented on its own:\n\n");
+
+    let index = crate_index_path(cli, crate_dir);
+    let mut entry = json!({
+        "version": serde_json::Value::Null,
+        "summary": serde_json::Value::Null,
+        "modules": [],
+        "outline": [],
+        "files": [index],
+    });
+    for (label, target_dir) in targets {
+        let manifest = read_manifest(target_dir);
+        let link = format!(
+            "{}/index.md",
+            target_dir.rsplit('/').next().unwrap_or(target_dir)
+        );
+        content.push_str(&format!("- [{}]({})", label, link));
+        if let Some(summary) = manifest["summary"].as_str() {
+            content.push_str(&format!(" - {}", summary));
+        }
+        content.push('\n');
+
+        for key in ["version", "summary"] {
+            if entry[key].is_null() {
+                entry[key] = manifest[key].clone();
+            }
+        }
+        for key in ["modules", "outline", "files"] {
+            if let (Some(merged), Some(items)) =
+                (entry[key].as_array_mut(), manifest[key].as_array())
+            {
+                merged.extend(items.iter().cloned());
+            }
+        }
+    }
+
+    if let Some(base_url) = &cli.base_url {
+        content = base_url_links(&content, crate_dir, base_url);
+    }
+    let index_path = cli.output.join(&index);
+    std::fs::write(&index_path, content)
+        .with_context(|| format!("Failed to write crate index: {}", index_path.display()))?;
+
+    let manifest_path = crate_manifest_path(cli, crate_dir);
+    std::fs::write(&manifest_path, serde_json::to_string_pretty(&entry)?).with_context(|| {
+        format!(
+            "Failed to write crate manifest: {}",
+            manifest_path.display()
+        )
+    })?;
+
+    // A library-only run, or one with other targets, leaves files of its own here
+    let crate_output = cli.output.join(crate_dir);
+    let entries = std::fs::read_dir(&crate_output)
+        .with_context(|| format!("Failed to read directory: {}", crate_output.display()))?;
+    for dir_entry in entries {
+        let path = dir_entry?.path();
+        let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+            continue;
         };
-        let mut normal_deps = HashSet::new();
+        let is_current = name == "index.md"
+            || name.starts_with('.')
+            || targets
+                .iter()
+                .any(|(_, target_dir)| target_dir.rsplit('/').next() == Some(name));
+        if is_current {
+            continue;
+        }
+        let removed = if path.is_dir() {
+            std::fs::remove_dir_all(&path)
+        } else {
+            std::fs::remove_file(&path)
+        };
+        removed.with_context(|| format!("Failed to remove stale output: {}", path.display()))?;
+    }
 
-        if let Some(deps) = pkg["dependencies"].as_array() {
-            for dep in deps {
-                // Only include normal dependencies (kind is null or not present)
-                // Platform filtering is handled by cargo metadata --filter-platform
-                if dep["kind"].is_null() {
-                    if let Some(dep_name) = dep["name"].as_str() {
-                        normal_deps.insert(dep_name.to_string());
+    Ok(())
+}
+
+/// The package `cargo doc-md` was run in, found through `resolve.root`
+fn root_package(metadata: &serde_json::Value) -> Result<&serde_json::Value> {
+    let packages = metadata["packages"]
+        .as_array()
+        .context("Missing 'packages' in metadata")?;
+
+    let Some(root_id) = metadata["resolve"]["root"].as_str() else {
+        bail!(
+            "Cannot document current crate from a virtual workspace root.\n\
+             Virtual workspaces have no root package.\n\
+             Use: cargo doc-md --workspace  (to document all workspace members)\n\
+             Or:  cargo doc-md -p <package>  (to document a specific package)"
+        );
+    };
+
+    packages
+        .iter()
+        .find(|p| p["id"].as_str() == Some(root_id))
+        .context("Root package not found in packages list")
+}
+
+/// The binary target `--bin` refers to: the one named `requested`, or the only one
+fn select_bin_target(package: &serde_json::Value, requested: Option<&str>) -> Result<String> {
+    let bins: Vec<&str> = package["targets"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|target| {
+            target["kind"]
+                .as_array()
+                .is_some_and(|kinds| kinds.iter().any(|k| k.as_str() == Some("bin")))
+        })
+        .filter_map(|target| target["name"].as_str())
+        .collect();
+    let package_name = package["name"].as_str().unwrap_or("current crate");
+
+    match (requested, bins.as_slice()) {
+        (_, []) => bail!("No binary targets found in {}", package_name),
+        (Some(name), _) if bins.contains(&name) => Ok(name.to_string()),
+        (Some(name), _) => bail!(
+            "No binary target named '{}' in {}\nAvailable binaries: {}",
+            name,
+            package_name,
+            bins.join(", ")
+        ),
+        (None, [only]) => Ok(only.to_string()),
+        (None, _) => bail!(
+            "{} has several binaries, pick one with --bin <NAME>: {}",
+            package_name,
+            bins.join(", ")
+        ),
+    }
+}
+
+/// Document `deps_to_document` in parallel, returning the documented crates and the
+/// labels of those that failed
+///
+/// Dependencies already documented in this run, as `documented_before`, are skipped:
+/// a crate with the same name and version, or one whose output would land in the
+/// same directory, like a path dependency on the root crate.
+fn try_document_dependencies(
+    deps_to_document: &[Dependency],
+    documented_before: &[DocumentedCrate],
+    target_dir: &Path,
+    metadata: &serde_json::Value,
+    cli: &Cli,
+) -> (Vec<DocumentedCrate>, Vec<String>) {
+    let (already_documented, deps_to_document): (Vec<&Dependency>, Vec<&Dependency>) =
+        deps_to_document.iter().partition(|dep| {
+            let dir = dep.dir_name(metadata, cli);
+            documented_before.iter().any(|documented| {
+                documented.name == dep.name
+                    && (documented.version == dep.version || documented.dir == dir)
+            })
+        });
+    for dep in already_documented {
+        progress!("  ⏭ {} (already documented)", dep.label());
+    }
+
+    // Markdown is printed as each crate finishes, so keep crate order stable in stdout mode
+    let jobs = if cli.stdout {
+        1
+    } else {
+        cli.jobs
+            .or_else(|| std::thread::available_parallelism().ok())
+            .map_or(1, NonZeroUsize::get)
+    };
+
+    // Versions of one crate share a rustdoc JSON path, so each group runs on a single thread
+    let groups: Vec<&[&Dependency]> = deps_to_document.chunk_by(|a, b| a.name == b.name).collect();
+
+    let next_group = AtomicUsize::new(0);
+    let successful = Mutex::new(Vec::new());
+    let failed = Mutex::new(Vec::new());
+
+    std::thread::scope(|scope| {
+        for _ in 0..jobs.min(groups.len()) {
+            scope.spawn(|| {
+                while let Some(group) = groups.get(next_group.fetch_add(1, Ordering::Relaxed)) {
+                    for dep in *group {
+                        match document_single_dependency(dep, target_dir, metadata, cli) {
+                            Ok(outcome @ (DocOutcome::Documented | DocOutcome::Unchanged)) => {
+                                let documented = dep.documented(metadata, cli);
+                                report_documented(&documented, outcome, cli);
+                                lock(&successful).push(documented);
+                            }
+                            Ok(DocOutcome::Skipped) => {
+                                // Skipped (e.g., binary-only crate) - not added to successful or failed
+                            }
+                            Err(e) => {
+                                lock(&failed).push(dep.label());
+                                problem!("  ✗ {} - {}", dep.label(), e);
+                            }
+                        }
+                    }
+                }
+            });
+        }
+    });
+
+    // Completion order varies between runs; sort for a deterministic master index
+    let mut successful = successful
+        .into_inner()
+        .unwrap_or_else(PoisonError::into_inner);
+    let mut failed = failed.into_inner().unwrap_or_else(PoisonError::into_inner);
+    successful.sort_by(|a: &DocumentedCrate, b| a.dir.cmp(&b.dir));
+    failed.sort();
+    record_failures(&failed);
+
+    (successful, failed)
+}
+
+/// Note crates that failed to document, so `--fail-on-warning` can fail the run
+fn record_failures(failed: &[String]) {
+    if !failed.is_empty() {
+        ANY_FAILED.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Lock a mutex, recovering the data even if another thread panicked while holding it
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn print_documentation_summary(successful: &[DocumentedCrate], failed: &[String]) {
+    progress!("\n📊 Summary:");
+    progress!("  ✓ Successful: {}", successful.len());
+    if !failed.is_empty() {
+        progress!("  ✗ Failed: {} ({})", failed.len(), failed.join(", "));
+    }
+}
+
+fn document_all_dependencies(
+    metadata: &serde_json::Value,
+    cli: &Cli,
+    current_crate: Option<&DocumentedCrate>,
+) -> Result<Vec<DocumentedCrate>> {
+    let deps_to_document =
+        selected_dependencies(get_all_dependencies(metadata, cli.include_dev_deps)?, cli);
+
+    if deps_to_document.is_empty() {
+        progress!("No dependencies found");
+        return Ok(Vec::new());
+    }
+
+    let target_dir = PathBuf::from(metadata["target_directory"].as_str().unwrap_or("target"));
+
+    progress!("📦 Documenting {} dependencies...", deps_to_document.len());
+
+    let documented_before = current_crate.map(std::slice::from_ref).unwrap_or_default();
+    let (successful, failed) = try_document_dependencies(
+        &deps_to_document,
+        documented_before,
+        &target_dir,
+        metadata,
+        cli,
+    );
+
+    print_documentation_summary(&successful, &failed);
+
+    Ok(successful)
+}
+
+fn document_workspace(metadata: &serde_json::Value, cli: &Cli) -> Result<()> {
+    let (workspace_members, excluded_members): (Vec<_>, Vec<_>) = get_workspace_members(metadata)?
+        .into_iter()
+        .partition(|member| !is_excluded_member(&member.name, cli));
+    if workspace_members.is_empty() {
+        bail!("Every workspace member is excluded by --exclude");
+    }
+    if !excluded_members.is_empty() {
+        let names: Vec<&str> = excluded_members.iter().map(|m| m.name.as_str()).collect();
+        progress!("⊘ Excluding workspace member(s): {}", names.join(", "));
+    }
+
+    progress!(
+        "📚 Documenting {} workspace member(s){}...\n",
+        workspace_members.len(),
+        if cli.no_deps {
+            " (without dependencies)"
+        } else {
+            " and their dependencies"
+        }
+    );
+
+    let target_dir = PathBuf::from(metadata["target_directory"].as_str().unwrap_or("target"));
+
+    // Excluded members are left out so they are collected as dependencies instead
+    let workspace_member_ids: Vec<String> = workspace_members
+        .iter()
+        .filter_map(|member| get_package_id(metadata, &member.name, &member.version).ok())
+        .collect();
+
+    let workspace_member_names: HashSet<String> =
+        workspace_members.iter().map(|m| m.name.clone()).collect();
+
+    let mut successful_members = Vec::new();
+    let mut failed_members = Vec::new();
+    let mut all_deps = HashSet::new();
+
+    for member in &workspace_members {
+        if is_selected(cli, &member.name) {
+            progress!(
+                "🔨 Generating docs for workspace member '{}'...",
+                member.name
+            );
+
+            match document_single_dependency(member, &target_dir, metadata, cli) {
+                Ok(outcome @ (DocOutcome::Documented | DocOutcome::Unchanged)) => {
+                    let documented = member.documented(metadata, cli);
+                    report_documented(&documented, outcome, cli);
+                    successful_members.push(documented);
+                }
+                Ok(DocOutcome::Skipped) => {
+                    // Skipped (e.g., binary-only crate)
+                    progress!("  ⊘ {} skipped", member.name);
+                    continue;
+                }
+                Err(e) => {
+                    failed_members.push(member.name.clone());
+                    problem!("  ✗ Failed to document '{}': {}", member.name, e);
+                    continue;
+                }
+            }
+        }
+
+        // Members left out by --only still lead to the dependencies it selects
+        if !cli.no_deps {
+            match get_package_id(metadata, &member.name, &member.version) {
+                Ok(member_id) => {
+                    match get_all_dependencies_recursive(
+                        metadata,
+                        &member_id,
+                        &workspace_member_ids,
+                        cli.include_dev_deps,
+                    ) {
+                        Ok(member_deps) => {
+                            for (name, version) in member_deps {
+                                if !workspace_member_names.contains(&name) {
+                                    all_deps.insert((name, version));
+                                }
+                            }
+                        }
+                        Err(e) => {
+                            problem!(
+                                "  ⚠ Warning: Could not get dependencies for '{}': {}",
+                                member.name,
+                                e
+                            );
+                        }
                     }
                 }
+                Err(e) => {
+                    problem!(
+                        "  ⚠ Warning: Could not find package ID for '{}': {}",
+                        member.name,
+                        e
+                    );
+                }
             }
         }
-        pkg_normal_deps.insert(pkg_id.to_string(), normal_deps);
     }
 
-    // Build the filtered dependency graph
-    for node in nodes {
-        let Some(node_id) = node["id"].as_str() else {
-            continue; // Skip nodes without IDs
-        };
-        let node_pkg = packages.iter().find(|p| p["id"].as_str() == Some(node_id));
-        let normal_deps = pkg_normal_deps.get(node_id);
+    record_failures(&failed_members);
 
-        if let (Some(_pkg), Some(normal_dep_names)) = (node_pkg, normal_deps) {
-            let mut filtered_deps = Vec::new();
+    let deps_to_document = selected_dependencies(dependencies_from_set(all_deps), cli);
+    if !cli.no_deps && !deps_to_document.is_empty() {
+        progress!(
+            "\n📦 Documenting {} unique external dependencies...",
+            deps_to_document.len()
+        );
+
+        let (successful_deps, failed_deps) = try_document_dependencies(
+            &deps_to_document,
+            &successful_members,
+            &target_dir,
+            metadata,
+            cli,
+        );
+
+        print_documentation_summary(&successful_deps, &failed_deps);
+
+        generate_indexes(cli, None, &successful_members, &successful_deps)?;
+    } else {
+        progress!("\n📊 Summary:");
+        progress!(
+            "  ✓ Workspace members documented: {}",
+            successful_members.len()
+        );
+        if !failed_members.is_empty() {
+            progress!(
+                "  ✗ Failed: {} ({})",
+                failed_members.len(),
+                failed_members.join(", ")
+            );
+        }
+
+        generate_indexes(cli, None, &successful_members, &[])?;
+    }
+
+    Ok(())
+}
+
+fn get_package_id(metadata: &serde_json::Value, name: &str, version: &str) -> Result<String> {
+    let packages = metadata["packages"]
+        .as_array()
+        .context("Missing 'packages' in metadata")?;
+
+    for pkg in packages {
+        if pkg["name"].as_str() == Some(name) && pkg["version"].as_str() == Some(version) {
+            return pkg["id"]
+                .as_str()
+                .map(String::from)
+                .context("Package ID not found");
+        }
+    }
+
+    bail!("Package {} {} not found in metadata", name, version)
+}
+
+/// Map of package ID -> IDs of its normal dependencies, plus the dev-dependencies
+/// of `dev_deps_of` when given
+fn build_normal_dependency_graph(
+    metadata: &serde_json::Value,
+    dev_deps_of: Option<&str>,
+) -> Result<HashMap<String, Vec<String>>> {
+    use std::collections::HashSet;
+
+    let packages = metadata["packages"]
+        .as_array()
+        .context("Missing 'packages' in metadata")?;
+
+    let nodes = metadata["resolve"]["nodes"]
+        .as_array()
+        .context("Missing 'nodes' in resolve")?;
+
+    let mut normal_dep_graph: HashMap<String, Vec<String>> = HashMap::new();
+
+    // For each package, build a map of package_id -> Set of normal dependency names
+    let mut pkg_normal_deps: HashMap<String, HashSet<String>> = HashMap::new();
+    for pkg in packages {
+        let Some(pkg_id) = pkg["id"].as_str() else {
+            continue; // Skip packages without IDs
+        };
+        let mut normal_deps = HashSet::new();
+        let include_dev = dev_deps_of == Some(pkg_id);
+
+        if let Some(deps) = pkg["dependencies"].as_array() {
+            for dep in deps {
+                // Only include normal dependencies (kind is null or not present)
+                // Platform filtering is handled by cargo metadata --filter-platform
+                if dep["kind"].is_null() || (include_dev && dep["kind"] == "dev") {
+                    if let Some(dep_name) = dep["name"].as_str() {
+                        normal_deps.insert(dep_name.to_string());
+                    }
+                }
+            }
+        }
+        pkg_normal_deps.insert(pkg_id.to_string(), normal_deps);
+    }
+
+    // Build the filtered dependency graph
+    for node in nodes {
+        let Some(node_id) = node["id"].as_str() else {
+            continue; // Skip nodes without IDs
+        };
+        let node_pkg = packages.iter().find(|p| p["id"].as_str() == Some(node_id));
+        let normal_deps = pkg_normal_deps.get(node_id);
+
+        if let (Some(_pkg), Some(normal_dep_names)) = (node_pkg, normal_deps) {
+            let mut filtered_deps = Vec::new();
+
+            if let Some(dep_ids) = node["dependencies"].as_array() {
+                for dep_id in dep_ids {
+                    if let Some(dep_id_str) = dep_id.as_str() {
+                        // Check if this dependency is in the normal deps list
+                        if let Some(dep_pkg) = packages
+                            .iter()
+                            .find(|p| p["id"].as_str() == Some(dep_id_str))
+                        {
+                            if let Some(dep_name) = dep_pkg["name"].as_str() {
+                                if normal_dep_names.contains(dep_name) {
+                                    filtered_deps.push(dep_id_str.to_string());
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+
+            normal_dep_graph.insert(node_id.to_string(), filtered_deps);
+        }
+    }
+
+    Ok(normal_dep_graph)
+}
+
+/// Every crate `package_id` depends on, directly or not, with its own
+/// dev-dependencies when `include_dev_deps` is set
+fn get_all_dependencies_recursive(
+    metadata: &serde_json::Value,
+    package_id: &str,
+    workspace_member_ids: &[String],
+    include_dev_deps: bool,
+) -> Result<HashSet<(String, String)>> {
+    let packages = metadata["packages"]
+        .as_array()
+        .context("Missing 'packages' in metadata")?;
+
+    let dev_deps_of = include_dev_deps.then_some(package_id);
+    let normal_dep_graph = build_normal_dependency_graph(metadata, dev_deps_of)?;
+
+    let mut all_deps = HashSet::new();
+    let mut visited = HashSet::new();
+    let mut to_visit = vec![package_id.to_string()];
+
+    while let Some(current_id) = to_visit.pop() {
+        if visited.contains(&current_id) {
+            continue;
+        }
+        visited.insert(current_id.clone());
+
+        if let Some(dep_ids) = normal_dep_graph.get(&current_id) {
+            for dep_id in dep_ids {
+                // Skip workspace members
+                if workspace_member_ids.contains(dep_id) {
+                    continue;
+                }
+
+                // Add to visit queue for recursive traversal
+                if !visited.contains(dep_id) {
+                    to_visit.push(dep_id.clone());
+                }
+
+                // Add to result if not already there
+                if let Some(pkg) = packages
+                    .iter()
+                    .find(|p| p["id"].as_str() == Some(dep_id.as_str()))
+                {
+                    if let (Some(name), Some(version)) =
+                        (pkg["name"].as_str(), pkg["version"].as_str())
+                    {
+                        // Semver-incompatible versions of one crate are documented separately
+                        all_deps.insert((name.to_string(), version.to_string()));
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(all_deps)
+}
+
+fn get_all_dependencies(
+    metadata: &serde_json::Value,
+    include_dev_deps: bool,
+) -> Result<Vec<Dependency>> {
+    let resolve = &metadata["resolve"];
+    let Some(root) = resolve["root"].as_str() else {
+        bail!(
+            "Cannot document dependencies from a virtual workspace root.\n\
+             Virtual workspaces have no root package.\n\
+             Use: cargo doc-md --workspace  (to document all workspace members)\n\
+             Or:  cargo doc-md -p <package>  (to document a specific package)"
+        );
+    };
+
+    let workspace_member_ids: Vec<String> = metadata["workspace_members"]
+        .as_array()
+        .map(|members| {
+            members
+                .iter()
+                .filter_map(|v| v.as_str().map(String::from))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let deps =
+        get_all_dependencies_recursive(metadata, root, &workspace_member_ids, include_dev_deps)?;
+
+    Ok(dependencies_from_set(deps))
+}
+
+/// Whether `--only` leaves `name` to be documented; every crate is when it isn't given
+fn is_selected(cli: &Cli, name: &str) -> bool {
+    cli.only.is_empty()
+        || cli
+            .only
+            .iter()
+            .any(|only| only.replace('-', "_") == name.replace('-', "_"))
+}
+
+/// `deps` without the crates `--only` leaves out, keeping the output directories
+/// they were given alongside the others
+fn selected_dependencies(mut deps: Vec<Dependency>, cli: &Cli) -> Vec<Dependency> {
+    deps.retain(|dep| is_selected(cli, &dep.name));
+    deps
+}
+
+/// Sorted dependencies from `(name, version)` pairs, with versioned output
+/// directories for crates that appear in more than one version
+fn dependencies_from_set(deps: HashSet<(String, String)>) -> Vec<Dependency> {
+    let mut deps: Vec<Dependency> = deps
+        .into_iter()
+        .map(|(name, version)| Dependency {
+            name,
+            version,
+            versioned_dir: false,
+            dependency: true,
+        })
+        .collect();
+    deps.sort_by(|a, b| (&a.name, &a.version).cmp(&(&b.name, &b.version)));
+
+    let mut name_counts: HashMap<String, usize> = HashMap::new();
+    for dep in &deps {
+        *name_counts.entry(dep.name.clone()).or_default() += 1;
+    }
+    for dep in &mut deps {
+        dep.versioned_dir = name_counts[&dep.name] > 1;
+    }
+
+    deps
+}
+
+impl Dependency {
+    /// Name used in progress messages, with the version when it disambiguates
+    fn label(&self) -> String {
+        if self.versioned_dir {
+            format!("{} {}", self.name, self.version)
+        } else {
+            self.name.clone()
+        }
+    }
+
+    /// Library target name from metadata (may differ from the package name)
+    fn lib_target_name(&self, metadata: &serde_json::Value) -> String {
+        metadata["packages"]
+            .as_array()
+            .and_then(|packages| {
+                packages.iter().find(|p| {
+                    p["name"].as_str() == Some(self.name.as_str())
+                        && (self.version.is_empty()
+                            || p["version"].as_str() == Some(self.version.as_str()))
+                })
+            })
+            .and_then(get_lib_target_name)
+            .unwrap_or_else(|| self.name.replace("-", "_"))
+    }
+
+    /// Output directory, relative to the output root: the lib target name, plus the
+    /// semver-compatible version (e.g. `rand-0.8`) when several versions are
+    /// documented, under `deps/` for dependencies in the nested layout
+    fn dir_name(&self, metadata: &serde_json::Value, cli: &Cli) -> String {
+        let lib_target_name = self.lib_target_name(metadata);
+        let dir = if self.versioned_dir {
+            format!(
+                "{}-{}",
+                lib_target_name,
+                semver_compatible_version(&self.version)
+            )
+        } else {
+            lib_target_name
+        };
+        layout_dir(cli, dir, self.dependency)
+    }
+
+    fn documented(&self, metadata: &serde_json::Value, cli: &Cli) -> DocumentedCrate {
+        DocumentedCrate {
+            name: self.name.clone(),
+            version: self.version.clone(),
+            dir: self.dir_name(metadata, cli),
+        }
+    }
+}
+
+/// The part of a version that semver treats as breaking: `1.2.3` → `1`,
+/// `0.8.5` → `0.8`, `0.0.3` → `0.0.3`
+fn semver_compatible_version(version: &str) -> &str {
+    let core = version.split(['-', '+']).next().unwrap_or(version);
+    let parts: Vec<&str> = core.split('.').collect();
+    let significant = match parts.as_slice() {
+        ["0", "0", ..] => parts.len(),
+        ["0", ..] => 2,
+        _ => 1,
+    }
+    .min(parts.len());
+
+    let len = parts[..significant].iter().map(|p| p.len()).sum::<usize>() + significant - 1;
+    &core[..len]
+}
+
+/// Whether an `--exclude` pattern names this workspace member, by package name or
+/// by crate name
+fn is_excluded_member(name: &str, cli: &Cli) -> bool {
+    let crate_name = name.replace('-', "_");
+    cli.exclude
+        .iter()
+        .any(|pattern| glob_matches(pattern, name) || glob_matches(pattern, &crate_name))
+}
+
+fn get_workspace_members(metadata: &serde_json::Value) -> Result<Vec<Dependency>> {
+    let workspace_members = metadata["workspace_members"]
+        .as_array()
+        .context("Missing 'workspace_members' in metadata")?;
+
+    if workspace_members.is_empty() {
+        bail!(
+            "Not in a workspace or workspace has no members.\n\
+             The --workspace flag requires a Cargo workspace.\n\
+             For single-crate projects, use: cargo doc-md (without --workspace)"
+        );
+    }
+
+    let packages = metadata["packages"]
+        .as_array()
+        .context("Missing 'packages' in metadata")?;
+
+    let mut members = Vec::new();
+    for member_id in workspace_members {
+        let member_id_str = member_id.as_str().context("Invalid workspace member ID")?;
+
+        if let Some(pkg) = packages
+            .iter()
+            .find(|p| p["id"].as_str() == Some(member_id_str))
+        {
+            if let (Some(name), Some(version)) = (pkg["name"].as_str(), pkg["version"].as_str()) {
+                members.push(Dependency {
+                    name: name.to_string(),
+                    version: version.to_string(),
+                    versioned_dir: false,
+                    dependency: false,
+                });
+            }
+        }
+    }
+
+    members.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(members)
+}
+
+/// Outcome of documenting a single crate
+#[derive(Debug, Clone, Copy)]
+enum DocOutcome {
+    /// Markdown was (re)generated
+    Documented,
+    /// Rustdoc JSON and options match the cache, so the existing output was kept
+    Unchanged,
+    /// Nothing to document (e.g., binary-only crate)
+    Skipped,
+}
+
+fn report_documented(documented: &DocumentedCrate, outcome: DocOutcome, cli: &Cli) {
+    if let DocOutcome::Unchanged = outcome {
+        progress!("  ⏭ {} (unchanged)", documented.name);
+    } else {
+        progress!(
+            "  ✓ {} → {}/{}",
+            documented.name,
+            cli.output.display(),
+            crate_index_path(cli, &documented.dir)
+        );
+    }
+}
+
+/// Documents a crate with `cargo rustdoc`, Err on failure
+fn document_single_dependency(
+    dep: &Dependency,
+    target_dir: &Path,
+    metadata: &serde_json::Value,
+    cli: &Cli,
+) -> Result<DocOutcome> {
+    // Build the package specification
+    // If we have a version, use name@version to disambiguate multiple versions
+    let package_spec = if dep.version.is_empty() {
+        dep.name.clone()
+    } else {
+        format!("{}@{}", dep.name, dep.version)
+    };
+
+    // Generate rustdoc JSON for the dependency, retrying with backoff when another
+    // cargo process kept the build lock
+    let mut attempt = 0;
+    let mut dev_only = false;
+    let output = loop {
+        let mut command = Command::new("cargo");
+        if dev_only {
+            command.args(dev_only_rustdoc_args(&package_spec, cli));
+            if documents_private_items(cli) {
+                let mut flags = std::env::var("RUSTDOCFLAGS").unwrap_or_default();
+                flags.push_str(" --document-private-items");
+                command.env("RUSTDOCFLAGS", flags.trim_start());
+            }
+        } else {
+            command.args(rustdoc_args(Some(&package_spec), &CrateTarget::Lib, cli));
+        }
+        let output = command.output().context("Failed to run cargo rustdoc")?;
+        if !output.status.success()
+            && !dev_only
+            && cli.include_dev_deps
+            && is_dev_only_resolve_failure(&String::from_utf8_lossy(&output.stderr))
+        {
+            dev_only = true;
+            continue;
+        }
+        if output.status.success()
+            || attempt >= cli.retries
+            || !is_lock_contention(&String::from_utf8_lossy(&output.stderr))
+        {
+            break output;
+        }
+        attempt += 1;
+        let delay = Duration::from_millis(500 << attempt.min(6));
+        progress!(
+            "  ↻ {} - cargo's build lock is busy, retrying in {:.1}s ({}/{})",
+            dep.label(),
+            delay.as_secs_f64(),
+            attempt,
+            cli.retries
+        );
+        std::thread::sleep(delay);
+    };
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        // Check for known non-error cases
+        if stderr.contains("no library targets found") {
+            progress!("  ⚠ No library target found (binary-only crate), skipping documentation");
+            return Ok(DocOutcome::Skipped); // Skipped, not an error
+        }
+        if let Some(target) = cli.target.as_deref().filter(|_| is_missing_target(&stderr)) {
+            bail!(missing_target_message(target));
+        }
+        if cli.verbose {
+            bail!("Failed to build '{}':\n{}", dep.name, stderr.trim_end());
+        }
+        // Show first few error lines
+        let error_lines: Vec<&str> = stderr
+            .lines()
+            .filter(|line| line.contains("error") || line.contains("failed"))
+            .take(2)
+            .collect();
+        if !error_lines.is_empty() {
+            bail!(
+                "Failed to build '{}':\n{}\n\nRun 'cargo build -p {}' or pass --verbose for full details",
+                dep.name,
+                error_lines.join("\n"),
+                package_spec
+            );
+        }
+        bail!(
+            "Failed to build '{}' (exit code: {})\nRun 'cargo build -p {}' or pass --verbose for details",
+            dep.name,
+            output.status,
+            package_spec
+        );
+    }
+
+    // The JSON file is named after the lib target, which may differ from the package name
+    let lib_target_name = dep.lib_target_name(metadata);
+    let crate_dir_name = dep.dir_name(metadata, cli);
+
+    // Find the generated JSON file
+    let json_path = rustdoc_json_dir(target_dir, cli).join(format!("{}.json", lib_target_name));
+
+    if !json_path.exists() {
+        bail!("Generated JSON file not found at {}", json_path.display());
+    }
+
+    let cache_key = if cli.no_cache || cli.stdout || cli.emit == Emit::Ir {
+        None
+    } else {
+        Some(conversion_cache_key(&json_path, &crate_dir_name, cli)?)
+    };
+
+    let cache_file = cli
+        .output
+        .join(".cache")
+        .join(format!("{}.hash", crate_dir_name));
+
+    if let Some(key) = &cache_key {
+        let cached = std::fs::read_to_string(&cache_file).ok();
+        if cached.as_deref() == Some(key.as_str())
+            && cli
+                .output
+                .join(crate_index_path(cli, &crate_dir_name))
+                .is_file()
+            && crate_manifest_path(cli, &crate_dir_name).is_file()
+            && (!cli.search_index
+                || cli
+                    .output
+                    .join(search_index_path(cli, &crate_dir_name))
+                    .is_file())
+        {
+            return Ok(DocOutcome::Unchanged);
+        }
+    }
+
+    convert_crate(&json_path, &crate_dir_name, cli)?;
+
+    if let Some(key) = cache_key {
+        if let Some(cache_dir) = cache_file.parent() {
+            std::fs::create_dir_all(cache_dir).with_context(|| {
+                format!("Failed to create cache directory: {}", cache_dir.display())
+            })?;
+        }
+        std::fs::write(&cache_file, key)
+            .with_context(|| format!("Failed to write cache file: {}", cache_file.display()))?;
+    }
+
+    Ok(DocOutcome::Documented)
+}
+
+/// Cache key covering the rustdoc JSON, the rendering options, and the tool version
+fn conversion_cache_key(json_path: &Path, crate_dir_name: &str, cli: &Cli) -> Result<String> {
+    let json = std::fs::read(json_path)
+        .with_context(|| format!("Failed to read file: {}", json_path.display()))?;
+
+    let settings = format!(
+        "{}\n{:?}\n{:?}\n",
+        env!("CARGO_PKG_VERSION"),
+        conversion_options(json_path, crate_dir_name, cli).markdown_options(),
+        documented_disambiguated_items(cli, crate_dir_name)
+    );
+
+    // 64-bit FNV-1a: stable across Rust versions, unlike std's DefaultHasher
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in settings.as_bytes().iter().chain(&json) {
+        hash ^= u64::from(*byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+
+    Ok(format!("{:016x}", hash))
+}
+
+fn conversion_options<'a>(
+    json_path: &'a Path,
+    crate_dir_name: &'a str,
+    cli: &'a Cli,
+) -> ConversionOptions<'a> {
+    let mut options = ConversionOptions::new(json_path, &cli.output);
+    options.include_private = cli.include_private;
+    options.frontmatter = cli.frontmatter;
+    options.documented_crates = &cli.documented_crates;
+    options.exclude = &cli.exclude;
+    options.src_base = cli.src_base.as_deref();
+    options.crate_dir = Some(crate_dir_name);
+    options.single_file = cli.single_file;
+    options.max_depth = cli.max_depth;
+    options.wikilinks = cli.wikilinks;
+    options.base_url = cli.base_url.as_deref();
+    options.since = cli.since.as_deref();
+    options.collapsible = cli.collapsible;
+    options.keep_hidden_lines = cli.keep_hidden_lines;
+    options.extract_sections = cli.extract_sections;
+    options.order = match cli.order {
+        Order::Alpha => ItemOrder::Alpha,
+        Order::Source => ItemOrder::Source,
+    };
+    options.visibility = match cli.visibility {
+        None | Some(Visibility::Public) => VisibilityLevel::Public,
+        Some(Visibility::Crate) => VisibilityLevel::Crate,
+        Some(Visibility::Restricted) => VisibilityLevel::Restricted,
+        Some(Visibility::Private) => VisibilityLevel::Private,
+    };
+    options
+}
+
+/// Whether rustdoc has to document private items, for items less visible than `pub`
+fn documents_private_items(cli: &Cli) -> bool {
+    cli.include_private
+        || cli
+            .visibility
+            .is_some_and(|level| level != Visibility::Public)
+}
+
+/// Whether this run may document more than one crate
+fn is_multi_crate_run(cli: &Cli) -> bool {
+    cli.json_dir.is_some()
+        || cli.json.is_none() && (!cli.no_deps || cli.workspace || cli.package.len() > 1)
+}
+
+/// Read a rustdoc JSON file for the crate it documents, whose output directory is
+/// named after the crate
+fn load_json_crate(json_path: &Path) -> Result<DocumentedCrate> {
+    // Load the JSON to extract the actual crate name from metadata
+    let json = cargo_doc_md::parser::read_json_file(json_path)?;
+    let crate_data = cargo_doc_md::parser::parse_rustdoc_json(&json)
+        .with_context(|| format!("Failed to parse JSON from: {}", json_path.display()))?;
+    let root_item = crate_data
+        .index
+        .get(&crate_data.root)
+        .context("Root item not found in rustdoc JSON")?;
+    let crate_name = root_item
+        .name
+        .as_deref()
+        .context("Crate name not found in rustdoc JSON")?;
+    progress!(
+        "Loaded crate: {} (format version: {})",
+        crate_name,
+        crate_data.format_version
+    );
+
+    Ok(DocumentedCrate {
+        name: crate_name.to_string(),
+        version: crate_data.crate_version.clone().unwrap_or_default(),
+        dir: crate_name.to_string(),
+    })
+}
+
+/// Convert every rustdoc JSON file in `json_dir` and index them as dependencies,
+/// the way `--json` indexes its one crate
+///
+/// Files that aren't rustdoc JSON, or are from an unsupported format version, are
+/// reported and skipped.
+fn convert_json_dir(json_dir: &Path, cli: &mut Cli) -> Result<()> {
+    let json_paths = cargo_doc_md::parser::json_files_in(json_dir)?;
+
+    progress!(
+        "📦 Converting {} rustdoc JSON files from {}...",
+        json_paths.len(),
+        json_dir.display()
+    );
+
+    let mut loaded = Vec::new();
+    let mut failed = Vec::new();
+    for json_path in json_paths {
+        match load_json_crate(&json_path) {
+            Ok(documented) => loaded.push((json_path, documented)),
+            Err(e) => {
+                problem!("  ✗ {}", e);
+                failed.push(json_path.display().to_string());
+            }
+        }
+    }
+
+    // Every crate in the directory is documented, so links between them stay local
+    cli.documented_crates = loaded
+        .iter()
+        .map(|(_, documented)| (documented.name.clone(), documented.dir.clone()))
+        .collect();
+
+    let mut successful = Vec::new();
+    for (json_path, documented) in loaded {
+        match convert_crate(&json_path, &documented.dir, cli) {
+            Ok(()) => successful.push(documented),
+            Err(e) => {
+                problem!("  ✗ Failed to convert '{}': {}", documented.name, e);
+                failed.push(documented.name);
+            }
+        }
+    }
+
+    record_failures(&failed);
+    print_documentation_summary(&successful, &failed);
+
+    generate_indexes(cli, None, &[], &successful)
+}
+
+/// Convert one crate's rustdoc JSON into its output directory, or print it in `--stdout` mode
+fn convert_crate(json_path: &Path, crate_dir_name: &str, cli: &Cli) -> Result<()> {
+    let disambiguated_items = documented_disambiguated_items(cli, crate_dir_name);
+    let mut options = conversion_options(json_path, crate_dir_name, cli);
+    options.disambiguated_items = &disambiguated_items;
+
+    if cli.emit == Emit::Ir {
+        let ir = serde_json::to_string_pretty(&cargo_doc_md::convert_json_file_to_ir(&options)?)?;
+        if cli.stdout {
+            println!("{}", ir);
+            return Ok(());
+        }
+        let path = PathBuf::from(format!("{}.json", crate_dir_name));
+        return cargo_doc_md::writer::write_files(&cli.output, &[(path, ir + "\n")]);
+    }
+
+    if cli.stdout {
+        let markdown = cargo_doc_md::convert_json_file_to_string(&options)?;
+        if is_multi_crate_run(cli) {
+            println!("# ===== {} =====\n", crate_dir_name);
+        }
+        print!("{}", markdown);
+        return Ok(());
+    }
+
+    // The crate's old output is replaced as a whole once conversion succeeds
+    let output = cargo_doc_md::convert_json_file(&options)?;
+    let stale_dir = cli.output.join(crate_dir_name);
+    if cli.single_file && stale_dir.is_dir() {
+        std::fs::remove_dir_all(&stale_dir).with_context(|| {
+            format!(
+                "Failed to remove existing crate directory: {}",
+                stale_dir.display()
+            )
+        })?;
+    }
+    if cli.search_index {
+        write_search_index(cli, crate_dir_name, &output)?;
+    }
+    write_crate_manifest(cli, crate_dir_name, &output)?;
+
+    lock(&DISAMBIGUATED).insert(
+        crate_dir_name.to_string(),
+        output.disambiguated_items.into_iter().collect(),
+    );
+    lock(&CONVERTED).push(ConvertedCrate {
+        json_path: json_path.to_path_buf(),
+        dir: crate_dir_name.to_string(),
+        disambiguated_items,
+    });
+    Ok(())
+}
+
+/// A crate converted in this run, with the items of other crates its links were
+/// resolved against
+struct ConvertedCrate {
+    json_path: PathBuf,
+    dir: String,
+    disambiguated_items: Vec<(String, String)>,
+}
+
+/// Items of the other crates the run documents whose headings have their kind
+/// appended, for links from the crate in `crate_dir_name` into them
+///
+/// Crates not converted yet are taken from their manifest of an earlier run, if any.
+fn documented_disambiguated_items(cli: &Cli, crate_dir_name: &str) -> Vec<(String, String)> {
+    let mut known = lock(&DISAMBIGUATED);
+    let mut items = Vec::new();
+    for (_, dir) in &cli.documented_crates {
+        if dir == crate_dir_name {
+            continue;
+        }
+        let crate_items = known.entry(dir.clone()).or_insert_with(|| {
+            std::fs::read_to_string(crate_manifest_path(cli, dir))
+                .ok()
+                .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).ok())
+                .and_then(|manifest| serde_json::from_value(manifest["disambiguated"].clone()).ok())
+                .unwrap_or_default()
+        });
+        items.extend(crate_items.iter().cloned());
+    }
+    items.sort();
+    items
+}
+
+/// Convert again the crates whose links were resolved before a crate they link into
+/// was converted, if its headings turned out to need their kind to be told apart
+fn relink_converted_crates(cli: &Cli) -> Result<()> {
+    let converted = std::mem::take(&mut *lock(&CONVERTED));
+    for converted in converted {
+        if documented_disambiguated_items(cli, &converted.dir) != converted.disambiguated_items {
+            progress!(
+                "🔗 Relinking {} to crates converted after it",
+                converted.dir
+            );
+            convert_crate(&converted.json_path, &converted.dir, cli)?;
+        }
+    }
+    Ok(())
+}
+
+/// Where `--search-index` writes a crate's index, relative to the output root
+fn search_index_path(cli: &Cli, crate_dir_name: &str) -> String {
+    if cli.single_file {
+        format!("{}.search.json", crate_dir_name)
+    } else {
+        format!("{}/search.json", crate_dir_name)
+    }
+}
+
+/// Write the flat search index for one crate, with files relative to the index itself
+fn write_search_index(cli: &Cli, crate_dir_name: &str, output: &MarkdownOutput) -> Result<()> {
+    let entries: Vec<_> = output
+        .search_index
+        .iter()
+        .map(|entry| {
+            let file = if cli.single_file {
+                crate_index_path(cli, crate_dir_name)
+            } else {
+                entry.file.clone()
+            };
+            json!({
+                "path": entry.path,
+                "kind": entry.kind,
+                "summary": entry.summary,
+                "file": file,
+                "anchor": entry.anchor,
+                "aliases": entry.aliases,
+            })
+        })
+        .collect();
+
+    let path = cli.output.join(search_index_path(cli, crate_dir_name));
+    std::fs::write(&path, serde_json::to_string_pretty(&entries)? + "\n")
+        .with_context(|| format!("Failed to write search index: {}", path.display()))
+}
+
+/// Entry point of a crate's output, relative to the output root
+fn crate_index_path(cli: &Cli, crate_dir_name: &str) -> String {
+    if cli.single_file {
+        format!("{}.md", crate_dir_name)
+    } else {
+        format!("{}/index.md", crate_dir_name)
+    }
+}
+
+/// Where the `manifest.json` entry for a converted crate is kept between runs
+fn crate_manifest_path(cli: &Cli, crate_dir_name: &str) -> PathBuf {
+    cli.output
+        .join(".cache")
+        .join(format!("{}.manifest.json", crate_dir_name))
+}
+
+/// Record what was generated for one crate, so crates skipped as unchanged
+/// on later runs still appear in `manifest.json`
+fn write_crate_manifest(cli: &Cli, crate_dir_name: &str, output: &MarkdownOutput) -> Result<()> {
+    let mut modules: Vec<_> = output.item_counts.iter().collect();
+    modules.sort_by_key(|(path, _)| *path);
+
+    let modules: Vec<_> = modules
+        .into_iter()
+        .map(|(path, counts)| {
+            let path = if cli.single_file {
+                crate_index_path(cli, crate_dir_name)
+            } else {
+                format!("{}/{}", crate_dir_name, path)
+            };
+            json!({
+                "path": path,
+                "items": counts,
+            })
+        })
+        .collect();
+
+    // Modules and the crate root's own items, for llms.txt
+    let outline: Vec<_> = output
+        .search_index
+        .iter()
+        .filter(|entry| entry.kind == "module" || entry.path.matches("::").count() == 1)
+        .map(|entry| {
+            let file = if cli.single_file {
+                crate_index_path(cli, crate_dir_name)
+            } else {
+                format!("{}/{}", crate_dir_name, entry.file)
+            };
+            let link = match &entry.anchor {
+                Some(anchor) => format!("{}#{}", file, anchor),
+                None => file,
+            };
+            json!({
+                "path": entry.path,
+                "summary": entry.summary,
+                "link": link,
+            })
+        })
+        .collect();
+
+    // Every markdown file written for the crate, for index.txt
+    let mut files: Vec<String> = if cli.single_file {
+        vec![crate_index_path(cli, crate_dir_name)]
+    } else {
+        output
+            .file_sizes
+            .keys()
+            .map(|file| format!("{}/{}", crate_dir_name, file))
+            .collect()
+    };
+    files.sort();
+
+    let entry = json!({
+        "version": output.crate_version,
+        "summary": output.crate_summary,
+        "modules": modules,
+        "outline": outline,
+        "files": files,
+        // Read back when other crates link into this one
+        "disambiguated": output.disambiguated_items,
+    });
+
+    let path = crate_manifest_path(cli, crate_dir_name);
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)
+            .with_context(|| format!("Failed to create cache directory: {}", dir.display()))?;
+    }
+    std::fs::write(&path, serde_json::to_string_pretty(&entry)?)
+        .with_context(|| format!("Failed to write crate manifest: {}", path.display()))
+}
+
+/// Write the output-root index files (master index, plus SUMMARY.md with `--mdbook`)
+fn generate_indexes(
+    cli: &Cli,
+    current_crate: Option<&DocumentedCrate>,
+    workspace_members: &[DocumentedCrate],
+    dependencies: &[DocumentedCrate],
+) -> Result<()> {
+    // The indexes link to markdown, which IR output doesn't produce
+    if cli.emit == Emit::Ir {
+        return Ok(());
+    }
+
+    // Only some crates were regenerated; index everything already in the output
+    let output_crates;
+    let (current_crate, workspace_members, dependencies) = if !cli.only.is_empty() && !cli.stdout {
+        output_crates = crates_in_output(cli)?;
+        let (current_crate, workspace_members, dependencies) = &output_crates;
+        (
+            current_crate.as_ref(),
+            &workspace_members[..],
+            &dependencies[..],
+        )
+    } else {
+        (current_crate, workspace_members, dependencies)
+    };
+
+    if !cli.no_index {
+        generate_master_index(cli, current_crate, workspace_members, dependencies)?;
+    }
+    generate_manifest(cli, current_crate, workspace_members, dependencies)?;
+
+    if cli.mdbook && !cli.stdout {
+        generate_mdbook_summary(cli, current_crate, workspace_members, dependencies)?;
+    }
+
+    if cli.llms_txt && !cli.stdout {
+        generate_llms_txt(cli, current_crate, workspace_members, dependencies)?;
+    }
+
+    if cli.file_list && !cli.stdout {
+        generate_file_list(cli, current_crate, workspace_members, dependencies)?;
+    }
+
+    if cli.stats {
+        print_stats(cli, current_crate, workspace_members, dependencies);
+    }
+
+    Ok(())
+}
+
+fn generate_master_index(
+    cli: &Cli,
+    current_crate: Option<&DocumentedCrate>,
+    workspace_members: &[DocumentedCrate],
+    dependencies: &[DocumentedCrate],
+) -> Result<()> {
+    use std::fs;
+
+    // Nothing is written to disk in stdout mode
+    if cli.stdout {
+        return Ok(());
+    }
+
+    let output_dir = cli.output.as_path();
+
+    let mut content = String::new();
+
+    if cli.frontmatter {
+        content.push_str(&frontmatter("Documentation Index", "index", 1));
+    }
+
+    content.push_str("# Documentation Index\n\n");
+    content.push_str("Generated markdown documentation for this project.\n\n");
+
+    // Current crate section
+    if let Some(current) = current_crate {
+        content.push_str("## Current Crate\n\n");
+        content.push_str(&index_entry(cli, current, &[]));
+        content.push('\n');
+    }
+
+    // Workspace members section
+    if !workspace_members.is_empty() {
+        content.push_str(&format!(
+            "## Workspace Members ({})\n\n",
+            workspace_members.len()
+        ));
+
+        for member in workspace_members {
+            content.push_str(&index_entry(cli, member, workspace_members));
+        }
+        content.push('\n');
+    }
+
+    // Dependencies section
+    if !dependencies.is_empty() {
+        content.push_str(&format!("## Dependencies ({})\n\n", dependencies.len()));
+
+        for dep in dependencies {
+            content.push_str(&index_entry(cli, dep, dependencies));
+        }
+        content.push('\n');
+    }
+
+    if !cli.no_footer {
+        content.push_str("---\n\n");
+        content.push_str(
+            "Generated with [cargo-doc-md](https://github.com/Crazytieguy/cargo-doc-md)\n",
+        );
+    }
+
+    // Ensure output directory exists
+    fs::create_dir_all(output_dir).with_context(|| {
+        format!(
+            "Failed to create output directory: {}",
+            output_dir.display()
+        )
+    })?;
+
+    if cli.wikilinks {
+        content = wikilinks(&content, "");
+    }
+    if let Some(base_url) = &cli.base_url {
+        content = base_url_links(&content, "", base_url);
+    }
+
+    let index_path = output_dir.join("index.md");
+    fs::write(&index_path, content)
+        .with_context(|| format!("Failed to write master index: {}", index_path.display()))?;
+
+    progress!("\n✓ Master index: {}", index_path.display());
+
+    // An index that lists no crates isn't worth opening
+    let documented_any =
+        current_crate.is_some() || !workspace_members.is_empty() || !dependencies.is_empty();
+    if cli.open && documented_any {
+        open_in_default_app(&index_path);
+    }
+
+    Ok(())
+}
+
+/// Open `path` with the platform opener, warning instead of failing when there is
+/// none or it can't handle the file
+fn open_in_default_app(path: &Path) {
+    let mut command = if cfg!(target_os = "windows") {
+        let mut command = Command::new("cmd");
+        // `start` takes its first quoted argument as the window title
+        command.args(["/C", "start", ""]);
+        command
+    } else if cfg!(target_os = "macos") {
+        Command::new("open")
+    } else {
+        Command::new("xdg-open")
+    };
+    let opener = command.get_program().to_string_lossy().into_owned();
+
+    match command.arg(path).status() {
+        Ok(status) if status.success() => {}
+        Ok(status) => problem!(
+            "⚠ Warning: {} could not open {} ({})",
+            opener,
+            path.display(),
+            status
+        ),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => problem!(
+            "⚠ Warning: Not opening {}: {} is not installed",
+            path.display(),
+            opener
+        ),
+        Err(e) => problem!(
+            "⚠ Warning: Failed to run {} for {}: {}",
+            opener,
+            path.display(),
+            e
+        ),
+    }
+}
+
+/// Rebuild the master index from the crate directories already under the output
+/// directory
+fn regenerate_master_index(cli: &Cli) -> Result<()> {
+    let (current_crate, workspace_members, dependencies) = crates_in_output(cli)?;
+
+    generate_master_index(
+        cli,
+        current_crate.as_ref(),
+        &workspace_members,
+        &dependencies,
+    )?;
+
+    if cli.llms_txt {
+        generate_llms_txt(
+            cli,
+            current_crate.as_ref(),
+            &workspace_members,
+            &dependencies,
+        )?;
+    }
+
+    if cli.file_list {
+        generate_file_list(
+            cli,
+            current_crate.as_ref(),
+            &workspace_members,
+            &dependencies,
+        )?;
+    }
+
+    Ok(())
+}
+
+/// The crates already under the output directory, split into the master index's
+/// sections
+///
+/// Names, versions, and sections come from `manifest.json` when it lists them and are
+/// otherwise inferred from directory names like `rand-0.8`.
+fn crates_in_output(
+    cli: &Cli,
+) -> Result<(
+    Option<DocumentedCrate>,
+    Vec<DocumentedCrate>,
+    Vec<DocumentedCrate>,
+)> {
+    use std::fs;
+
+    let output_dir = cli.output.as_path();
+    let manifest_path = output_dir.join("manifest.json");
+    let manifest: serde_json::Value = match fs::read_to_string(&manifest_path) {
+        Ok(content) => serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse manifest: {}", manifest_path.display()))?,
+        Err(_) => json!({}),
+    };
+    let recorded: HashMap<&str, &serde_json::Value> = manifest["crates"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| Some((entry["index"].as_str()?, entry)))
+        .collect();
+
+    // The nested layout keeps dependencies one level down, under deps/
+    let mut search_dirs = vec![(String::new(), output_dir.to_path_buf())];
+    if cli.layout == Layout::Nested && output_dir.join("deps").is_dir() {
+        search_dirs.push(("deps/".to_string(), output_dir.join("deps")));
+    }
+
+    let mut crate_dirs = Vec::new();
+    for (prefix, search_dir) in search_dirs {
+        let entries = fs::read_dir(&search_dir)
+            .with_context(|| format!("Failed to read directory: {}", search_dir.display()))?;
+        for entry in entries {
+            let path = entry?.path();
+            // Hidden entries are output still being written by another run
+            let Some(name) = path
+                .file_name()
+                .and_then(|name| name.to_str())
+                .filter(|name| !name.starts_with('.'))
+            else {
+                continue;
+            };
+            let crate_dir = if cli.single_file {
+                path.is_file()
+                    .then(|| name.strip_suffix(".md"))
+                    .flatten()
+                    .filter(|stem| !["index", "SUMMARY"].contains(stem))
+            } else {
+                path.join("index.md").is_file().then_some(name)
+            };
+            crate_dirs.extend(crate_dir.map(|dir| format!("{}{}", prefix, dir)));
+        }
+    }
+    crate_dirs.sort();
+
+    if crate_dirs.is_empty() {
+        bail!("No generated crates found in {}", output_dir.display());
+    }
+
+    let mut current_crate = None;
+    let mut workspace_members = Vec::new();
+    let mut dependencies = Vec::new();
+    for dir in crate_dirs {
+        let recorded = recorded.get(crate_index_path(cli, &dir).as_str());
+        let field = |key: &str| recorded.and_then(|entry| entry[key].as_str());
+        let cached_version = fs::read_to_string(crate_manifest_path(cli, &dir))
+            .ok()
+            .and_then(|content| serde_json::from_str::<serde_json::Value>(&content).ok())
+            .and_then(|entry| entry["version"].as_str().map(String::from));
+        let base_name = dir.rsplit('/').next().unwrap_or(&dir);
+        let (dir_name, dir_version) = base_name.split_once('-').unwrap_or((base_name, ""));
+
+        let documented = DocumentedCrate {
+            name: field("name").unwrap_or(dir_name).to_string(),
+            version: field("version")
+                .map(String::from)
+                .or(cached_version)
+                .unwrap_or_else(|| dir_version.to_string()),
+            dir: dir.clone(),
+        };
+        match field("section") {
+            Some("current") if current_crate.is_none() => current_crate = Some(documented),
+            Some("workspace") => workspace_members.push(documented),
+            _ => dependencies.push(documented),
+        }
+    }
+
+    Ok((current_crate, workspace_members, dependencies))
+}
+
+/// Master index line for a crate, showing its version when `section` holds several
+fn index_entry(cli: &Cli, documented: &DocumentedCrate, section: &[DocumentedCrate]) -> String {
+    let index = crate_index_path(cli, &documented.dir);
+    if has_sibling_versions(documented, section) {
+        format!(
+            "- [`{}` {}]({})\n",
+            documented.name, documented.version, index
+        )
+    } else {
+        format!("- [`{}`]({})\n", documented.name, index)
+    }
+}
+
+/// Whether another version of the same crate appears in `section`
+fn has_sibling_versions(documented: &DocumentedCrate, section: &[DocumentedCrate]) -> bool {
+    section
+        .iter()
+        .any(|other| other.name == documented.name && other.version != documented.version)
+}
+
+/// Write `manifest.json` at the output root describing every documented crate
+fn generate_manifest(
+    cli: &Cli,
+    current_crate: Option<&DocumentedCrate>,
+    workspace_members: &[DocumentedCrate],
+    dependencies: &[DocumentedCrate],
+) -> Result<()> {
+    use std::fs;
+
+    // Nothing is written to disk in stdout mode
+    if cli.stdout {
+        return Ok(());
+    }
+
+    let entries = (current_crate.into_iter().map(|c| ("current", c)))
+        .chain(workspace_members.iter().map(|c| ("workspace", c)))
+        .chain(dependencies.iter().map(|c| ("dependency", c)));
+
+    let mut crates = Vec::new();
+    for (section, documented) in entries {
+        let crate_dir = &documented.dir;
+        let entry_path = crate_manifest_path(cli, crate_dir);
+        let recorded: serde_json::Value = match fs::read_to_string(&entry_path) {
+            Ok(content) => serde_json::from_str(&content).with_context(|| {
+                format!("Failed to parse crate manifest: {}", entry_path.display())
+            })?,
+            Err(_) => json!({}),
+        };
+
+        crates.push(json!({
+            "name": documented.name,
+            "version": recorded["version"],
+            "index": crate_index_path(cli, crate_dir),
+            "section": section,
+            "modules": recorded.get("modules").cloned().unwrap_or_else(|| json!([])),
+        }));
+    }
+
+    let manifest = json!({
+        "tool_version": env!("CARGO_PKG_VERSION"),
+        "crates": crates,
+    });
+
+    let manifest_path = cli.output.join("manifest.json");
+    fs::write(&manifest_path, serde_json::to_string_pretty(&manifest)?)
+        .with_context(|| format!("Failed to write manifest: {}", manifest_path.display()))?;
+
+    Ok(())
+}
+
+/// Print a table of documented item counts per crate, plus a total row
+///
+/// The counts are the ones recorded in each crate's manifest while its markdown was
+/// generated, so crates skipped as unchanged are counted too.
+fn print_stats(
+    cli: &Cli,
+    current_crate: Option<&DocumentedCrate>,
+    workspace_members: &[DocumentedCrate],
+    dependencies: &[DocumentedCrate],
+) {
+    const COLUMNS: [(&str, &str); 7] = [
+        ("struct", "Structs"),
+        ("enum", "Enums"),
+        ("trait", "Traits"),
+        ("function", "Functions"),
+        ("constant", "Constants"),
+        ("type_alias", "Type aliases"),
+        ("module", "Modules"),
+    ];
+
+    let crates: Vec<&DocumentedCrate> = current_crate
+        .into_iter()
+        .chain(workspace_members)
+        .chain(dependencies)
+        .collect();
+    let mut rows: Vec<(String, [u64; 7])> = Vec::new();
+    for documented in &crates {
+        let manifest: serde_json::Value =
+            std::fs::read_to_string(crate_manifest_path(cli, &documented.dir))
+                .ok()
+                .and_then(|content| serde_json::from_str(&content).ok())
+                .unwrap_or_else(|| json!({}));
+        let mut counts = [0; 7];
+        for module in manifest["modules"].as_array().into_iter().flatten() {
+            for (count, (kind, _)) in counts.iter_mut().zip(COLUMNS) {
+                *count += module["items"][kind].as_u64().unwrap_or(0);
+            }
+        }
+        rows.push((documented.dir.clone(), counts));
+    }
+    let mut totals = [0; 7];
+    for (_, counts) in &rows {
+        for (total, count) in totals.iter_mut().zip(counts) {
+            *total += count;
+        }
+    }
+    let crate_word = if crates.len() == 1 { "crate" } else { "crates" };
+    rows.push((format!("Total ({} {})", crates.len(), crate_word), totals));
+
+    let name_width = rows
+        .iter()
+        .map(|(name, _)| name.len())
+        .chain(std::iter::once("Crate".len()))
+        .max()
+        .unwrap_or_default();
+    let mut header = format!("{:<name_width$}", "Crate");
+    for (_, title) in COLUMNS {
+        header.push_str(&format!("  {}", title));
+    }
+    let mut table = format!("\n📊 Documented items\n\n{}", header);
+    for (i, (name, counts)) in rows.iter().enumerate() {
+        if i + 1 == rows.len() {
+            table.push_str(&format!("\n{}", "-".repeat(header.len())));
+        }
+        table.push_str(&format!("\n{:<name_width$}", name));
+        for (count, (_, title)) in counts.iter().zip(COLUMNS) {
+            table.push_str(&format!("  {:>width$}", count, width = title.len()));
+        }
+    }
+
+    // Asked for explicitly, so shown even with --quiet
+    if PROGRESS_TO_STDERR.load(Ordering::Relaxed) {
+        eprintln!("{}", table);
+    } else {
+        println!("{}", table);
+    }
+}
+
+/// Write `index.txt` at the output root: the path of every generated markdown file,
+/// relative to the output root, one per line and sorted
+///
+/// Crate files come from the crate manifests; manifests written before they
+/// recorded files fall back to the crate index and module files.
+fn generate_file_list(
+    cli: &Cli,
+    current_crate: Option<&DocumentedCrate>,
+    workspace_members: &[DocumentedCrate],
+    dependencies: &[DocumentedCrate],
+) -> Result<()> {
+    let mut files = BTreeSet::new();
+    if !cli.no_index {
+        files.insert("index.md".to_string());
+    }
+    if cli.mdbook {
+        files.insert("SUMMARY.md".to_string());
+    }
+
+    for documented in current_crate
+        .into_iter()
+        .chain(workspace_members)
+        .chain(dependencies)
+    {
+        let manifest: serde_json::Value =
+            std::fs::read_to_string(crate_manifest_path(cli, &documented.dir))
+                .ok()
+                .and_then(|content| serde_json::from_str(&content).ok())
+                .unwrap_or_else(|| json!({}));
+        files.insert(crate_index_path(cli, &documented.dir));
+        let recorded = match manifest.get("files") {
+            Some(files) => files,
+            None => &manifest["modules"],
+        };
+        files.extend(
+            recorded
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|file| file.as_str().or_else(|| file["path"].as_str()))
+                .map(String::from),
+        );
+    }
+
+    let content: String = files.iter().map(|file| format!("{}\n", file)).collect();
+    let path = cli.output.join("index.txt");
+    std::fs::write(&path, content)
+        .with_context(|| format!("Failed to write file list: {}", path.display()))
+}
+
+/// Write `llms.txt` at the output root, following the llms.txt convention: a title,
+/// a one-line summary, then a link list per crate built from the crate manifests
+fn generate_llms_txt(
+    cli: &Cli,
+    current_crate: Option<&DocumentedCrate>,
+    workspace_members: &[DocumentedCrate],
+    dependencies: &[DocumentedCrate],
+) -> Result<()> {
+    let read_manifest = |documented: &DocumentedCrate| -> serde_json::Value {
+        std::fs::read_to_string(crate_manifest_path(cli, &documented.dir))
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_else(|| json!({}))
+    };
+
+    let mut content = String::new();
+    match current_crate {
+        Some(current) => {
+            content.push_str(&format!("# {}\n\n", current.name));
+            if let Some(summary) = read_manifest(current)["summary"].as_str() {
+                content.push_str(&format!("> {}\n\n", summary));
+            }
+        }
+        None => {
+            content.push_str("# Documentation Index\n\n");
+            content.push_str("> Generated markdown documentation for this project.\n\n");
+        }
+    }
+    content.push_str(
+        "API documentation generated by cargo-doc-md. Links are relative to this file; \
+         each crate's entry point is its index.md.\n\n",
+    );
+
+    let sections = [
+        current_crate.map(std::slice::from_ref).unwrap_or_default(),
+        workspace_members,
+        dependencies,
+    ];
+    for section in sections {
+        for documented in section {
+            let manifest = read_manifest(documented);
+            if has_sibling_versions(documented, section) {
+                content.push_str(&format!(
+                    "## {} {}\n\n",
+                    documented.name, documented.version
+                ));
+            } else {
+                content.push_str(&format!("## {}\n\n", documented.name));
+            }
+            if let Some(summary) = manifest["summary"].as_str() {
+                content.push_str(&format!("{}\n\n", summary));
+            }
 
-            if let Some(dep_ids) = node["dependencies"].as_array() {
-                for dep_id in dep_ids {
-                    if let Some(dep_id_str) = dep_id.as_str() {
-                        // Check if this dependency is in the normal deps list
-                        if let Some(dep_pkg) = packages
-                            .iter()
-                            .find(|p| p["id"].as_str() == Some(dep_id_str))
-                        {
-                            if let Some(dep_name) = dep_pkg["name"].as_str() {
-                                if normal_dep_names.contains(dep_name) {
-                                    filtered_deps.push(dep_id_str.to_string());
-                                }
-                            }
-                        }
+            content.push_str(&format!(
+                "- [{}]({}): Crate overview\n",
+                documented.name,
+                crate_index_path(cli, &documented.dir)
+            ));
+            for entry in manifest["outline"].as_array().into_iter().flatten() {
+                let (Some(path), Some(link)) = (entry["path"].as_str(), entry["link"].as_str())
+                else {
+                    continue;
+                };
+                match entry["summary"].as_str() {
+                    Some(summary) => {
+                        content.push_str(&format!("- [{}]({}): {}\n", path, link, summary))
                     }
+                    None => content.push_str(&format!("- [{}]({})\n", path, link)),
                 }
             }
-
-            normal_dep_graph.insert(node_id.to_string(), filtered_deps);
+            content.push('\n');
         }
     }
 
-    Ok(normal_dep_graph)
-}
+    let llms_path = cli.output.join("llms.txt");
+    std::fs::write(&llms_path, content.trim_end().to_string() + "\n")
+        .with_context(|| format!("Failed to write llms.txt: {}", llms_path.display()))?;
 
-fn get_all_dependencies_recursive(
-    metadata: &serde_json::Value,
-    package_id: &str,
-    workspace_member_ids: &[String],
-) -> Result<HashMap<String, String>> {
-    use std::collections::HashSet;
+    progress!("✓ llms.txt: {}", llms_path.display());
 
-    let packages = metadata["packages"]
-        .as_array()
-        .context("Missing 'packages' in metadata")?;
+    Ok(())
+}
+
+fn generate_mdbook_summary(
+    cli: &Cli,
+    current_crate: Option<&DocumentedCrate>,
+    workspace_members: &[DocumentedCrate],
+    dependencies: &[DocumentedCrate],
+) -> Result<()> {
+    let output_dir = cli.output.as_path();
+    let mut content = String::new();
 
-    let normal_dep_graph = build_normal_dependency_graph(metadata)?;
+    content.push_str("# Summary\n\n");
+    if !cli.no_index {
+        content.push_str("[Documentation Index](index.md)\n\n");
+    }
 
-    let mut all_deps = HashMap::new();
-    let mut visited = HashSet::new();
-    let mut to_visit = vec![package_id.to_string()];
+    let current_crate: Vec<DocumentedCrate> = current_crate.into_iter().cloned().collect();
+    let sections = [
+        ("Current Crate", summary_titles(&current_crate)),
+        ("Workspace Members", summary_titles(workspace_members)),
+        ("Dependencies", summary_titles(dependencies)),
+    ];
 
-    while let Some(current_id) = to_visit.pop() {
-        if visited.contains(&current_id) {
+    for (title, crates) in sections {
+        if crates.is_empty() {
             continue;
         }
-        visited.insert(current_id.clone());
-
-        if let Some(dep_ids) = normal_dep_graph.get(&current_id) {
-            for dep_id in dep_ids {
-                // Skip workspace members
-                if workspace_member_ids.contains(dep_id) {
-                    continue;
-                }
-
-                // Add to visit queue for recursive traversal
-                if !visited.contains(dep_id) {
-                    to_visit.push(dep_id.clone());
-                }
 
-                // Add to result if not already there
-                if let Some(pkg) = packages
-                    .iter()
-                    .find(|p| p["id"].as_str() == Some(dep_id.as_str()))
-                {
-                    if let (Some(name), Some(version)) =
-                        (pkg["name"].as_str(), pkg["version"].as_str())
-                    {
-                        // HashMap automatically deduplicates by name (matching cargo doc behavior)
-                        all_deps.insert(name.to_string(), version.to_string());
-                    }
-                }
+        content.push_str(&format!("# {}\n\n", title));
+        for (crate_title, crate_dir) in &crates {
+            if cli.single_file {
+                content.push_str(&format!("- [{}]({}.md)\n", crate_title, crate_dir));
+            } else {
+                content.push_str(&summary_entries_for_crate(
+                    output_dir,
+                    crate_title,
+                    crate_dir,
+                )?);
             }
         }
+        content.push('\n');
     }
 
-    Ok(all_deps)
-}
-
-fn get_all_dependencies(metadata: &serde_json::Value) -> Result<Vec<Dependency>> {
-    let resolve = &metadata["resolve"];
-    let Some(root) = resolve["root"].as_str() else {
-        bail!(
-            "Cannot document dependencies from a virtual workspace root.\n\
-             Virtual workspaces have no root package.\n\
-             Use: cargo doc-md --workspace  (to document all workspace members)\n\
-             Or:  cargo doc-md -p <package>  (to document a specific package)"
-        );
-    };
-
-    let workspace_member_ids: Vec<String> = metadata["workspace_members"]
-        .as_array()
-        .map(|members| {
-            members
-                .iter()
-                .filter_map(|v| v.as_str().map(String::from))
-                .collect()
-        })
-        .unwrap_or_default();
+    let summary_path = output_dir.join("SUMMARY.md");
+    std::fs::write(&summary_path, content)
+        .with_context(|| format!("Failed to write SUMMARY.md: {}", summary_path.display()))?;
 
-    let deps_map = get_all_dependencies_recursive(metadata, root, &workspace_member_ids)?;
+    progress!("✓ mdBook summary: {}", summary_path.display());
 
-    let mut deps: Vec<Dependency> = deps_map
-        .into_iter()
-        .map(|(name, version)| Dependency { name, version })
-        .collect();
+    Ok(())
+}
 
-    deps.sort_by(|a, b| a.name.cmp(&b.name));
-    Ok(deps)
+/// `(chapter title, directory)` pairs for a SUMMARY.md section
+fn summary_titles(crates: &[DocumentedCrate]) -> Vec<(String, String)> {
+    crates
+        .iter()
+        .map(|c| {
+            let title = if has_sibling_versions(c, crates) {
+                format!("{} {}", c.name, c.version)
+            } else {
+                c.name.clone()
+            };
+            (title, c.dir.clone())
+        })
+        .collect()
 }
 
-fn get_workspace_members(metadata: &serde_json::Value) -> Result<Vec<Dependency>> {
-    let workspace_members = metadata["workspace_members"]
-        .as_array()
-        .context("Missing 'workspace_members' in metadata")?;
+/// Build the nested SUMMARY.md entries for one crate from the files written to disk
+fn summary_entries_for_crate(
+    output_dir: &Path,
+    crate_title: &str,
+    crate_dir_name: &str,
+) -> Result<String> {
+    let crate_dir = output_dir.join(crate_dir_name);
 
-    if workspace_members.is_empty() {
-        bail!(
-            "Not in a workspace or workspace has no members.\n\
-             The --workspace flag requires a Cargo workspace.\n\
-             For single-crate projects, use: cargo doc-md (without --workspace)"
-        );
-    }
+    let mut entries = format!("- [{}]({}/index.md)\n", crate_title, crate_dir_name);
 
-    let packages = metadata["packages"]
-        .as_array()
-        .context("Missing 'packages' in metadata")?;
+    let root_module_file = format!(
+        "{}.md",
+        crate_dir_name.rsplit('/').next().unwrap_or(crate_dir_name)
+    );
+    if crate_dir.join(&root_module_file).is_file() {
+        entries.push_str(&format!(
+            "  - [Crate Root]({}/{})\n",
+            crate_dir_name, root_module_file
+        ));
+    }
 
-    let mut members = Vec::new();
-    for member_id in workspace_members {
-        let member_id_str = member_id.as_str().context("Invalid workspace member ID")?;
+    // Module paths as components, sorted so parents precede their children
+    let mut module_paths: Vec<Vec<String>> = collect_markdown_files(&crate_dir)?
+        .into_iter()
+        .filter(|path| path != Path::new("index.md") && path != Path::new(&root_module_file))
+        .map(|path| {
+            path.with_extension("")
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy().into_owned())
+                .collect()
+        })
+        .collect();
+    module_paths.sort();
+
+    let mut emitted: Vec<Vec<String>> = Vec::new();
+    for module_path in &module_paths {
+        // Parents without a file of their own become mdBook draft chapters
+        for depth in 1..=module_path.len() {
+            let prefix = &module_path[..depth];
+            if emitted.iter().any(|p| p == prefix) {
+                continue;
+            }
 
-        if let Some(pkg) = packages
-            .iter()
-            .find(|p| p["id"].as_str() == Some(member_id_str))
-        {
-            if let (Some(name), Some(version)) = (pkg["name"].as_str(), pkg["version"].as_str()) {
-                members.push(Dependency {
-                    name: name.to_string(),
-                    version: version.to_string(),
-                });
+            let indent = "  ".repeat(depth);
+            let name = &prefix[depth - 1];
+            if depth == module_path.len() {
+                entries.push_str(&format!(
+                    "{}- [{}]({}/{}.md)\n",
+                    indent,
+                    name,
+                    crate_dir_name,
+                    prefix.join("/")
+                ));
+            } else {
+                entries.push_str(&format!("{}- [{}]()\n", indent, name));
             }
+            emitted.push(prefix.to_vec());
         }
     }
 
-    members.sort_by(|a, b| a.name.cmp(&b.name));
-    Ok(members)
+    Ok(entries)
 }
 
-/// Returns Ok(true) if documented, Ok(false) if skipped (e.g., binary-only crate), Err on failure
-fn document_single_dependency(
-    dep: &Dependency,
-    output_base: &Path,
-    target_dir: &Path,
-    metadata: &serde_json::Value,
-    include_private: bool,
-) -> Result<bool> {
-    // Build the package specification
-    // If we have a version, use name@version to disambiguate multiple versions
-    let package_spec = if dep.version.is_empty() {
-        dep.name.clone()
-    } else {
-        format!("{}@{}", dep.name, dep.version)
+/// Print the markdown files added, removed, or modified in `new_dir` relative to
+/// `old_dir`, followed by a unified diff of each modified file
+///
+/// The "Generated with cargo-doc-md" footer is ignored. Returns whether anything changed.
+fn print_diff(old_dir: &Path, new_dir: &Path) -> Result<bool> {
+    let old_files: BTreeSet<PathBuf> = collect_markdown_files(old_dir)?.into_iter().collect();
+    let new_files: BTreeSet<PathBuf> = collect_markdown_files(new_dir)?.into_iter().collect();
+
+    let read = |dir: &Path, file: &Path| -> Result<String> {
+        let path = dir.join(file);
+        let content = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read file: {}", path.display()))?;
+        Ok(content
+            .split_inclusive('\n')
+            .filter(|line| !line.starts_with("Generated with [cargo-doc-md]"))
+            .collect())
     };
 
-    // Generate rustdoc JSON for the dependency
-    let mut args = vec![
-        "+nightly",
-        "rustdoc",
-        "-p",
-        &package_spec,
-        "--lib",
-        "--",
-        "--output-format=json",
-        "-Z",
-        "unstable-options",
-    ];
+    let added: Vec<&PathBuf> = new_files.difference(&old_files).collect();
+    let removed: Vec<&PathBuf> = old_files.difference(&new_files).collect();
+    let mut modified = Vec::new();
+    for file in old_files.intersection(&new_files) {
+        let (old, new) = (read(old_dir, file)?, read(new_dir, file)?);
+        if old != new {
+            modified.push((file, old, new));
+        }
+    }
 
-    if include_private {
-        args.push("--document-private-items");
+    if added.is_empty() && removed.is_empty() && modified.is_empty() {
+        println!("No documentation changes");
+        return Ok(false);
     }
 
-    let output = Command::new("cargo")
-        .args(&args)
-        .output()
-        .context("Failed to run cargo rustdoc")?;
+    println!(
+        "Documentation changes: {} added, {} removed, {} modified\n",
+        added.len(),
+        removed.len(),
+        modified.len()
+    );
+    for file in &added {
+        println!("  added:    {}", file.display());
+    }
+    for file in &removed {
+        println!("  removed:  {}", file.display());
+    }
+    for (file, _, _) in &modified {
+        println!("  modified: {}", file.display());
+    }
 
-    if !output.status.success() {
-        let stderr = String::from_utf8_lossy(&output.stderr);
-        // Check for known non-error cases
-        if stderr.contains("no library targets found") {
-            println!("  ⚠ No library target found (binary-only crate), skipping documentation");
-            return Ok(false); // Skipped, not an error
-        }
-        // Show first few error lines
-        let error_lines: Vec<&str> = stderr
-            .lines()
-            .filter(|line| line.contains("error") || line.contains("failed"))
-            .take(2)
-            .collect();
-        if !error_lines.is_empty() {
-            bail!(
-                "Failed to build '{}':\n{}\n\nRun 'cargo build -p {}' for full details",
-                dep.name,
-                error_lines.join("\n"),
-                package_spec
-            );
-        }
-        bail!(
-            "Failed to build '{}' (exit code: {})\nRun 'cargo build -p {}' for details",
-            dep.name,
-            output.status,
-            package_spec
+    for (file, old, new) in &modified {
+        let file = file.display().to_string();
+        println!();
+        print!(
+            "{}",
+            similar::TextDiff::from_lines(old, new)
+                .unified_diff()
+                .header(&format!("a/{}", file), &format!("b/{}", file))
         );
     }
 
-    // Find the package in metadata to get lib target name
-    let packages = metadata["packages"]
-        .as_array()
-        .context("Missing 'packages' in metadata")?;
+    Ok(true)
+}
 
-    let package = packages.iter().find(|p| {
-        if let (Some(pkg_name), Some(pkg_version)) = (p["name"].as_str(), p["version"].as_str()) {
-            pkg_name == dep.name && (dep.version.is_empty() || pkg_version == dep.version)
-        } else {
-            false
+/// Check the relative links in every markdown file under `output_dir`, printing the
+/// broken ones to stderr
+///
+/// A link is broken when its file does not exist or, for a markdown file with a
+/// `#fragment`, no heading there has that anchor. Links with a scheme, like `https:`
+/// and `mailto:`, are skipped, except those under `base_url`, which are resolved
+/// against `output_dir`. Returns whether any link was broken.
+fn report_broken_links(output_dir: &Path, base_url: Option<&str>) -> Result<bool> {
+    let read = |path: &Path| {
+        std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read file: {}", path.display()))
+    };
+    let mut anchors: HashMap<PathBuf, HashSet<String>> = HashMap::new();
+    let mut checked = 0;
+    let mut broken = 0;
+
+    for file in collect_markdown_files(output_dir)? {
+        let source = output_dir.join(&file);
+        let content = read(&source)?;
+
+        for (line, dest) in markdown_links(&content) {
+            let under_base = base_url
+                .map(|base_url| base_url.trim_end_matches('/'))
+                .and_then(|base_url| dest.strip_prefix(base_url))
+                .and_then(|rest| rest.strip_prefix('/'));
+            if under_base.is_none() && dest.contains(':') && !dest.starts_with(['.', '/', '#']) {
+                continue;
+            }
+            checked += 1;
+
+            let (path, fragment) = match dest.split_once('#') {
+                Some((path, fragment)) => (path, Some(fragment)),
+                None => (dest.as_str(), None),
+            };
+            let target = if let Some(path) = under_base {
+                output_dir.join(path.split_once('#').map_or(path, |(path, _)| path))
+            } else if path.is_empty() {
+                source.clone()
+            } else {
+                source.parent().unwrap_or(output_dir).join(path)
+            };
+
+            let problem = if !target.is_file() {
+                Some("file not found".to_string())
+            } else if let Some(fragment) =
+                fragment.filter(|_| target.extension().is_some_and(|ext| ext == "md"))
+            {
+                let key = std::fs::canonicalize(&target).unwrap_or_else(|_| target.clone());
+                let known = match anchors.entry(key) {
+                    std::collections::hash_map::Entry::Occupied(entry) => entry.into_mut(),
+                    std::collections::hash_map::Entry::Vacant(entry) => {
+                        entry.insert(heading_anchors(&read(&target)?))
+                    }
+                };
+                (!known.contains(fragment)).then(|| format!("no heading with anchor #{}", fragment))
+            } else {
+                None
+            };
+
+            if let Some(problem) = problem {
+                eprintln!(
+                    "{}:{}: broken link to {}: {}",
+                    source.display(),
+                    line,
+                    dest,
+                    problem
+                );
+                broken += 1;
+            }
         }
-    });
-
-    // Get the library target name (may differ from package name)
-    let lib_target_name = package
-        .and_then(get_lib_target_name)
-        .unwrap_or_else(|| dep.name.replace("-", "_"));
-
-    // Find the generated JSON file
-    let json_path = target_dir
-        .join("doc")
-        .join(format!("{}.json", lib_target_name));
-
-    if !json_path.exists() {
-        bail!("Generated JSON file not found at {}", json_path.display());
     }
 
-    // Remove existing crate directory to ensure clean documentation
-    let crate_output_dir = output_base.join(&lib_target_name);
-    if crate_output_dir.exists() {
-        std::fs::remove_dir_all(&crate_output_dir).with_context(|| {
-            format!(
-                "Failed to remove existing crate directory: {}",
-                crate_output_dir.display()
-            )
-        })?;
+    if broken > 0 {
+        eprintln!("\n❌ {} of {} links are broken", broken, checked);
+    } else {
+        progress!("✓ All {} links resolve", checked);
     }
-
-    // Convert to markdown directly in output directory
-    // The converter will create a subdirectory with the crate name
-    let options = ConversionOptions {
-        input_path: &json_path,
-        output_dir: output_base,
-        include_private,
-    };
-
-    cargo_doc_md::convert_json_file(&options)?;
-
-    Ok(true) // Successfully documented
+    Ok(broken > 0)
 }
 
-fn generate_master_index(
-    output_dir: &Path,
-    current_crate: Option<&str>,
-    workspace_members: &[String],
-    dependencies: &[String],
-) -> Result<()> {
-    use std::fs;
+/// Recursively list `.md` files under `dir`, relative to `dir`
+fn collect_markdown_files(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut to_visit = vec![dir.to_path_buf()];
+
+    while let Some(current) = to_visit.pop() {
+        let entries = std::fs::read_dir(&current)
+            .with_context(|| format!("Failed to read directory: {}", current.display()))?;
+
+        for entry in entries {
+            let path = entry?.path();
+            if path.is_dir() {
+                to_visit.push(path);
+            } else if path.extension().is_some_and(|ext| ext == "md") {
+                if let Ok(relative) = path.strip_prefix(dir) {
+                    files.push(relative.to_path_buf());
+                }
+            }
+        }
+    }
 
-    let mut content = String::new();
+    files.sort();
+    Ok(files)
+}
 
-    content.push_str("# Documentation Index\n\n");
-    content.push_str("Generated markdown documentation for this project.\n\n");
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    // Current crate section
-    if let Some(crate_name) = current_crate {
-        content.push_str("## Current Crate\n\n");
-        content.push_str(&format!(
-            "- [`{}`]({}/index.md)\n\n",
-            crate_name,
-            crate_name.replace("-", "_")
-        ));
+    fn parse_one(line: &str) -> ConfigValue {
+        let mut entries = parse_config(line).unwrap();
+        assert_eq!(entries.len(), 1);
+        entries.remove(0).2
     }
 
-    // Workspace members section
-    if !workspace_members.is_empty() {
-        content.push_str(&format!(
-            "## Workspace Members ({})\n\n",
-            workspace_members.len()
-        ));
+    fn parse_error(content: &str) -> String {
+        format!("{:#}", parse_config(content).err().unwrap())
+    }
 
-        for member in workspace_members {
-            let member_path = format!("{}/index.md", member.replace("-", "_"));
-            content.push_str(&format!("- [`{}`]({})\n", member, member_path));
-        }
-        content.push('\n');
+    fn scalar(value: &str) -> ConfigValue {
+        ConfigValue::Scalar(value.to_string())
     }
 
-    // Dependencies section
-    if !dependencies.is_empty() {
-        content.push_str(&format!("## Dependencies ({})\n\n", dependencies.len()));
+    #[test]
+    fn test_hash_in_strings_is_not_a_comment() {
+        assert_eq!(
+            parse_one(r#"src-base = "https://example.com/#L" # comment"#),
+            scalar("https://example.com/#L")
+        );
+        assert_eq!(parse_one("title = 'a # b' # comment"), scalar("a # b"));
+        assert_eq!(strip_toml_comment(r#"x = "a\\" # c"#), r#"x = "a\\" "#);
+    }
 
-        for dep in dependencies {
-            let dep_path = format!("{}/index.md", dep.replace("-", "_"));
-            content.push_str(&format!("- [`{}`]({})\n", dep, dep_path));
-        }
-        content.push('\n');
+    #[test]
+    fn test_escaped_quotes() {
+        assert_eq!(
+            parse_one(r#"title = "say \"hi\" # still the string" # comment"#),
+            scalar(r#"say "hi" # still the string"#)
+        );
+        assert_eq!(parse_one(r#"title = "tab\tend\\""#), scalar("tab\tend\\"));
     }
 
-    content.push_str("---\n\n");
-    content
-        .push_str("Generated with [cargo-doc-md](https://github.com/Crazytieguy/cargo-doc-md)\n");
+    #[test]
+    fn test_literal_strings_have_no_escapes() {
+        assert_eq!(
+            parse_one(r"json = 'C:\docs\n.json'"),
+            scalar(r"C:\docs\n.json")
+        );
+        assert_eq!(
+            parse_one(r#"title = 'has "quotes"'"#),
+            scalar(r#"has "quotes""#)
+        );
+    }
 
-    // Ensure output directory exists
-    fs::create_dir_all(output_dir).with_context(|| {
-        format!(
-            "Failed to create output directory: {}",
-            output_dir.display()
+    #[test]
+    fn test_multi_line_arrays_with_comments() {
+        let entries = parse_config(
+            "exclude = [ # what to leave out\n    \"a::*\", # first\n    'b#c', 3,\n    # just a comment\n]\nsingle-file = true\n",
         )
-    })?;
-
-    let index_path = output_dir.join("index.md");
-    fs::write(&index_path, content)
-        .with_context(|| format!("Failed to write master index: {}", index_path.display()))?;
+        .unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].0, 1);
+        assert_eq!(
+            entries[0].2,
+            ConfigValue::Array(vec!["a::*".to_string(), "b#c".to_string(), "3".to_string()])
+        );
+        assert_eq!(
+            entries[1],
+            (6, "single-file".to_string(), ConfigValue::Bool(true))
+        );
 
-    println!("\n✓ Master index: {}", index_path.display());
+        assert!(
+            parse_error("exclude = [\n    \"a\",\n")
+                .contains("array for `exclude` is never closed")
+        );
+        assert!(
+            parse_error("exclude = [\"a\" \"b\"]").contains("expected `,` or `]` in the array")
+        );
+    }
 
-    Ok(())
+    #[test]
+    fn test_rejected_escapes_and_tables() {
+        assert!(parse_error(r#"title = "\u0041""#).contains("unsupported escape `\\u`"));
+        assert!(parse_error(r#"title = "unterminated"#).contains("unterminated string"));
+        assert!(
+            parse_error("output = 'docs'\n[output]\n").contains("line 2: tables aren't supported")
+        );
+        assert!(parse_error("[[crates]]\n").contains("line 1: tables aren't supported"));
+        assert!(parse_error("output.dir = 'docs'").contains("invalid key `output.dir`"));
+        assert!(parse_error("depth = 1.5").contains("unexpected `.5` after the value"));
+    }
 }
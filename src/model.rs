@@ -0,0 +1,218 @@
+//! A parsed crate, for traversing its modules and items without rendering markdown.
+
+use crate::converter::{self, MarkdownOptions, MarkdownOutput};
+use anyhow::Result;
+use rustdoc_types::{Crate, Id, Item, ItemEnum};
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+/// A crate's documentation model: the crate's modules and the items in each, with
+/// full paths resolved.
+///
+/// Items are the ones the markdown documents, before `include_private` and `exclude`
+/// are applied: private items are included when the rustdoc JSON has them, and can
+/// be told apart with [`DocItem::is_public`]. Build one with
+/// [`parse_json_file`](crate::parse_json_file) or
+/// [`parse_json_str`](crate::parse_json_str), then [`render`](DocModel::render) it
+/// to markdown or walk it yourself.
+pub struct DocModel {
+    crate_data: Crate,
+    item_paths: HashMap<Id, Vec<String>>,
+    /// Module paths and their items' IDs, in the order of
+    /// [`MarkdownOutput::files_in_order`]; grouped on first use, since rendering
+    /// groups items itself
+    modules: OnceLock<Vec<(String, Vec<Id>)>>,
+}
+
+impl DocModel {
+    /// Build the model of a crate already loaded from rustdoc JSON.
+    pub fn new(crate_data: Crate) -> Self {
+        DocModel {
+            item_paths: converter::build_path_map(&crate_data),
+            crate_data,
+            modules: OnceLock::new(),
+        }
+    }
+
+    fn grouped_modules(&self) -> &[(String, Vec<Id>)] {
+        self.modules.get_or_init(|| {
+            let options = MarkdownOptions {
+                include_private: true,
+                ..Default::default()
+            };
+            let crate_name = self.name();
+            let mut modules: Vec<(String, Vec<Id>)> =
+                converter::group_by_module(&self.crate_data, &self.item_paths, &options)
+                    .into_iter()
+                    .map(|(path, items)| (path, items.into_iter().map(|(id, _)| id).collect()))
+                    .collect();
+            modules.sort_by_cached_key(|(path, _)| {
+                let file = converter::module_file_path(path, crate_name);
+                let (rank, components) = converter::file_order_key(&file, crate_name);
+                let components: Vec<String> = components.into_iter().map(String::from).collect();
+                (rank, components)
+            });
+            modules
+        })
+    }
+
+    /// The rustdoc JSON the model was built from.
+    pub fn crate_data(&self) -> &Crate {
+        &self.crate_data
+    }
+
+    fn root(&self) -> Option<&Item> {
+        self.crate_data.index.get(&self.crate_data.root)
+    }
+
+    /// The crate's name.
+    pub fn name(&self) -> &str {
+        self.root()
+            .and_then(|item| item.name.as_deref())
+            .unwrap_or("unknown")
+    }
+
+    /// The crate's version, if rustdoc recorded one.
+    pub fn version(&self) -> Option<&str> {
+        self.crate_data.crate_version.as_deref()
+    }
+
+    /// The crate root's docs, as written.
+    pub fn docs(&self) -> Option<&str> {
+        self.root().and_then(|item| item.docs.as_deref())
+    }
+
+    /// Every module that holds items: the crate root first, then the others depth-first.
+    pub fn modules(&self) -> impl Iterator<Item = Module<'_>> {
+        self.grouped_modules().iter().map(|(path, ids)| Module {
+            model: self,
+            path,
+            ids,
+        })
+    }
+
+    /// The module with the full path `path`, e.g. `my_crate::io`.
+    pub fn module(&self, path: &str) -> Option<Module<'_>> {
+        self.modules().find(|module| module.path == path)
+    }
+
+    /// Every item of every module, in module order.
+    pub fn items(&self) -> impl Iterator<Item = DocItem<'_>> {
+        self.modules().flat_map(|module| module.items())
+    }
+
+    /// Render the model to markdown, as [`converter::convert_to_markdown_multifile`] does.
+    pub fn render(&self, options: &MarkdownOptions) -> Result<MarkdownOutput> {
+        converter::convert_to_markdown_multifile(&self.crate_data, options)
+    }
+
+    fn item(&self, id: &Id) -> Option<DocItem<'_>> {
+        Some(DocItem {
+            id: *id,
+            item: self.crate_data.index.get(id)?,
+            path: self.item_paths.get(id)?,
+        })
+    }
+}
+
+/// A module of a [`DocModel`] with the items directly inside it.
+#[derive(Clone, Copy)]
+pub struct Module<'a> {
+    model: &'a DocModel,
+    path: &'a str,
+    ids: &'a [Id],
+}
+
+impl<'a> Module<'a> {
+    /// Full path, e.g. `my_crate::io`; the crate root's is the crate name.
+    pub fn path(&self) -> &'a str {
+        self.path
+    }
+
+    /// The module's own item, with its docs, if it is in the rustdoc JSON.
+    pub fn item(&self) -> Option<DocItem<'a>> {
+        if self.path == self.model.name() {
+            return self.model.item(&self.model.crate_data.root);
+        }
+        self.model
+            .item_paths
+            .iter()
+            .find(|(id, path)| {
+                path.join("::") == self.path
+                    && self
+                        .model
+                        .crate_data
+                        .index
+                        .get(id)
+                        .is_some_and(|item| matches!(item.inner, ItemEnum::Module(_)))
+            })
+            .and_then(|(id, _)| self.model.item(id))
+    }
+
+    /// Items directly inside the module, sorted by name. Submodules are items too.
+    pub fn items(&self) -> impl Iterator<Item = DocItem<'a>> + use<'a> {
+        let model = self.model;
+        self.ids.iter().filter_map(move |id| model.item(id))
+    }
+}
+
+/// One documented item of a [`DocModel`].
+#[derive(Clone, Copy)]
+pub struct DocItem<'a> {
+    id: Id,
+    item: &'a Item,
+    path: &'a [String],
+}
+
+impl<'a> DocItem<'a> {
+    /// The item's ID in the rustdoc JSON.
+    pub fn id(&self) -> Id {
+        self.id
+    }
+
+    /// The rustdoc JSON item, for details the model doesn't cover.
+    pub fn item(&self) -> &'a Item {
+        self.item
+    }
+
+    /// What kind of item this is, e.g. `struct` or `type_alias`, as in `manifest.json`.
+    pub fn kind(&self) -> &'static str {
+        converter::item_kind_name(self.item).unwrap_or("item")
+    }
+
+    /// The item's name.
+    pub fn name(&self) -> &'a str {
+        self.path.last().map_or("", String::as_str)
+    }
+
+    /// Full path, e.g. `my_crate::io::Reader`.
+    pub fn path(&self) -> String {
+        self.path.join("::")
+    }
+
+    /// Whether the item is declared `pub`.
+    pub fn is_public(&self) -> bool {
+        converter::is_public(self.item)
+    }
+
+    /// Whether the item is an `async fn`.
+    pub fn is_async(&self) -> bool {
+        matches!(&self.item.inner, ItemEnum::Function(f) if f.header.is_async)
+    }
+
+    /// The item's docs, as written.
+    pub fn docs(&self) -> Option<&'a str> {
+        self.item.docs.as_deref()
+    }
+
+    /// The first sentence of the item's docs, on one line.
+    pub fn summary(&self) -> Option<String> {
+        self.docs().and_then(converter::first_sentence)
+    }
+
+    /// One-line signature, e.g. `fn read(&mut self, buf: &mut [u8]) -> usize`, for
+    /// kinds that have one.
+    pub fn signature(&self) -> Option<String> {
+        converter::item_signature(self.name(), self.item)
+    }
+}
@@ -1,18 +1,29 @@
 //! JSON parser for rustdoc output.
 
 use anyhow::{Context, Result};
+use flate2::read::MultiGzDecoder;
 use rustdoc_types::Crate;
-use std::path::Path;
+use std::io::Read;
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
 
-/// Load and parse a rustdoc JSON file.
+/// Rustdoc JSON format versions that can be read.
+///
+/// `rustdoc-types` 0.56 describes format 56, but the nightly the test fixture was
+/// generated with writes format 57, which still deserializes into those types.
+/// That is the only reason 57 is accepted; later formats are rejected until the
+/// dependency is updated.
+pub const SUPPORTED_FORMAT_VERSIONS: RangeInclusive<u32> =
+    rustdoc_types::FORMAT_VERSION..=rustdoc_types::FORMAT_VERSION + 1;
+
+/// Load and parse a rustdoc JSON file, which may be gzip-compressed.
 pub fn load_rustdoc_json(path: &Path) -> Result<Crate> {
-    let contents = std::fs::read_to_string(path)
-        .with_context(|| format!("Failed to read file: {}", path.display()))?;
+    let contents = read_json_file(path)?;
 
-    let crate_data: Crate = serde_json::from_str(&contents)
+    let crate_data = parse_rustdoc_json(&contents)
         .with_context(|| format!("Failed to parse JSON from: {}", path.display()))?;
 
-    println!(
+    eprintln!(
         "Loaded crate: {} (format version: {})",
         crate_data
             .index
@@ -24,3 +35,85 @@ pub fn load_rustdoc_json(path: &Path) -> Result<Crate> {
 
     Ok(crate_data)
 }
+
+/// Parse rustdoc JSON that is already in memory.
+///
+/// JSON in a format version outside [`SUPPORTED_FORMAT_VERSIONS`] is an error naming
+/// the version, rather than whichever field first failed to parse.
+pub fn parse_rustdoc_json(json: &str) -> Result<Crate> {
+    match serde_json::from_str::<Crate>(json) {
+        Ok(crate_data) => {
+            check_format_version(crate_data.format_version)?;
+            Ok(crate_data)
+        }
+        Err(err) => {
+            let format_version = serde_json::from_str::<serde_json::Value>(json)
+                .ok()
+                .and_then(|value| value.get("format_version")?.as_u64());
+            if let Some(format_version) = format_version {
+                check_format_version(u32::try_from(format_version).unwrap_or(u32::MAX))?;
+            }
+            Err(err).context("Invalid rustdoc JSON")
+        }
+    }
+}
+
+fn check_format_version(format_version: u32) -> Result<()> {
+    if !SUPPORTED_FORMAT_VERSIONS.contains(&format_version) {
+        anyhow::bail!(
+            "rustdoc JSON format version {} is not supported (expected {} to {}); \
+             update cargo-doc-md or your nightly toolchain",
+            format_version,
+            SUPPORTED_FORMAT_VERSIONS.start(),
+            SUPPORTED_FORMAT_VERSIONS.end()
+        );
+    }
+    Ok(())
+}
+
+/// Read a JSON file as text, decompressing it first if it is gzipped.
+///
+/// Compression is detected from the gzip magic bytes, so `.json.gz` files work
+/// whatever their name.
+pub fn read_json_file(path: &Path) -> Result<String> {
+    let bytes =
+        std::fs::read(path).with_context(|| format!("Failed to read file: {}", path.display()))?;
+
+    if !bytes.starts_with(&[0x1f, 0x8b]) {
+        return String::from_utf8(bytes)
+            .with_context(|| format!("File is not valid UTF-8: {}", path.display()));
+    }
+
+    let mut contents = String::new();
+    MultiGzDecoder::new(&bytes[..])
+        .read_to_string(&mut contents)
+        .with_context(|| format!("Failed to decompress file: {}", path.display()))?;
+    Ok(contents)
+}
+
+/// The rustdoc JSON files (`*.json` or `*.json.gz`) directly in `dir`, sorted by name.
+///
+/// Fails if `dir` can't be read or has none.
+pub fn json_files_in(dir: &Path) -> Result<Vec<PathBuf>> {
+    let entries = std::fs::read_dir(dir)
+        .with_context(|| format!("Failed to read directory: {}", dir.display()))?;
+    let mut json_paths = Vec::new();
+    for entry in entries {
+        let path = entry
+            .with_context(|| format!("Failed to read directory: {}", dir.display()))?
+            .path();
+        let is_json = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .is_some_and(|name| name.ends_with(".json") || name.ends_with(".json.gz"));
+        if is_json && path.is_file() {
+            json_paths.push(path);
+        }
+    }
+    json_paths.sort();
+
+    if json_paths.is_empty() {
+        anyhow::bail!("No rustdoc JSON files found in {}", dir.display());
+    }
+    Ok(json_paths)
+}
@@ -3,7 +3,7 @@
 use crate::converter::MarkdownOutput;
 use anyhow::{Context, Result};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 /// Write markdown content to a file in the specified directory.
 pub fn write_markdown(output_dir: &Path, content: &str) -> Result<()> {
@@ -46,3 +46,123 @@ pub fn write_markdown_multifile(output_dir: &Path, output: &MarkdownOutput) -> R
 
     Ok(())
 }
+
+/// Write `(relative path, content)` pairs under the specified directory.
+pub fn write_files(output_dir: &Path, files: &[(PathBuf, String)]) -> Result<()> {
+    for (file_path, content) in files {
+        write_file(output_dir, file_path, content)?;
+    }
+
+    Ok(())
+}
+
+/// Write one file under the specified directory, creating parent directories as needed.
+pub fn write_file(output_dir: &Path, file_path: &Path, content: &str) -> Result<()> {
+    let full_path = output_dir.join(file_path);
+
+    // Create parent directories if needed
+    if let Some(parent) = full_path.parent() {
+        fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create directory: {}", parent.display()))?;
+    }
+
+    fs::write(&full_path, content)
+        .with_context(|| format!("Failed to write file: {}", full_path.display()))
+}
+
+/// Output written next to its final location, then moved into place in one step.
+///
+/// Files go under [`relative_path`](StagedOutput::relative_path), a hidden sibling of
+/// the target named after it and this process. [`commit`](StagedOutput::commit)
+/// swaps the staged output in for whatever is at the target, so readers never see it
+/// half-written and files left from an earlier run are gone with the old output.
+/// Dropped without committing, e.g. after an error, it removes what was staged and
+/// leaves the target as it was.
+pub struct StagedOutput {
+    output_dir: PathBuf,
+    target: PathBuf,
+    staging: PathBuf,
+    committed: bool,
+}
+
+impl StagedOutput {
+    /// Stage output for `target`, a file or directory relative to `output_dir`.
+    pub fn new(output_dir: &Path, target: &Path) -> Result<Self> {
+        let staging = sibling(target, "tmp")?;
+        let staged = StagedOutput {
+            output_dir: output_dir.to_path_buf(),
+            target: target.to_path_buf(),
+            staging,
+            committed: false,
+        };
+        // Left over from a crashed run that had the same process ID
+        staged.remove(&staged.staging)?;
+        Ok(staged)
+    }
+
+    /// Where to write the output, relative to the output directory
+    pub fn relative_path(&self) -> &Path {
+        &self.staging
+    }
+
+    /// Move the staged output to the target, replacing what was there.
+    ///
+    /// Files are replaced atomically. A directory can't be renamed over another, so
+    /// the old one is first moved aside and then deleted.
+    pub fn commit(mut self) -> Result<()> {
+        let staging = self.output_dir.join(&self.staging);
+        let target = self.output_dir.join(&self.target);
+        if !staging.exists() {
+            // Nothing was written
+            self.committed = true;
+            return Ok(());
+        }
+
+        let old = sibling(&self.target, "old")?;
+        let replaces_dir = target.is_dir();
+        if replaces_dir {
+            fs::rename(&target, self.output_dir.join(&old)).with_context(|| {
+                format!("Failed to move aside old output: {}", target.display())
+            })?;
+        }
+        fs::rename(&staging, &target)
+            .with_context(|| format!("Failed to move output into place: {}", target.display()))?;
+        self.committed = true;
+
+        if replaces_dir {
+            self.remove(&old)?;
+        }
+        Ok(())
+    }
+
+    fn remove(&self, relative_path: &Path) -> Result<()> {
+        let path = self.output_dir.join(relative_path);
+        let removed = if path.is_dir() {
+            fs::remove_dir_all(&path)
+        } else if path.exists() {
+            fs::remove_file(&path)
+        } else {
+            return Ok(());
+        };
+        removed.with_context(|| format!("Failed to remove: {}", path.display()))
+    }
+}
+
+impl Drop for StagedOutput {
+    fn drop(&mut self) {
+        if !self.committed {
+            self.remove(&self.staging).ok();
+        }
+    }
+}
+
+/// `dir/.name.{suffix}-{pid}` for `dir/name`
+fn sibling(path: &Path, suffix: &str) -> Result<PathBuf> {
+    let name = path
+        .file_name()
+        .with_context(|| format!("Output path has no file name: {}", path.display()))?;
+    let mut sibling_name = std::ffi::OsString::from(".");
+    sibling_name.push(name);
+    sibling_name.push(format!(".{}-{}", suffix, std::process::id()));
+    Ok(path.with_file_name(sibling_name))
+}
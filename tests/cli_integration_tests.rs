@@ -33,6 +33,15 @@ fn test_flag_validation_workspace_with_package() {
     assert!(err.contains("cannot be used with") || err.contains("conflicts with"));
 }
 
+#[test]
+fn test_flag_validation_include_dev_deps_with_no_deps() {
+    // Dev-dependencies are only documented alongside the other dependencies
+    let result = run_cargo_doc_md(&["--include-dev-deps", "--no-deps"]);
+    assert!(result.is_err());
+    let err = result.unwrap_err();
+    assert!(err.contains("cannot be used with") || err.contains("conflicts with"));
+}
+
 #[test]
 fn test_json_validation_file_not_found() {
     let result = run_cargo_doc_md(&["--json", "nonexistent_file_12345.json"]);
@@ -124,6 +133,50 @@ fn test_cleanup_removes_stale_files() {
     fs::remove_dir_all(&output_dir).ok();
 }
 
+#[test]
+fn test_output_moved_into_place() {
+    let output_dir = PathBuf::from("target/doc-md-test-staged-output");
+    fs::remove_dir_all(&output_dir).ok();
+    let convert = || {
+        run_cargo_doc_md(&[
+            "--json",
+            "tests/fixtures/test_crate.json",
+            "-o",
+            output_dir.to_str().unwrap(),
+        ])
+    };
+
+    convert().expect("first conversion should succeed");
+    let stale_file = output_dir.join("test_crate/stale_module.md");
+    fs::write(&stale_file, "Left over from an earlier run").unwrap();
+    // Another run part way through writing the same crate
+    let in_progress = output_dir.join(".test_crate.tmp-1");
+    fs::create_dir_all(&in_progress).unwrap();
+    fs::write(in_progress.join("index.md"), "# test_crate\n").unwrap();
+
+    convert().expect("second conversion should succeed");
+    assert!(!stale_file.exists(), "old output should be replaced");
+    assert!(output_dir.join("test_crate/index.md").exists());
+    let staged: Vec<_> = fs::read_dir(&output_dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .filter(|name| name.contains(".tmp-") || name.contains(".old-"))
+        .collect();
+    assert_eq!(staged, [".test_crate.tmp-1"], "nothing of ours left behind");
+
+    fs::remove_file(output_dir.join("index.md")).unwrap();
+    run_cargo_doc_md(&["--index-only", "-o", output_dir.to_str().unwrap()])
+        .expect("index regeneration should succeed");
+    let index = fs::read_to_string(output_dir.join("index.md")).unwrap();
+    assert!(index.contains("- [`test_crate`](test_crate/index.md)"));
+    assert!(
+        !index.contains(".test_crate"),
+        "in-progress output is skipped"
+    );
+
+    fs::remove_dir_all(&output_dir).ok();
+}
+
 #[test]
 fn test_package_flag_single() {
     // Test that -p flag is accepted (gracefully fails if package doesn't exist)
@@ -174,6 +227,24 @@ fn test_workspace_flag_in_single_crate() {
     }
 }
 
+#[test]
+fn test_workspace_exclude_every_member() {
+    // This project is its only workspace member, matched here by its crate name
+    let result = run_cargo_doc_md(&[
+        "--workspace",
+        "--no-deps",
+        "--exclude",
+        "cargo_doc_*",
+        "-o",
+        "target/doc-md-test-workspace-exclude",
+    ]);
+    assert!(
+        result
+            .unwrap_err()
+            .contains("Every workspace member is excluded by --exclude")
+    );
+}
+
 #[test]
 fn test_workspace_with_no_deps() {
     // Test that --workspace --no-deps combination is valid
@@ -234,3 +305,1281 @@ fn test_output_directory_validation_parent_missing() {
 
     fs::remove_dir_all(temp_dir.join("cargo_doc_md_test_parent_12345")).ok();
 }
+
+#[test]
+fn test_stdout_mode_prints_only_markdown() {
+    let stdout = run_cargo_doc_md(&["--json", "tests/fixtures/test_crate.json", "--stdout"])
+        .expect("stdout mode should succeed");
+
+    assert!(
+        stdout.starts_with("# test_crate\n"),
+        "Crate index should come first"
+    );
+    assert!(stdout.contains("# Module: nested::inner::deep"));
+    assert!(
+        stdout.find("# Module: nested\n").unwrap()
+            < stdout.find("# Module: nested::inner\n").unwrap()
+    );
+    assert!(!stdout.contains("Master index"));
+    assert!(!stdout.contains("Loaded crate"));
+    assert!(!stdout.contains("# ====="));
+}
+
+#[test]
+fn test_quiet_suppresses_progress() {
+    let output_dir = PathBuf::from("target/doc-md-test-quiet");
+    fs::remove_dir_all(&output_dir).ok();
+
+    let output = Command::new("cargo")
+        .args([
+            "run",
+            "-q",
+            "--",
+            "--json",
+            "tests/fixtures/test_crate.json",
+            "-q",
+            "-o",
+        ])
+        .arg(&output_dir)
+        .output()
+        .expect("Failed to execute command");
+
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+    assert!(output.stderr.is_empty());
+    assert!(output_dir.join("index.md").exists());
+    assert!(output_dir.join("test_crate/index.md").exists());
+
+    fs::remove_dir_all(&output_dir).ok();
+}
+
+#[test]
+fn test_stdout_conflicts_with_output() {
+    let result = run_cargo_doc_md(&["--stdout", "-o", "docs"]);
+    assert!(result.is_err());
+    let err = result.unwrap_err();
+    assert!(err.contains("cannot be used with") || err.contains("conflicts with"));
+}
+
+#[test]
+fn test_mdbook_summary_generation() {
+    let output_dir = PathBuf::from("target/doc-md-test-mdbook");
+    fs::remove_dir_all(&output_dir).ok();
+
+    let result = run_cargo_doc_md(&[
+        "--json",
+        "tests/fixtures/test_crate.json",
+        "-o",
+        output_dir.to_str().unwrap(),
+        "--mdbook",
+    ]);
+    assert!(result.is_ok(), "mdbook generation should succeed");
+
+    let summary = fs::read_to_string(output_dir.join("SUMMARY.md")).unwrap();
+    assert!(summary.starts_with("# Summary\n"));
+    assert!(summary.contains("\n- [test_crate](test_crate/index.md)\n"));
+    assert!(summary.contains("\n  - [nested](test_crate/nested.md)\n"));
+    assert!(summary.contains("\n    - [inner](test_crate/nested/inner.md)\n"));
+    assert!(summary.contains("\n      - [deep](test_crate/nested/inner/deep.md)\n"));
+    assert_eq!(summary.matches("(test_crate/types.md)").count(), 1);
+
+    fs::remove_dir_all(&output_dir).ok();
+}
+
+#[test]
+fn test_llms_txt_generation() {
+    let output_dir = PathBuf::from("target/doc-md-test-llms-txt");
+    fs::remove_dir_all(&output_dir).ok();
+
+    let result = run_cargo_doc_md(&[
+        "--json",
+        "tests/fixtures/test_crate.json",
+        "-o",
+        output_dir.to_str().unwrap(),
+        "--llms-txt",
+    ]);
+    assert!(result.is_ok(), "llms.txt generation should succeed");
+
+    let llms = fs::read_to_string(output_dir.join("llms.txt")).unwrap();
+    assert!(llms.starts_with("# Documentation Index\n\n> "));
+    assert!(llms.contains(
+        "\n## test_crate\n\nA comprehensive test crate demonstrating all Rust documentation features.\n\n"
+    ));
+    assert!(llms.contains("\n- [test_crate](test_crate/index.md): Crate overview\n"));
+    assert!(llms.contains(
+        "\n- [test_crate::functions](test_crate/functions.md): Function examples demonstrating various signatures and patterns.\n"
+    ));
+    assert!(llms.contains(
+        "\n- [test_crate::Labeled](test_crate/test_crate.md#test_cratelabeled): A value paired with a display label.\n"
+    ));
+    assert!(
+        llms.contains("\n- [test_crate::nested::inner::deep](test_crate/nested/inner/deep.md)")
+    );
+    // Only the modules' own pages, not the items inside them
+    assert!(!llms.contains("test_crate::functions::add"));
+
+    // Rebuilding the indexes from the output gives the same file
+    fs::remove_file(output_dir.join("llms.txt")).unwrap();
+    let result = run_cargo_doc_md(&[
+        "--index-only",
+        "--llms-txt",
+        "-o",
+        output_dir.to_str().unwrap(),
+    ]);
+    assert!(result.is_ok(), "index-only run should succeed");
+    assert_eq!(
+        fs::read_to_string(output_dir.join("llms.txt")).unwrap(),
+        llms
+    );
+
+    fs::remove_dir_all(&output_dir).ok();
+}
+
+#[test]
+fn test_manifest_generation() {
+    let output_dir = PathBuf::from("target/doc-md-test-manifest");
+    fs::remove_dir_all(&output_dir).ok();
+
+    let result = run_cargo_doc_md(&[
+        "--json",
+        "tests/fixtures/test_crate.json",
+        "-o",
+        output_dir.to_str().unwrap(),
+    ]);
+    assert!(result.is_ok(), "conversion should succeed");
+
+    let manifest: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(output_dir.join("manifest.json")).unwrap())
+            .expect("manifest.json should be valid JSON");
+    assert_eq!(manifest["tool_version"], env!("CARGO_PKG_VERSION"));
+
+    let crates = manifest["crates"].as_array().unwrap();
+    assert_eq!(crates.len(), 1);
+    assert_eq!(crates[0]["name"], "test_crate");
+    assert_eq!(crates[0]["version"], "0.1.0");
+    assert_eq!(crates[0]["index"], "test_crate/index.md");
+
+    let modules = crates[0]["modules"].as_array().unwrap();
+    let types = modules
+        .iter()
+        .find(|m| m["path"] == "test_crate/types.md")
+        .expect("types module should be listed");
+    assert!(types["items"]["struct"].as_u64().unwrap() > 0);
+
+    fs::remove_dir_all(&output_dir).ok();
+}
+
+#[test]
+fn test_single_file_output() {
+    let output_dir = PathBuf::from("target/doc-md-test-single-file");
+    fs::remove_dir_all(&output_dir).ok();
+
+    let result = run_cargo_doc_md(&[
+        "--json",
+        "tests/fixtures/test_crate.json",
+        "--single-file",
+        "--mdbook",
+        "-o",
+        output_dir.to_str().unwrap(),
+    ]);
+    assert!(result.is_ok(), "conversion should succeed");
+
+    assert!(output_dir.join("test_crate.md").is_file());
+    assert!(!output_dir.join("test_crate").exists());
+
+    let index = fs::read_to_string(output_dir.join("index.md")).unwrap();
+    assert!(index.contains("- [`test_crate`](test_crate.md)"));
+
+    let summary = fs::read_to_string(output_dir.join("SUMMARY.md")).unwrap();
+    assert!(summary.contains("- [test_crate](test_crate.md)"));
+
+    let manifest: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(output_dir.join("manifest.json")).unwrap())
+            .expect("manifest.json should be valid JSON");
+    assert_eq!(manifest["crates"][0]["index"], "test_crate.md");
+    assert_eq!(manifest["crates"][0]["modules"][0]["path"], "test_crate.md");
+
+    fs::remove_dir_all(&output_dir).ok();
+}
+
+#[test]
+fn test_emit_ir() {
+    let output_dir = PathBuf::from("target/doc-md-test-emit-ir");
+    fs::remove_dir_all(&output_dir).ok();
+
+    let result = run_cargo_doc_md(&[
+        "--json",
+        "tests/fixtures/test_crate.json",
+        "--emit",
+        "ir",
+        "-o",
+        output_dir.to_str().unwrap(),
+    ]);
+    assert!(result.is_ok(), "conversion should succeed");
+
+    let ir: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(output_dir.join("test_crate.json")).unwrap())
+            .expect("IR should be valid JSON");
+    assert_eq!(ir["name"], "test_crate");
+    assert!(!output_dir.join("test_crate").exists());
+    assert!(!output_dir.join("index.md").exists());
+
+    fs::remove_dir_all(&output_dir).ok();
+}
+
+#[test]
+fn test_search_index() {
+    let output_dir = PathBuf::from("target/doc-md-test-search-index");
+    fs::remove_dir_all(&output_dir).ok();
+
+    let result = run_cargo_doc_md(&[
+        "--json",
+        "tests/fixtures/test_crate.json",
+        "--search-index",
+        "-o",
+        output_dir.to_str().unwrap(),
+    ]);
+    assert!(result.is_ok(), "conversion should succeed");
+
+    let index: serde_json::Value = serde_json::from_str(
+        &fs::read_to_string(output_dir.join("test_crate/search.json")).unwrap(),
+    )
+    .expect("search.json should be valid JSON");
+    let entries = index
+        .as_array()
+        .expect("search index should be a flat array");
+    let builder = entries
+        .iter()
+        .find(|entry| entry["path"] == "test_crate::patterns::Builder")
+        .expect("Builder not indexed");
+    assert_eq!(builder["kind"], "struct");
+    assert_eq!(builder["file"], "patterns.md");
+    assert_eq!(builder["anchor"], "test_cratepatternsbuilder");
+
+    fs::remove_dir_all(&output_dir).ok();
+}
+
+#[test]
+fn test_bin_target() {
+    let output_dir = PathBuf::from("target/doc-md-test-bin");
+    fs::remove_dir_all(&output_dir).ok();
+
+    let result = run_cargo_doc_md(&["--bin", "--no-deps", "-o", output_dir.to_str().unwrap()]);
+    assert!(result.is_ok(), "documenting the binary should succeed");
+    assert!(output_dir.join("cargo_doc_md/index.md").is_file());
+
+    fs::remove_dir_all(&output_dir).ok();
+}
+
+#[test]
+fn test_bin_target_not_found() {
+    let result = run_cargo_doc_md(&["--bin", "missing", "--no-deps"]);
+    assert!(result.is_err());
+    let err = result.unwrap_err();
+    assert!(err.contains("No binary target named 'missing'"));
+    assert!(err.contains("Available binaries: cargo-doc-md"));
+}
+
+#[test]
+fn test_target_not_installed() {
+    let result = run_cargo_doc_md(&["--target", "wasm64-unknown-unknown", "--no-deps"]);
+    assert!(result.is_err());
+    assert!(
+        result
+            .unwrap_err()
+            .contains("rustup +nightly target add wasm64-unknown-unknown")
+    );
+}
+
+#[test]
+fn test_jobs_flag_rejects_zero() {
+    let result = run_cargo_doc_md(&["--jobs", "0", "--no-deps"]);
+    assert!(result.is_err());
+    assert!(result.unwrap_err().contains("invalid value"));
+}
+
+#[test]
+fn test_cache_skips_unchanged_crates() {
+    let output_dir = PathBuf::from("target/doc-md-test-cache");
+    fs::remove_dir_all(&output_dir).ok();
+    let output = output_dir.to_str().unwrap();
+
+    let first = run_cargo_doc_md(&["-o", output, "-p", "itoa", "--no-deps"])
+        .expect("First run should succeed");
+    assert!(first.contains("✓ itoa →"));
+    assert!(output_dir.join(".cache/itoa.hash").exists());
+
+    let second = run_cargo_doc_md(&["-o", output, "-p", "itoa", "--no-deps"])
+        .expect("Second run should succeed");
+    assert!(second.contains("⏭ itoa (unchanged)"));
+
+    let uncached = run_cargo_doc_md(&["-o", output, "-p", "itoa", "--no-deps", "--no-cache"])
+        .expect("Uncached run should succeed");
+    assert!(uncached.contains("✓ itoa →"));
+    assert!(output_dir.join("itoa/index.md").exists());
+
+    fs::remove_dir_all(&output_dir).ok();
+}
+
+#[test]
+fn test_requested_package_not_documented_again_as_dependency() {
+    let output_dir = PathBuf::from("target/doc-md-test-already-documented");
+    fs::remove_dir_all(&output_dir).ok();
+
+    // itoa is requested and is also a dependency of serde_json
+    let stdout = run_cargo_doc_md(&[
+        "-o",
+        output_dir.to_str().unwrap(),
+        "-p",
+        "serde_json",
+        "-p",
+        "itoa",
+    ])
+    .expect("documenting should succeed");
+
+    assert_eq!(stdout.matches("✓ itoa →").count(), 1);
+    assert!(stdout.contains("⏭ itoa (already documented)"));
+    let index = fs::read_to_string(output_dir.join("index.md")).unwrap();
+    assert_eq!(index.matches("(itoa/index.md)").count(), 1);
+
+    fs::remove_dir_all(&output_dir).ok();
+}
+
+#[test]
+fn test_only_regenerates_selected_crates() {
+    let output_dir = PathBuf::from("target/doc-md-test-only");
+    fs::remove_dir_all(&output_dir).ok();
+    let output = output_dir.to_str().unwrap();
+
+    let first =
+        run_cargo_doc_md(&["-o", output, "--only", "anyhow"]).expect("first run should succeed");
+    assert!(first.contains("✓ anyhow →"));
+    assert!(!first.contains("✓ itoa →"));
+    assert!(!output_dir.join("cargo_doc_md").exists());
+
+    let second =
+        run_cargo_doc_md(&["-o", output, "--only", "itoa"]).expect("second run should succeed");
+    assert!(second.contains("✓ itoa →"));
+    assert!(!second.contains("anyhow"));
+    // The earlier crate is left in place and still indexed
+    assert!(output_dir.join("anyhow/index.md").exists());
+    let index = fs::read_to_string(output_dir.join("index.md")).unwrap();
+    assert!(index.contains("(anyhow/index.md)"));
+    assert!(index.contains("(itoa/index.md)"));
+
+    let err = run_cargo_doc_md(&["-o", output, "--only", "nonexistent-crate-12345"])
+        .expect_err("an unknown crate should be rejected");
+    assert!(err.contains("--only nonexistent-crate-12345: no such package"));
+
+    fs::remove_dir_all(&output_dir).ok();
+}
+
+#[test]
+fn test_only_with_index_options() {
+    let output_dir = PathBuf::from("target/doc-md-test-only-index-options");
+    fs::remove_dir_all(&output_dir).ok();
+
+    let stdout = run_cargo_doc_md(&[
+        "--manifest-path",
+        "tests/fixtures/test_crate/Cargo.toml",
+        "--only",
+        "test_crate",
+        "--no-deps",
+        "--no-index",
+        "--mdbook",
+        "--stats",
+        "-o",
+        output_dir.to_str().unwrap(),
+    ])
+    .expect("regenerating one crate should succeed");
+
+    // The other index options apply to --only runs as to full ones
+    assert!(!output_dir.join("index.md").exists());
+    let summary = fs::read_to_string(output_dir.join("SUMMARY.md")).unwrap();
+    assert!(summary.contains("\n- [test_crate](test_crate/index.md)\n"));
+    let manifest = fs::read_to_string(output_dir.join("manifest.json")).unwrap();
+    assert!(manifest.contains("\"test_crate/index.md\""));
+    assert!(stdout.contains("Total (1 crate)"));
+
+    fs::remove_dir_all(&output_dir).ok();
+}
+
+#[test]
+fn test_diff_against_previous_output() {
+    let old_dir = PathBuf::from("target/doc-md-test-diff-old");
+    let new_dir = PathBuf::from("target/doc-md-test-diff-new");
+    fs::remove_dir_all(&old_dir).ok();
+    fs::remove_dir_all(&new_dir).ok();
+
+    let generate = |output_dir: &PathBuf, diff_dir: Option<&PathBuf>| {
+        let mut cmd = Command::new("cargo");
+        cmd.args([
+            "run",
+            "--",
+            "--json",
+            "tests/fixtures/test_crate.json",
+            "-o",
+        ])
+        .arg(output_dir);
+        if let Some(diff_dir) = diff_dir {
+            cmd.arg("--diff").arg(diff_dir);
+        }
+        cmd.output().expect("Failed to execute command")
+    };
+
+    assert!(generate(&old_dir, None).status.success());
+
+    let unchanged = generate(&new_dir, Some(&old_dir));
+    assert!(unchanged.status.success(), "identical docs should pass");
+    assert_eq!(
+        String::from_utf8_lossy(&unchanged.stdout),
+        "No documentation changes\n"
+    );
+
+    let functions = old_dir.join("test_crate/functions.md");
+    let content = fs::read_to_string(&functions).unwrap();
+    fs::write(
+        &functions,
+        content.replace("Adds two numbers", "Sums two numbers"),
+    )
+    .unwrap();
+    fs::write(old_dir.join("test_crate/removed.md"), "# Removed\n").unwrap();
+
+    let changed = generate(&new_dir, Some(&old_dir));
+    assert_eq!(changed.status.code(), Some(1), "changed docs should fail");
+    let stdout = String::from_utf8_lossy(&changed.stdout);
+    assert!(stdout.contains("Documentation changes: 0 added, 1 removed, 1 modified"));
+    assert!(stdout.contains("  removed:  test_crate/removed.md"));
+    assert!(stdout.contains("  modified: test_crate/functions.md"));
+    assert!(stdout.contains("--- a/test_crate/functions.md"));
+    assert!(stdout.contains("-Sums two numbers together."));
+    assert!(stdout.contains("+Adds two numbers together."));
+
+    fs::remove_dir_all(&old_dir).ok();
+    fs::remove_dir_all(&new_dir).ok();
+}
+
+#[test]
+fn test_index_only() {
+    let output_dir = PathBuf::from("target/doc-md-test-index-only");
+    fs::remove_dir_all(&output_dir).ok();
+
+    let result = run_cargo_doc_md(&[
+        "--json",
+        "tests/fixtures/test_crate.json",
+        "-o",
+        output_dir.to_str().unwrap(),
+    ]);
+    assert!(result.is_ok(), "conversion should succeed");
+
+    // A crate directory copied in by hand, with no manifest entry
+    fs::create_dir_all(output_dir.join("rand-0.8")).unwrap();
+    fs::write(output_dir.join("rand-0.8/index.md"), "# rand\n").unwrap();
+    fs::remove_file(output_dir.join("index.md")).unwrap();
+
+    let result = run_cargo_doc_md(&["--index-only", "-o", output_dir.to_str().unwrap()]);
+    assert!(result.is_ok(), "index regeneration should succeed");
+
+    let index = fs::read_to_string(output_dir.join("index.md")).unwrap();
+    assert!(index.contains("## Dependencies (2)"));
+    assert!(index.contains("- [`rand`](rand-0.8/index.md)"));
+    assert!(index.contains("- [`test_crate`](test_crate/index.md)"));
+
+    fs::remove_dir_all(&output_dir).ok();
+}
+
+#[test]
+fn test_wikilinks_master_index() {
+    let output_dir = PathBuf::from("target/doc-md-test-wikilinks");
+    fs::remove_dir_all(&output_dir).ok();
+
+    let result = run_cargo_doc_md(&[
+        "--json",
+        "tests/fixtures/test_crate.json",
+        "--wikilinks",
+        "-o",
+        output_dir.to_str().unwrap(),
+    ]);
+    assert!(result.is_ok(), "conversion should succeed: {:?}", result);
+
+    let index = fs::read_to_string(output_dir.join("index.md")).unwrap();
+    assert!(index.contains("- [[test_crate/index|`test_crate`]]"));
+    let crate_index = fs::read_to_string(output_dir.join("test_crate/index.md")).unwrap();
+    assert!(crate_index.contains("[[test_crate/types|`types`]]"));
+
+    fs::remove_dir_all(&output_dir).ok();
+}
+
+#[test]
+fn test_no_footer() {
+    let output_dir = PathBuf::from("target/doc-md-test-no-footer");
+    fs::remove_dir_all(&output_dir).ok();
+
+    let result = run_cargo_doc_md(&[
+        "--json",
+        "tests/fixtures/test_crate.json",
+        "--no-footer",
+        "-o",
+        output_dir.to_str().unwrap(),
+    ]);
+    assert!(result.is_ok(), "conversion should succeed: {:?}", result);
+
+    let index = fs::read_to_string(output_dir.join("index.md")).unwrap();
+    assert!(!index.contains("Generated with"));
+    assert!(index.ends_with("(test_crate/index.md)\n\n"));
+
+    fs::remove_dir_all(&output_dir).ok();
+}
+
+#[test]
+fn test_no_index() {
+    let output_dir = PathBuf::from("target/doc-md-test-no-index");
+    fs::remove_dir_all(&output_dir).ok();
+
+    let result = run_cargo_doc_md(&[
+        "--json",
+        "tests/fixtures/test_crate.json",
+        "--no-index",
+        "-o",
+        output_dir.to_str().unwrap(),
+    ]);
+    assert!(result.is_ok(), "conversion should succeed: {:?}", result);
+    assert!(!result.unwrap().contains("Master index"));
+
+    assert!(!output_dir.join("index.md").exists());
+    assert!(output_dir.join("test_crate/index.md").exists());
+    assert!(output_dir.join("test_crate/types.md").exists());
+
+    fs::remove_dir_all(&output_dir).ok();
+}
+
+#[test]
+fn test_index_only_nested_layout() {
+    let output_dir = PathBuf::from("target/doc-md-test-index-only-nested");
+    fs::remove_dir_all(&output_dir).ok();
+    fs::create_dir_all(output_dir.join("my_app")).unwrap();
+    fs::create_dir_all(output_dir.join("deps/serde")).unwrap();
+    fs::write(output_dir.join("my_app/index.md"), "# my_app\n").unwrap();
+    fs::write(output_dir.join("deps/serde/index.md"), "# serde\n").unwrap();
+
+    let result = run_cargo_doc_md(&[
+        "--index-only",
+        "--layout",
+        "nested",
+        "-o",
+        output_dir.to_str().unwrap(),
+    ]);
+    assert!(
+        result.is_ok(),
+        "index-only run should succeed: {:?}",
+        result
+    );
+
+    let index = fs::read_to_string(output_dir.join("index.md")).unwrap();
+    assert!(index.contains("- [`my_app`](my_app/index.md)\n"));
+    assert!(index.contains("- [`serde`](deps/serde/index.md)\n"));
+    assert!(!index.contains("(deps/index.md)"));
+
+    fs::remove_dir_all(&output_dir).ok();
+}
+
+#[test]
+fn test_index_only_without_output() {
+    let output_dir = PathBuf::from("target/doc-md-test-index-only-empty");
+    fs::remove_dir_all(&output_dir).ok();
+    fs::create_dir_all(&output_dir).unwrap();
+
+    let result = run_cargo_doc_md(&["--index-only", "-o", output_dir.to_str().unwrap()]);
+    assert!(result.unwrap_err().contains("No generated crates found"));
+
+    fs::remove_dir_all(&output_dir).ok();
+}
+
+#[test]
+fn test_check_links() {
+    let output_dir = PathBuf::from("target/doc-md-test-check-links");
+    fs::remove_dir_all(&output_dir).ok();
+    let args = [
+        "--json",
+        "tests/fixtures/test_crate.json",
+        "--check-links",
+        "-o",
+        output_dir.to_str().unwrap(),
+    ];
+
+    let result = run_cargo_doc_md(&args);
+    assert!(
+        result.is_ok(),
+        "generated links should resolve: {:?}",
+        result
+    );
+    assert!(result.unwrap().contains("links resolve"));
+
+    // Leftover files in the output directory are checked too
+    fs::write(
+        output_dir.join("notes.md"),
+        "# Notes\n\n\
+         See [the types](test_crate/types.md#test_cratetypespair) and [the web](https://example.com).\n\
+         [Gone](test_crate/missing.md) and [misspelled](test_crate/types.md#test_cratetypespear).\n\
+         Back to [the top](#notes), but not [`here`](#nowhere).\n",
+    )
+    .unwrap();
+    let err = run_cargo_doc_md(&args).expect_err("broken links should fail the run");
+    let notes = output_dir.join("notes.md");
+    let notes = notes.display();
+    assert!(err.contains(&format!(
+        "{}:4: broken link to test_crate/missing.md: file not found",
+        notes
+    )));
+    assert!(err.contains(&format!(
+        "{}:4: broken link to test_crate/types.md#test_cratetypespear: no heading with anchor #test_cratetypespear",
+        notes
+    )));
+    assert!(err.contains(&format!(
+        "{}:5: broken link to #nowhere: no heading with anchor #nowhere",
+        notes
+    )));
+    assert!(!err.contains("example.com"));
+    assert!(!err.contains("#notes"));
+    assert!(err.contains("3 of "));
+
+    fs::remove_dir_all(&output_dir).ok();
+}
+
+#[test]
+fn test_output_dir_from_environment() {
+    let env_dir = PathBuf::from("target/doc-md-test-env-output");
+    let explicit_dir = PathBuf::from("target/doc-md-test-env-output-explicit");
+    fs::remove_dir_all(&env_dir).ok();
+    fs::remove_dir_all(&explicit_dir).ok();
+    let run = |args: &[&str]| {
+        Command::new("cargo")
+            .args(["run", "--", "--json", "tests/fixtures/test_crate.json"])
+            .args(args)
+            .env("CARGO_DOC_MD_OUTPUT", &env_dir)
+            .output()
+            .expect("Failed to execute command")
+    };
+
+    assert!(run(&[]).status.success());
+    assert!(env_dir.join("test_crate/index.md").exists());
+    fs::remove_dir_all(&env_dir).ok();
+
+    // An explicit --output wins over the environment
+    assert!(
+        run(&["-o", explicit_dir.to_str().unwrap()])
+            .status
+            .success()
+    );
+    assert!(explicit_dir.join("test_crate/index.md").exists());
+    assert!(!env_dir.exists());
+
+    fs::remove_dir_all(&explicit_dir).ok();
+}
+
+#[test]
+fn test_stats() {
+    let output_dir = PathBuf::from("target/doc-md-test-stats");
+    fs::remove_dir_all(&output_dir).ok();
+
+    let stdout = run_cargo_doc_md(&[
+        "--json",
+        "tests/fixtures/test_crate.json",
+        "--stats",
+        "--quiet",
+        "-o",
+        output_dir.to_str().unwrap(),
+    ])
+    .expect("conversion should succeed");
+
+    let lines: Vec<&str> = stdout.lines().collect();
+    let header = lines
+        .iter()
+        .position(|line| line.starts_with("Crate "))
+        .expect("stats table header");
+    assert!(
+        lines[header]
+            .ends_with("Structs  Enums  Traits  Functions  Constants  Type aliases  Modules")
+    );
+    let row: Vec<&str> = lines[header + 1].split_whitespace().collect();
+    assert_eq!(row[0], "test_crate");
+    assert!(
+        row[1..]
+            .iter()
+            .all(|count| count.parse::<u64>().unwrap() > 0)
+    );
+    // A single crate's totals are its own counts
+    let total = lines.last().unwrap();
+    assert!(total.starts_with("Total (1 crate)"));
+    assert_eq!(
+        total.split_whitespace().skip(3).collect::<Vec<_>>(),
+        row[1..]
+    );
+
+    fs::remove_dir_all(&output_dir).ok();
+}
+
+#[cfg(unix)]
+#[test]
+fn test_open_index() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let output_dir = PathBuf::from("target/doc-md-test-open");
+    let bin_dir = PathBuf::from("target/doc-md-test-open-bin");
+    fs::remove_dir_all(&output_dir).ok();
+    fs::remove_dir_all(&bin_dir).ok();
+    fs::create_dir_all(&bin_dir).unwrap();
+    let opened = bin_dir.join("opened");
+    let opener = format!(
+        "#!/bin/sh\necho \"$1\" > {}\n",
+        fs::canonicalize(&bin_dir).unwrap().join("opened").display()
+    );
+    for name in ["xdg-open", "open"] {
+        let script = bin_dir.join(name);
+        fs::write(&script, &opener).unwrap();
+        fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+    }
+    let run = |path: &std::ffi::OsStr| {
+        Command::new(env!("CARGO_BIN_EXE_cargo-doc-md"))
+            .args(["--json", "tests/fixtures/test_crate.json", "--open", "-o"])
+            .arg(&output_dir)
+            .env("PATH", path)
+            .output()
+            .expect("Failed to execute command")
+    };
+
+    let output = run(fs::canonicalize(&bin_dir).unwrap().as_os_str());
+    assert!(output.status.success());
+    let opened_path = fs::read_to_string(&opened).expect("the opener should have run");
+    assert_eq!(
+        opened_path.trim(),
+        output_dir.join("index.md").to_str().unwrap()
+    );
+
+    // Without an opener, generation still succeeds and only warns
+    let output = run(std::ffi::OsStr::new(""));
+    assert!(output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("Not opening"));
+    assert!(output_dir.join("index.md").exists());
+
+    fs::remove_dir_all(&output_dir).ok();
+    fs::remove_dir_all(&bin_dir).ok();
+}
+
+#[test]
+fn test_flag_validation_open_with_stdout() {
+    let result = run_cargo_doc_md(&["--open", "--stdout"]);
+    assert!(result.is_err());
+    let err = result.unwrap_err();
+    assert!(err.contains("cannot be used with"));
+}
+
+#[test]
+fn test_file_list() {
+    let output_dir = PathBuf::from("target/doc-md-test-file-list");
+    fs::remove_dir_all(&output_dir).ok();
+
+    run_cargo_doc_md(&[
+        "--json",
+        "tests/fixtures/test_crate.json",
+        "--file-list",
+        "-o",
+        output_dir.to_str().unwrap(),
+    ])
+    .expect("conversion should succeed");
+
+    let list = fs::read_to_string(output_dir.join("index.txt")).expect("index.txt");
+    let files: Vec<&str> = list.lines().collect();
+    assert!(files.contains(&"index.md"));
+    assert!(files.contains(&"test_crate/index.md"));
+    assert!(files.contains(&"test_crate/nested/inner.md"));
+    assert!(files.is_sorted());
+    // Exactly the markdown that was written
+    for file in &files {
+        assert!(output_dir.join(file).is_file(), "{} should exist", file);
+    }
+    let written = fs::read_dir(output_dir.join("test_crate/nested"))
+        .unwrap()
+        .filter(|entry| entry.as_ref().unwrap().path().extension() == Some("md".as_ref()))
+        .count();
+    let listed = files
+        .iter()
+        .filter(|file| {
+            file.strip_prefix("test_crate/nested/")
+                .is_some_and(|rest| !rest.contains('/'))
+        })
+        .count();
+    assert_eq!(listed, written);
+
+    fs::remove_dir_all(&output_dir).ok();
+}
+
+#[test]
+fn test_json_dir() {
+    let json_dir = PathBuf::from("target/doc-md-test-json-dir-input");
+    let output_dir = PathBuf::from("target/doc-md-test-json-dir");
+    fs::remove_dir_all(&json_dir).ok();
+    fs::remove_dir_all(&output_dir).ok();
+    fs::create_dir_all(&json_dir).unwrap();
+    fs::copy(
+        "tests/fixtures/test_crate.json",
+        json_dir.join("test_crate.json"),
+    )
+    .unwrap();
+    // Not rustdoc JSON: reported and skipped
+    fs::write(json_dir.join("settings.json"), "{\"theme\": \"dark\"}").unwrap();
+    // Not JSON at all: ignored
+    fs::write(json_dir.join("search-index.js"), "var searchIndex;").unwrap();
+
+    let stdout = run_cargo_doc_md(&[
+        "--json-dir",
+        json_dir.to_str().unwrap(),
+        "-o",
+        output_dir.to_str().unwrap(),
+    ])
+    .expect("conversion should succeed");
+
+    assert!(stdout.contains("Converting 2 rustdoc JSON files"));
+    assert!(stdout.contains("Failed to parse JSON from:"));
+    assert!(output_dir.join("test_crate/index.md").exists());
+    let index = fs::read_to_string(output_dir.join("index.md")).unwrap();
+    assert!(index.contains("- [`test_crate`](test_crate/index.md)"));
+    assert!(!index.contains("settings"));
+
+    fs::remove_dir_all(&json_dir).ok();
+    fs::remove_dir_all(&output_dir).ok();
+}
+
+#[test]
+fn test_base_url() {
+    let output_dir = PathBuf::from("target/doc-md-test-base-url");
+    fs::remove_dir_all(&output_dir).ok();
+
+    run_cargo_doc_md(&[
+        "--json",
+        "tests/fixtures/test_crate.json",
+        "--base-url",
+        "/docs/api/",
+        "--check-links",
+        "-o",
+        output_dir.to_str().unwrap(),
+    ])
+    .expect("links under the base URL should resolve");
+
+    let master = fs::read_to_string(output_dir.join("index.md")).unwrap();
+    assert!(master.contains("[`test_crate`](/docs/api/test_crate/index.md)"));
+    assert!(master.contains("(https://github.com/Crazytieguy/cargo-doc-md)"));
+
+    let root = fs::read_to_string(output_dir.join("test_crate/test_crate.md")).unwrap();
+    assert!(root.contains("](/docs/api/test_crate/errors.md#test_crateerrorscustomerror)"));
+    // Links within the file stay anchors
+    assert!(root.contains("](#module-types)"));
+
+    let deep = fs::read_to_string(output_dir.join("test_crate/nested/inner.md")).unwrap();
+    assert!(deep.contains("](/docs/api/test_crate/nested.md)"));
+    assert!(!deep.contains("](../"));
+}
+
+#[test]
+fn test_fail_on_warning() {
+    let json_dir = PathBuf::from("target/doc-md-test-fail-on-warning-input");
+    let output_dir = PathBuf::from("target/doc-md-test-fail-on-warning");
+    fs::remove_dir_all(&json_dir).ok();
+    fs::create_dir_all(&json_dir).unwrap();
+    fs::copy(
+        "tests/fixtures/test_crate.json",
+        json_dir.join("test_crate.json"),
+    )
+    .unwrap();
+    fs::write(json_dir.join("broken.json"), "{\"theme\": \"dark\"}").unwrap();
+    let args = [
+        "--json-dir",
+        json_dir.to_str().unwrap(),
+        "-o",
+        output_dir.to_str().unwrap(),
+    ];
+
+    // Lenient by default
+    let stdout = run_cargo_doc_md(&args).expect("partial failure should exit 0");
+    assert!(stdout.contains("✗ Failed: 1"));
+
+    let output = Command::new(env!("CARGO_BIN_EXE_cargo-doc-md"))
+        .args(args)
+        .arg("--fail-on-warning")
+        .output()
+        .expect("Failed to execute command");
+    assert_eq!(output.status.code(), Some(1));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("✓ Successful: 1"));
+    assert!(stdout.contains("✗ Failed: 1"));
+    assert!(stdout.contains("Some crates failed to document (--fail-on-warning)"));
+    assert!(output_dir.join("test_crate/index.md").exists());
+
+    fs::remove_dir_all(&json_dir).ok();
+    fs::remove_dir_all(&output_dir).ok();
+}
+
+#[test]
+fn test_flag_validation_json_dir_with_json() {
+    let result = run_cargo_doc_md(&["--json-dir", "target/doc", "--json", "test.json"]);
+    assert!(result.is_err());
+    let err = result.unwrap_err();
+    assert!(err.contains("cannot be used with"));
+}
+
+#[test]
+fn test_manifest_path() {
+    let output_dir = PathBuf::from("target/doc-md-test-manifest-path");
+    fs::remove_dir_all(&output_dir).ok();
+
+    let stdout = run_cargo_doc_md(&[
+        "--manifest-path",
+        "tests/fixtures/test_crate/Cargo.toml",
+        "--no-deps",
+        "-o",
+        output_dir.to_str().unwrap(),
+    ])
+    .expect("documenting another crate should succeed");
+
+    // The fixture crate is documented, not the crate in the current directory
+    assert!(stdout.contains("Current crate documented"));
+    assert!(output_dir.join("test_crate/types.md").exists());
+    assert!(!output_dir.join("cargo_doc_md").exists());
+
+    fs::remove_dir_all(&output_dir).ok();
+}
+
+#[test]
+fn test_include_private_shows_visibility() {
+    let output_dir = PathBuf::from("target/doc-md-test-private-visibility");
+    fs::remove_dir_all(&output_dir).ok();
+    let output = output_dir.to_str().unwrap();
+    let args = [
+        "--manifest-path",
+        "tests/fixtures/test_crate/Cargo.toml",
+        "--no-deps",
+        "-o",
+        output,
+    ];
+
+    run_cargo_doc_md(&[&args[..], &["--include-private"]].concat())
+        .expect("documenting private items should succeed");
+    let root = fs::read_to_string(output_dir.join("test_crate/test_crate.md")).unwrap();
+    assert!(root.contains("## pub test_crate::PlainStruct\n\n*Struct*\n"));
+    assert!(root.contains("- `pub name: String`\n"));
+    assert!(root.contains("- `pub(crate) private_field: bool`\n"));
+    assert!(root.contains("- `pub fn get_value(&self) -> i32`\n"));
+    assert!(root.contains("- `pub(crate) fn is_private(&self) -> bool`\n"));
+    // Trait impl items have no visibility of their own
+    assert!(root.contains("    - `fn default() -> PlainStruct`\n"));
+    let types = fs::read_to_string(output_dir.join("test_crate/types.md")).unwrap();
+    assert!(types.contains("## private test_crate::types::swap_values\n\n*Function*\n"));
+    let inner = fs::read_to_string(output_dir.join("test_crate/nested/inner.md")).unwrap();
+    assert!(
+        inner.contains("## private test_crate::nested::inner::private_function\n\n*Function*\n")
+    );
+
+    fs::remove_dir_all(&output_dir).ok();
+    run_cargo_doc_md(&args).expect("documenting public items should succeed");
+    let root = fs::read_to_string(output_dir.join("test_crate/test_crate.md")).unwrap();
+    assert!(root.contains("## test_crate::PlainStruct\n\n*Struct*\n"));
+    assert!(root.contains("- `name: String`\n"));
+    assert!(root.contains("- `fn get_value(&self) -> i32`\n"));
+    assert!(!root.contains("is_private"));
+}
+
+#[test]
+fn test_visibility_level() {
+    let output_dir = PathBuf::from("target/doc-md-test-visibility");
+    let output = output_dir.to_str().unwrap();
+    let inner_functions = |level: &str| {
+        fs::remove_dir_all(&output_dir).ok();
+        run_cargo_doc_md(&[
+            "--manifest-path",
+            "tests/fixtures/test_crate/Cargo.toml",
+            "--no-deps",
+            "--visibility",
+            level,
+            "-o",
+            output,
+        ])
+        .expect("documenting down to a visibility should succeed");
+        let inner = fs::read_to_string(output_dir.join("test_crate/nested/inner.md")).unwrap();
+        inner
+            .lines()
+            .filter_map(|line| line.strip_prefix("## "))
+            .filter_map(|heading| heading.split_once("test_crate::nested::inner::"))
+            .map(|(_, name)| name)
+            .filter(|name| name.ends_with("_function"))
+            .map(String::from)
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(inner_functions("public"), ["inner_function"]);
+    assert_eq!(
+        inner_functions("crate"),
+        ["crate_function", "inner_function"]
+    );
+    let inner = fs::read_to_string(output_dir.join("test_crate/nested/inner.md")).unwrap();
+    assert!(
+        inner.contains("## pub(crate) test_crate::nested::inner::crate_function\n\n*Function*\n")
+    );
+    assert!(inner.contains("## pub test_crate::nested::inner::inner_function\n\n*Function*\n"));
+
+    assert_eq!(
+        inner_functions("restricted"),
+        [
+            "crate_function",
+            "inner_function",
+            "nested_function",
+            "parent_function"
+        ]
+    );
+    let inner = fs::read_to_string(output_dir.join("test_crate/nested/inner.md")).unwrap();
+    assert!(inner.contains(
+        "## pub(in crate::nested) test_crate::nested::inner::parent_function\n\n*Function*\n"
+    ));
+    assert!(!inner.contains("private_function"));
+
+    let err = run_cargo_doc_md(&["--visibility", "crate", "--include-private", "-o", output])
+        .expect_err("--visibility and --include-private conflict");
+    assert!(err.contains("cannot be used with"));
+}
+
+#[test]
+fn test_targets() {
+    let output_dir = PathBuf::from("target/doc-md-test-targets");
+    fs::remove_dir_all(&output_dir).ok();
+    // Left by an earlier library-only run
+    fs::create_dir_all(output_dir.join("test_crate")).unwrap();
+    fs::write(output_dir.join("test_crate/types.md"), "# stale\n").unwrap();
+
+    run_cargo_doc_md(&[
+        "--manifest-path",
+        "tests/fixtures/test_crate/Cargo.toml",
+        "--no-deps",
+        "--targets",
+        "lib,bins,examples",
+        "--check-links",
+        "-o",
+        output_dir.to_str().unwrap(),
+    ])
+    .expect("documenting every target should succeed");
+
+    let crate_index = fs::read_to_string(output_dir.join("test_crate/index.md")).unwrap();
+    assert!(crate_index.contains("- [Library `test_crate`](lib/index.md)"));
+    assert!(crate_index.contains("- [Binary `test-tool`](bin_test_tool/index.md)"));
+    assert!(crate_index.contains("- [Example `demo`](example_demo/index.md)"));
+    assert!(output_dir.join("test_crate/lib/types.md").exists());
+    let bin = fs::read_to_string(output_dir.join("test_crate/bin_test_tool/test_tool.md")).unwrap();
+    assert!(bin.contains("USAGE_ERROR"));
+    let example = fs::read_to_string(output_dir.join("test_crate/example_demo/demo.md")).unwrap();
+    assert!(example.contains("GREETING"));
+    // Links from other targets into the library point at its directory
+    assert!(example.contains(
+        "[`test_crate::functions::repeat`](../../test_crate/lib/functions.md#test_cratefunctionsrepeat)"
+    ));
+    assert!(!output_dir.join("test_crate/types.md").exists());
+
+    let index = fs::read_to_string(output_dir.join("index.md")).unwrap();
+    assert!(index.contains("(test_crate/index.md)"));
+
+    fs::remove_dir_all(&output_dir).ok();
+}
+
+#[test]
+fn test_targets_frontmatter() {
+    let output_dir = PathBuf::from("target/doc-md-test-targets-frontmatter");
+    fs::remove_dir_all(&output_dir).ok();
+
+    run_cargo_doc_md(&[
+        "--manifest-path",
+        "tests/fixtures/test_crate/Cargo.toml",
+        "--no-deps",
+        "--targets",
+        "lib,examples",
+        "--frontmatter",
+        "-o",
+        output_dir.to_str().unwrap(),
+    ])
+    .expect("documenting the targets should succeed");
+
+    // Each target's slugs follow its own directory, below the crate's index
+    let crate_index = fs::read_to_string(output_dir.join("test_crate/index.md")).unwrap();
+    assert!(crate_index.starts_with(
+        "---\ntitle: \"test_crate\"\nslug: test_crate/index\nsidebar_position: 1\n---\n\n"
+    ));
+    let lib_index = fs::read_to_string(output_dir.join("test_crate/lib/index.md")).unwrap();
+    assert!(lib_index.contains("\nslug: test_crate/lib/index\n"));
+    let example_index =
+        fs::read_to_string(output_dir.join("test_crate/example_demo/index.md")).unwrap();
+    assert!(example_index.contains("\nslug: test_crate/example_demo/index\n"));
+    let index = fs::read_to_string(output_dir.join("index.md")).unwrap();
+    assert!(index.starts_with(
+        "---\ntitle: \"Documentation Index\"\nslug: index\nsidebar_position: 1\n---\n\n"
+    ));
+
+    fs::remove_dir_all(&output_dir).ok();
+}
+
+/// A workspace of crates `a` and `b` at `dir`, where `a` depends on `b` and links to
+/// its items
+fn write_cross_crate_workspace(dir: &std::path::Path) {
+    fs::remove_dir_all(dir).ok();
+    let files = [
+        (
+            "Cargo.toml",
+            "[workspace]\nmembers = [\"a\", \"b\"]\nresolver = \"2\"\n",
+        ),
+        (
+            "a/Cargo.toml",
+            "[package]\nname = \"a\"\nversion = \"0.1.0\"\nedition = \"2021\"\n\n\
+             [dependencies]\nb = { path = \"../b\" }\n",
+        ),
+        (
+            "a/src/lib.rs",
+            "//! Built on [`b::Thing`].\n\n\
+             /// Wraps a [`b::Thing`] made by [`b::thing()`].\npub struct Wrapper(pub b::Thing);\n",
+        ),
+        (
+            "b/Cargo.toml",
+            "[package]\nname = \"b\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+        ),
+        (
+            "b/src/lib.rs",
+            "//! Things.\n\n/// A thing.\npub struct Thing;\n\n/// Makes a thing.\npub fn thing() {}\n\n\
+             /// Not part of the API.\nfn helper() {}\n",
+        ),
+    ];
+    for (path, content) in files {
+        let path = dir.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+}
+
+#[test]
+fn test_cross_crate_links_with_private_items() {
+    let dir = PathBuf::from("target/doc-md-test-cross-crate-private");
+    write_cross_crate_workspace(&dir);
+    let manifest = dir.join("Cargo.toml");
+    let output_dir = dir.join("docs");
+
+    for private_args in [&[][..], &["--include-private"], &["--visibility", "crate"]] {
+        fs::remove_dir_all(&output_dir).ok();
+        let args = [
+            "--manifest-path",
+            manifest.to_str().unwrap(),
+            "--workspace",
+            "--check-links",
+            "-o",
+            output_dir.to_str().unwrap(),
+        ];
+        run_cargo_doc_md(&[&args[..], private_args].concat())
+            .expect("links between the crates should resolve");
+
+        // a is converted before b, and still links to the headings b tells apart by kind
+        let a = fs::read_to_string(output_dir.join("a/a.md")).unwrap();
+        if private_args.is_empty() {
+            assert!(a.contains("[`b::Thing`](../b/b.md#bthing-struct)"));
+            assert!(a.contains("[`b::thing()`](../b/b.md#bthing-function)"));
+        }
+    }
+
+    // Headings in b start with the visibility, and so do the anchors a links to
+    let b = fs::read_to_string(output_dir.join("b/b.md")).unwrap();
+    assert!(b.contains("\n## pub b::Thing (struct)\n"));
+    let a = fs::read_to_string(output_dir.join("a/a.md")).unwrap();
+    assert!(a.contains("[`b::Thing`](../b/b.md#pub-bthing-struct)"));
+    assert!(a.contains("[`b::thing()`](../b/b.md#pub-bthing-function)"));
+
+    fs::remove_dir_all(&dir).ok();
+}
+
+#[test]
+fn test_manifest_path_validation() {
+    let err = run_cargo_doc_md(&["--manifest-path", "README.md", "--no-deps"])
+        .expect_err("a non-manifest file should be rejected");
+    assert!(err.contains("--manifest-path must point at a Cargo.toml file: README.md"));
+
+    let err = run_cargo_doc_md(&["--manifest-path", "missing/Cargo.toml", "--no-deps"])
+        .expect_err("a missing manifest should be rejected");
+    assert!(err.contains("Manifest not found: missing/Cargo.toml"));
+}
+
+#[test]
+fn test_config_file() {
+    let config_dir = PathBuf::from("target/doc-md-test-config");
+    fs::remove_dir_all(&config_dir).ok();
+    fs::create_dir_all(&config_dir).unwrap();
+    let config = config_dir.join("cargo-doc-md.toml");
+    fs::write(
+        &config,
+        r#"# Paths are relative to this file
+json = "../../tests/fixtures/test_crate.json"
+output = "docs"
+single-file = true  # one file per crate
+exclude = [
+    "test_crate::collisions::*",
+    'test_crate::functions::*', # trailing commas are fine
+]
+no-footer = false
+"#,
+    )
+    .unwrap();
+
+    run_cargo_doc_md(&["--config", config.to_str().unwrap()]).expect("config run should succeed");
+    let markdown = fs::read_to_string(config_dir.join("docs/test_crate.md")).unwrap();
+    assert!(markdown.contains("## test_crate::types::Pair"));
+    assert!(!markdown.contains("## test_crate::functions::"));
+    assert!(!markdown.contains("## test_crate::collisions::"));
+
+    // Options on the command line win, as do options that conflict with the file's
+    let json_dir = config_dir.join("json");
+    fs::create_dir_all(&json_dir).unwrap();
+    fs::copy(
+        "tests/fixtures/test_crate.json",
+        json_dir.join("test_crate.json"),
+    )
+    .unwrap();
+    let cli_output = config_dir.join("cli-docs");
+    run_cargo_doc_md(&[
+        "--config",
+        config.to_str().unwrap(),
+        "--json-dir",
+        json_dir.to_str().unwrap(),
+        "-o",
+        cli_output.to_str().unwrap(),
+        "--exclude",
+        "test_crate::types::*",
+    ])
+    .expect("overridden run should succeed");
+    let markdown = fs::read_to_string(cli_output.join("test_crate.md")).unwrap();
+    assert!(!markdown.contains("## test_crate::types::Pair"));
+    assert!(markdown.contains("## test_crate::functions::"));
+
+    // Found next to Cargo.toml without --config
+    fs::write(
+        config_dir.join("Cargo.toml"),
+        "[package]\nname = \"docs\"\n",
+    )
+    .unwrap();
+    fs::remove_dir_all(config_dir.join("docs")).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_cargo-doc-md"))
+        .current_dir(&config_dir)
+        .output()
+        .expect("Failed to execute command");
+    assert!(output.status.success());
+    assert!(config_dir.join("docs/test_crate.md").exists());
+
+    fs::write(&config, "json = 'test.json'\ncolour = \"always\"\n").unwrap();
+    let err = run_cargo_doc_md(&["--config", config.to_str().unwrap()]).unwrap_err();
+    assert!(err.contains("cargo-doc-md.toml:2: unknown option `colour`"));
+
+    fs::write(&config, "[output]\ndir = \"docs\"\n").unwrap();
+    let err = run_cargo_doc_md(&["--config", config.to_str().unwrap()]).unwrap_err();
+    assert!(err.contains("line 1: tables aren't supported"));
+
+    fs::remove_dir_all(&config_dir).ok();
+}
@@ -0,0 +1,8 @@
+//! Shows the basics of `test_crate`.
+
+/// Greeting printed by the demo, twice over with [`test_crate::functions::repeat`].
+pub const GREETING: &str = "hello";
+
+fn main() {
+    println!("{}", test_crate::functions::repeat(GREETING, 2));
+}
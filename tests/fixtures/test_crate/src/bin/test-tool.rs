@@ -0,0 +1,12 @@
+//! Command-line front end for `test_crate`.
+
+/// Exit code when the arguments can't be parsed.
+pub const USAGE_ERROR: i32 = 2;
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.is_empty() {
+        std::process::exit(USAGE_ERROR);
+    }
+    println!("{}", test_crate::functions::repeat(&args[0], 2));
+}
@@ -0,0 +1,18 @@
+//! Items sharing a name across namespaces.
+//!
+//! Types, values, and modules live in separate namespaces, so all three of
+//! these coexist.
+
+/// A struct named like the function and module below.
+pub struct Foo;
+
+/// A function named like the struct and module.
+pub fn foo() -> Foo {
+    Foo
+}
+
+/// A module named like the struct and function.
+pub mod foo {
+    /// A function inside the `foo` module.
+    pub fn bar() {}
+}
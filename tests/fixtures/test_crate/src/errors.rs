@@ -2,6 +2,10 @@ use std::error::Error as StdError;
 use std::fmt;
 use std::io;
 
+/// Error type for the example crate.
+///
+/// I/O failures are wrapped from [`io::Error`] via [`From`], and every variant
+/// implements [`std::error::Error`][StdError].
 #[derive(Debug)]
 pub enum CustomError {
     NotFound,
@@ -19,6 +19,8 @@ use std::ops::Mul;
 ///
 /// assert_eq!(add(2, 3), 5);
 /// ```
+#[doc(alias = "sum")]
+#[doc(alias = "plus")]
 pub fn add(a: i32, b: i32) -> i32 {
     a + b
 }
@@ -44,6 +46,7 @@ pub fn multiply<T: Mul<Output = T> + Copy>(a: T, b: T) -> T {
 /// # Returns
 ///
 /// A new `Vec<u8>` containing a copy of the input data.
+#[must_use = "the input is left untouched; use the returned copy"]
 pub fn process_slice(data: &[u8]) -> Vec<u8> {
     data.to_vec()
 }
@@ -168,3 +171,172 @@ where
 {
     format!("t: {}, u: {:?}", t, u)
 }
+
+/// Adds two numbers using the original implementation.
+#[deprecated(since = "0.1.0", note = "use `add` instead")]
+pub fn old_add(a: i32, b: i32) -> i32 {
+    a + b
+}
+
+/// Multiplies two numbers using the original implementation.
+#[deprecated]
+pub fn old_multiply(a: i32, b: i32) -> i32 {
+    a * b
+}
+
+/// Formats a value with extra decoration.
+#[cfg(feature = "extras")]
+#[doc(cfg(feature = "extras"))]
+pub fn decorated(value: i32) -> String {
+    format!("** {} **", value)
+}
+
+/// Reads a value from the platform's preferred location.
+#[cfg(all(unix, feature = "extras"))]
+#[doc(cfg(all(unix, any(feature = "extras", not(windows)))))]
+pub fn platform_value() -> i32 {
+    42
+}
+
+/// Accepts an argument it never looks at.
+pub fn ignored_argument(_: i32) -> bool {
+    true
+}
+
+/// Doubles a value; callable from C.
+pub extern "C" fn c_callback(value: i32) -> i32 {
+    value * 2
+}
+
+/// Reads a byte at compile time or run time without checking the pointer.
+///
+/// # Safety
+///
+/// `ptr` must be valid for reads.
+pub const unsafe fn read_unchecked(ptr: *const u8) -> u8 {
+    unsafe { *ptr }
+}
+
+unsafe extern "C" {
+    /// Formats and prints C-style, with a variable number of arguments.
+    pub fn printf(format: *const std::ffi::c_char, ...) -> std::ffi::c_int;
+}
+
+/// Yields the even numbers below `limit`.
+pub fn evens(limit: u8) -> impl Iterator<Item = u8> {
+    (0..limit).filter(|n| n % 2 == 0)
+}
+
+/// Formats any displayable, cloneable value twice.
+pub fn describe_twice(value: impl std::fmt::Display + Clone) -> String {
+    format!("{} {}", value.clone(), value)
+}
+
+/// Splits text on whitespace without copying.
+pub fn words<'a>(text: &'a str) -> impl Iterator<Item = &'a str> + use<'a> {
+    text.split_whitespace()
+}
+
+/**
+    Sums the bytes of `data`, wrapping on overflow.
+
+    ```
+    use test_crate::functions::checksum;
+
+    assert_eq!(checksum(&[1, 2, 3]), 6);
+    ```
+
+    The same loop, written out:
+
+        let mut sum = 0u8;
+        for byte in data {
+            sum = sum.wrapping_add(*byte);
+        }
+*/
+pub fn checksum(data: &[u8]) -> u8 {
+    data.iter().fold(0, |sum, byte| sum.wrapping_add(*byte))
+}
+
+#[doc = "   Repeats `text` `times` times."]
+#[doc = ""]
+#[doc = "   ```"]
+#[doc = "   use test_crate::functions::repeat;"]
+#[doc = ""]
+#[doc = "   assert_eq!(repeat(\"ab\", 2), \"abab\");"]
+#[doc = "   ```"]
+pub fn repeat(text: &str, times: usize) -> String {
+    text.repeat(times)
+}
+
+/// Deletes the file at `path`.
+///
+/// > [!WARNING]
+/// > The file is removed immediately, without going through the trash.
+///
+/// > [!NOTE]
+/// > Missing files are not an error; see [`checksum`] for a read-only helper.
+pub fn remove_file(path: &str) -> bool {
+    !path.is_empty()
+}
+
+/// Boxes `value` as a trait object that can cross threads.
+pub fn boxed_display(value: u32) -> Box<dyn std::fmt::Display + Send + 'static> {
+    Box::new(value)
+}
+
+/// Writes `value` to a string, whatever its concrete type.
+pub fn display_to_string(value: &(dyn std::fmt::Display + Sync)) -> String {
+    value.to_string()
+}
+
+/// Splits `input` at the first `=` into a key and a value.
+///
+/// ```
+/// # use test_crate::functions::parse_pair;
+/// # fn main() -> Option<()> {
+/// let (key, value) = parse_pair("name=ferris")?;
+/// ##[derive(Debug)]
+/// struct Entry<'a>(&'a str, &'a str);
+/// println!("{:?}", Entry(key, value));
+/// #
+/// # Some(())
+/// # }
+/// ```
+///
+/// Only Rust examples have hidden lines:
+///
+/// ```sh
+/// # prints the pair
+/// echo "name=ferris"
+/// ```
+pub fn parse_pair(input: &str) -> Option<(&str, &str)> {
+    input.split_once('=')
+}
+
+/// Reads the config file at `path`.
+///
+/// # Returns
+///
+/// The file's contents, with line endings
+/// normalized to `\n`.
+///
+/// # Errors
+///
+/// - [`std::io::ErrorKind::NotFound`] if there is no file at `path`
+/// - Any other error from [`std::fs::read_to_string`]
+///
+/// # Panics
+///
+/// Panics if `path` is empty.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use test_crate::functions::read_config;
+/// let config = read_config("app.toml")?;
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub fn read_config(path: &str) -> std::io::Result<String> {
+    assert!(!path.is_empty(), "path is empty");
+    Ok(std::fs::read_to_string(path)?.replace("\r\n", "\n"))
+}
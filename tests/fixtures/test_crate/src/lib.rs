@@ -1,4 +1,6 @@
+#![feature(doc_cfg)]
 #![feature(trait_alias)]
+#![feature(negative_impls)]
 
 //! # Test Crate
 //!
@@ -15,6 +17,9 @@
 //! - **Error handling**: Custom error types with `std::error::Error`
 //! - **Documentation**: Rich markdown with examples, links, and code blocks
 //!
+//! Start with [`PlainStruct`] and the [`Builder`], or see [`types::Container`] for
+//! generics.
+//!
 //! ## Quick Start
 //!
 //! ```rust
@@ -43,7 +48,8 @@
 //!
 //! ## Feature Flags
 //!
-//! This crate has no feature flags but demonstrates documentation of them.
+//! The `extras` feature (enabled by default) gates a few functions to
+//! demonstrate documentation of feature-gated items.
 //!
 //! ## Safety
 //!
@@ -57,6 +63,7 @@ pub mod lifetimes;
 pub mod patterns;
 pub mod async_example;
 pub mod errors;
+pub mod collisions;
 
 use std::fmt;
 
@@ -138,6 +145,14 @@ impl PlainStruct {
     pub fn set_value(&mut self, value: i32) {
         self.value = value;
     }
+
+    pub(crate) fn is_private(&self) -> bool {
+        self.private_field
+    }
+
+    fn reset(&mut self) {
+        self.value = 0;
+    }
 }
 
 impl Default for PlainStruct {
@@ -244,8 +259,14 @@ impl SimpleEnum {
 
 pub enum ComplexEnum {
     Unit,
+    /// A name and a count, by position.
     Tuple(String, i32),
-    Struct { name: String, age: u32 },
+    /// A person, by name and age.
+    Struct {
+        /// Full name, as written.
+        name: String,
+        age: u32,
+    },
 }
 
 impl ComplexEnum {
@@ -303,6 +324,20 @@ impl MyTrait for PlainStruct {
     }
 }
 
+/// Borrowed structs describe themselves too, so `&PlainStruct` can be passed where a
+/// `MyTrait` is expected.
+impl MyTrait for &PlainStruct {
+    fn required_method(&self) -> String {
+        (*self).required_method()
+    }
+}
+
+impl MyTrait for [PlainStruct; 2] {
+    fn required_method(&self) -> String {
+        format!("{} and {}", self[0].name, self[1].name)
+    }
+}
+
 impl MyTrait for String {
     fn required_method(&self) -> String {
         self.clone()
@@ -331,6 +366,19 @@ pub fn generic_function<T: fmt::Display>(item: T) -> String {
     format!("Item: {}", item)
 }
 
+/// A value paired with a display label.
+pub struct Labeled<T>(pub T);
+
+impl<T: fmt::Display> Labeled<T>
+where
+    Self: Sized,
+{
+    /// Render the value with its label.
+    pub fn label(&self) -> String {
+        format!("label: {}", self.0)
+    }
+}
+
 pub fn multiple_bounds<T>(item: T) -> String
 where
     T: fmt::Display + fmt::Debug + Clone,
@@ -369,6 +417,7 @@ macro_rules! create_struct {
     };
 }
 
+/// Returns the largest of its arguments.
 #[macro_export]
 macro_rules! max {
     ($x:expr) => ($x);
@@ -20,6 +20,15 @@ pub struct DoubleBorrow<'a, 'b> {
     pub second: &'b str,
 }
 
+impl<'a, 'b> DoubleBorrow<'a, 'b>
+where
+    'b: 'a,
+{
+    pub fn second_as_first(&self) -> &'a str {
+        self.second
+    }
+}
+
 pub struct LifetimeWithBound<'a, T: 'a> {
     pub reference: &'a T,
 }
@@ -26,6 +26,26 @@ pub mod inner {
         "inner"
     }
 
+    /// Visible to the whole crate.
+    pub(crate) fn crate_function() -> &'static str {
+        "crate"
+    }
+
+    /// Visible to `nested`, the parent module.
+    pub(super) fn parent_function() -> &'static str {
+        "parent"
+    }
+
+    /// Visible to `nested` and the modules in it.
+    pub(in crate::nested) fn nested_function() -> &'static str {
+        "nested"
+    }
+
+    /// Visible only inside `inner`.
+    fn private_function() -> &'static str {
+        "private"
+    }
+
     /// Deeply nested module.
     pub mod deep {
         /// A struct in the deeply nested module.
@@ -71,6 +91,7 @@ pub mod inner {
 }
 
 pub use inner::InnerStruct;
+pub use inner::deep::*;
 
 /// An outer struct that contains an inner struct.
 pub struct OuterStruct {
@@ -67,6 +67,7 @@ pub trait DefaultImpl {
 
 impl<T> DefaultImpl for T {}
 
+/// A trait only this crate can implement, through an unnameable supertrait.
 pub trait Sealed: private::SealedTrait {}
 
 mod private {
@@ -85,6 +86,20 @@ pub trait SuperTrait: Clone + fmt::Debug {
     fn super_method(&self);
 }
 
+/// A value that can be copied around and described alongside borrowed context.
+pub trait Describe<'a, T>: Clone + fmt::Debug + 'a
+where
+    T: fmt::Display + 'a,
+{
+    fn describe(&self, context: T) -> String;
+}
+
+/// Work that runs once and is handed over boxed.
+pub trait Runnable {
+    /// Runs the work, consuming it.
+    fn run(self: Box<Self>) -> String;
+}
+
 pub trait Associated {
     type Assoc: fmt::Display + Clone;
 
@@ -100,3 +115,65 @@ impl Associated for AssociatedImpl {
         "associated".to_string()
     }
 }
+
+/// A collection that lends out views borrowing from itself.
+pub trait Lending {
+    /// A view of one element, borrowing from the collection.
+    type Item<'a>: fmt::Debug
+    where
+        Self: 'a;
+
+    /// The collection's size, for collections that know it.
+    type Len: Copy + Into<usize>
+    where
+        Self: Sized;
+
+    fn lend(&self, index: usize) -> Option<Self::Item<'_>>;
+}
+
+impl Lending for Vec<String> {
+    type Item<'a> = &'a str;
+    type Len = u8;
+
+    fn lend(&self, index: usize) -> Option<Self::Item<'_>> {
+        self.get(index).map(String::as_str)
+    }
+}
+
+/// Types for which the all-zero bit pattern is a valid value.
+///
+/// # Safety
+///
+/// Implementors must not contain references, `NonNull`, or other types that forbid zero.
+pub unsafe trait Zeroable: Sized {
+    /// Returns the all-zero value.
+    fn zeroed() -> Self;
+}
+
+/// A counter that starts from zero.
+pub struct Counter {
+    pub count: u64,
+}
+
+unsafe impl Zeroable for Counter {
+    fn zeroed() -> Self {
+        Counter { count: 0 }
+    }
+}
+
+/// A heap buffer owned through a raw pointer.
+pub struct RawBuffer {
+    ptr: *mut u8,
+    len: usize,
+}
+
+// The buffer is uniquely owned, so moving it to another thread is sound
+unsafe impl Send for RawBuffer {}
+
+impl RawBuffer {
+    /// Returns the buffer's length in bytes.
+    pub fn len(&self) -> usize {
+        let _ = self.ptr;
+        self.len
+    }
+}
@@ -7,6 +7,9 @@
 //! - Structs with lifetime parameters
 
 use std::collections::HashMap;
+use std::fmt;
+use std::pin::Pin;
+use std::rc::Rc;
 
 /// A generic container for items of type `T`.
 ///
@@ -83,6 +86,33 @@ impl<'a> RefStruct<'a> {
     }
 }
 
+/// A unit of work that is handed off through smart pointers.
+pub struct Job {
+    /// Human-readable name of the job.
+    pub name: String,
+}
+
+impl Job {
+    /// Runs the job, consuming its allocation.
+    pub fn consume(self: Box<Self>) -> String {
+        self.name
+    }
+
+    /// Returns a handle to a job that is already shared.
+    pub fn share(self: Rc<Self>) -> Rc<Self> {
+        self
+    }
+
+    /// Advances a pinned job by one step.
+    pub fn step(self: Pin<&mut Self>) {}
+}
+
+impl crate::traits::Runnable for Job {
+    fn run(self: Box<Self>) -> String {
+        self.consume()
+    }
+}
+
 /// Represents the status of an operation.
 ///
 /// This enum demonstrates:
@@ -94,7 +124,10 @@ pub enum Status {
     /// The operation is idle and waiting to start.
     Idle,
     /// The operation is running with progress information.
-    Running { progress: f32 },
+    Running {
+        /// Fraction of the work done, from `0.0` to `1.0`.
+        progress: f32,
+    },
     /// The operation completed successfully.
     Completed,
     /// The operation failed with an error message.
@@ -127,6 +160,50 @@ impl Default for Status {
     }
 }
 
+/// A fixed-capacity buffer whose size is part of its type.
+pub struct FixedBuffer<'a, T, const N: usize = 4> {
+    /// Backing storage.
+    pub data: [T; N],
+    /// Label shown in debug output.
+    pub label: &'a str,
+}
+
+impl<'a, T: Copy + Default, const N: usize> FixedBuffer<'a, T, N> {
+    /// Creates a buffer filled with default values.
+    pub fn new(label: &'a str) -> Self {
+        Self {
+            data: [T::default(); N],
+            label,
+        }
+    }
+
+    /// Returns the buffer's capacity, `N`.
+    pub fn capacity(&self) -> usize {
+        N
+    }
+}
+
+/// Copies the first `N` items into an array, if there are enough.
+pub fn first_n<T: Copy, const N: usize>(items: &[T]) -> Option<[T; N]> {
+    items.get(..N)?.try_into().ok()
+}
+
+/// Priority levels with explicit discriminants.
+///
+/// Variants without an explicit value continue counting from the previous one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum Priority {
+    /// Handle whenever convenient.
+    Low = 1,
+    /// Handle soon.
+    Normal,
+    /// Handle before anything else.
+    High = 10,
+    /// Drop everything.
+    Critical = 0xFF,
+}
+
 /// A type alias for a string-to-string map.
 ///
 /// Commonly used for configuration and metadata.
@@ -141,7 +218,15 @@ pub const DEFAULT_CAPACITY: usize = 10;
 /// The maximum number of retries.
 pub const MAX_RETRIES: u32 = 3;
 
+/// Capacity for twice the default, computed at compile time.
+pub const DOUBLE_CAPACITY: usize = DEFAULT_CAPACITY * 2;
+
 /// A pair of related values.
+///
+/// Holds two values where a [`Container`] holds many; see also
+/// [crate::types::Status] and [a buffer](FixedBuffer). Helpers like
+/// [`swap_values`] and [the checker](crate::types::check_pair) are internal and
+/// not documented, and [`Triple`] does not exist.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Pair<T, U> {
     pub first: T,
@@ -174,3 +259,70 @@ impl<T, U> From<Pair<T, U>> for (T, U) {
         (pair.first, pair.second)
     }
 }
+
+/// A value together with the name of where it came from.
+#[derive(Debug)]
+pub struct Tagged<T> {
+    /// The value itself
+    pub value: T,
+    /// Where the value came from
+    pub source: &'static str,
+}
+
+impl<T> From<T> for Tagged<T> {
+    fn from(value: T) -> Self {
+        Tagged {
+            value,
+            source: "unknown",
+        }
+    }
+}
+
+impl<T, U> PartialEq<Tagged<U>> for Tagged<T>
+where
+    T: PartialEq<U>,
+{
+    fn eq(&self, other: &Tagged<U>) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Tagged<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (from {})", self.value, self.source)
+    }
+}
+
+/// A color as red, green, and blue channels.
+#[must_use]
+pub struct Rgb(
+    /// Red channel.
+    pub u8,
+    /// Green channel.
+    pub u8,
+    pub u8,
+);
+
+fn swap_values<T, U>(pair: Pair<T, U>) -> Pair<U, T> {
+    pair.swap()
+}
+
+fn check_pair<T: PartialEq>(pair: &Pair<T, T>) -> bool {
+    let _ = swap_values::<u8, u8>;
+    pair.first == pair.second
+}
+
+/// A handle that must stay on the thread that created it.
+pub struct ThreadBound {
+    pub id: u64,
+}
+
+impl !Send for ThreadBound {}
+
+impl !Sync for ThreadBound {}
+
+impl Clone for ThreadBound {
+    fn clone(&self) -> Self {
+        ThreadBound { id: self.id }
+    }
+}
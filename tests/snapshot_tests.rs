@@ -1,3 +1,4 @@
+use cargo_doc_md::converter::{ItemOrder, MarkdownOptions};
 use cargo_doc_md::{ConversionOptions, converter, parser};
 use std::path::Path;
 
@@ -5,7 +6,7 @@ use std::path::Path;
 fn test_multifile_output_structure() {
     let json_path = Path::new("tests/fixtures/test_crate.json");
     let crate_data = parser::load_rustdoc_json(json_path).expect("Failed to load JSON");
-    let output = converter::convert_to_markdown_multifile(&crate_data, false)
+    let output = converter::convert_to_markdown_multifile(&crate_data, &MarkdownOptions::default())
         .expect("Failed to convert to markdown");
 
     let mut file_paths: Vec<_> = output.files.keys().collect();
@@ -19,7 +20,7 @@ fn test_multifile_output_structure() {
 fn test_index_file() {
     let json_path = Path::new("tests/fixtures/test_crate.json");
     let crate_data = parser::load_rustdoc_json(json_path).expect("Failed to load JSON");
-    let output = converter::convert_to_markdown_multifile(&crate_data, false)
+    let output = converter::convert_to_markdown_multifile(&crate_data, &MarkdownOptions::default())
         .expect("Failed to convert to markdown");
 
     let index_content = output.files.get("index.md").expect("index.md not found");
@@ -30,7 +31,7 @@ fn test_index_file() {
 fn test_lib_module() {
     let json_path = Path::new("tests/fixtures/test_crate.json");
     let crate_data = parser::load_rustdoc_json(json_path).expect("Failed to load JSON");
-    let output = converter::convert_to_markdown_multifile(&crate_data, false)
+    let output = converter::convert_to_markdown_multifile(&crate_data, &MarkdownOptions::default())
         .expect("Failed to convert to markdown");
 
     if let Some(lib_content) = output.files.get("test_crate.md") {
@@ -42,7 +43,7 @@ fn test_lib_module() {
 fn test_types_module() {
     let json_path = Path::new("tests/fixtures/test_crate.json");
     let crate_data = parser::load_rustdoc_json(json_path).expect("Failed to load JSON");
-    let output = converter::convert_to_markdown_multifile(&crate_data, false)
+    let output = converter::convert_to_markdown_multifile(&crate_data, &MarkdownOptions::default())
         .expect("Failed to convert to markdown");
 
     if let Some(types_content) = output.files.get("types.md") {
@@ -54,7 +55,7 @@ fn test_types_module() {
 fn test_functions_module() {
     let json_path = Path::new("tests/fixtures/test_crate.json");
     let crate_data = parser::load_rustdoc_json(json_path).expect("Failed to load JSON");
-    let output = converter::convert_to_markdown_multifile(&crate_data, false)
+    let output = converter::convert_to_markdown_multifile(&crate_data, &MarkdownOptions::default())
         .expect("Failed to convert to markdown");
 
     if let Some(functions_content) = output.files.get("functions.md") {
@@ -62,11 +63,23 @@ fn test_functions_module() {
     }
 }
 
+#[test]
+fn test_traits_module() {
+    let json_path = Path::new("tests/fixtures/test_crate.json");
+    let crate_data = parser::load_rustdoc_json(json_path).expect("Failed to load JSON");
+    let output = converter::convert_to_markdown_multifile(&crate_data, &MarkdownOptions::default())
+        .expect("Failed to convert to markdown");
+
+    if let Some(traits_content) = output.files.get("traits.md") {
+        insta::assert_snapshot!("traits_module", traits_content);
+    }
+}
+
 #[test]
 fn test_nested_modules() {
     let json_path = Path::new("tests/fixtures/test_crate.json");
     let crate_data = parser::load_rustdoc_json(json_path).expect("Failed to load JSON");
-    let output = converter::convert_to_markdown_multifile(&crate_data, false)
+    let output = converter::convert_to_markdown_multifile(&crate_data, &MarkdownOptions::default())
         .expect("Failed to convert to markdown");
 
     if let Some(nested_content) = output.files.get("nested.md") {
@@ -82,11 +95,35 @@ fn test_nested_modules() {
     }
 }
 
+#[test]
+fn test_nested_module_breadcrumbs() {
+    let json_path = Path::new("tests/fixtures/test_crate.json");
+    let crate_data = parser::load_rustdoc_json(json_path).expect("Failed to load JSON");
+    let output = converter::convert_to_markdown_multifile(&crate_data, &MarkdownOptions::default())
+        .expect("Failed to convert to markdown");
+
+    let breadcrumb = |file: &str| output.files[file].lines().next().unwrap().to_string();
+    // Each link climbs only as far as the file's own directory depth requires
+    let breadcrumbs = [
+        "test_crate.md",
+        "nested.md",
+        "nested/inner.md",
+        "nested/inner/deep.md",
+        "nested/inner/deep/deeper.md",
+    ]
+    .map(|file| format!("{}: {}", file, breadcrumb(file)))
+    .join("\n");
+    assert!(breadcrumbs.contains(
+        "nested/inner/deep.md: **[test_crate](../../index.md) › [nested](../../nested.md) › [inner](../inner.md) › deep**"
+    ));
+    insta::assert_snapshot!("nested_module_breadcrumbs", breadcrumbs);
+}
+
 #[test]
 fn test_struct_formatting() {
     let json_path = Path::new("tests/fixtures/test_crate.json");
     let crate_data = parser::load_rustdoc_json(json_path).expect("Failed to load JSON");
-    let output = converter::convert_to_markdown_multifile(&crate_data, false)
+    let output = converter::convert_to_markdown_multifile(&crate_data, &MarkdownOptions::default())
         .expect("Failed to convert to markdown");
 
     let lib_content = output
@@ -94,11 +131,20 @@ fn test_struct_formatting() {
         .get("test_crate.md")
         .expect("lib module not found");
 
-    assert!(lib_content.contains("## test_crate::UnitStruct"));
-    assert!(lib_content.contains("**Unit Struct**"));
+    let unit = item_section(lib_content, "## test_crate::UnitStruct\n");
+    assert!(unit.contains("```rust\npub struct UnitStruct;\n```"));
+
+    let tuple = item_section(lib_content, "## test_crate::TupleStruct\n");
+    assert!(tuple.contains("```rust\npub struct TupleStruct(pub String, pub i32);\n```"));
+    assert!(tuple.contains("- **.0**: `String` (pub)\n- **.1**: `i32` (pub)\n"));
+    insta::assert_snapshot!("tuple_struct", tuple);
 
-    assert!(lib_content.contains("## test_crate::TupleStruct"));
-    assert!(lib_content.contains("**Tuple Struct**"));
+    let types_content = &output.files["types.md"];
+    let rgb = item_section(types_content, "## test_crate::types::Rgb\n");
+    assert!(rgb.contains("pub struct Rgb(pub u8, pub u8, pub u8);"));
+    assert!(rgb.contains("- **.0**: `u8` (pub) - Red channel.\n"));
+    assert!(rgb.contains("- **.2**: `u8` (pub)\n"));
+    insta::assert_snapshot!("documented_tuple_struct", rgb);
 
     assert!(lib_content.contains("## test_crate::PlainStruct"));
     assert!(lib_content.contains("**Fields:**"));
@@ -108,7 +154,7 @@ fn test_struct_formatting() {
 fn test_generic_struct_formatting() {
     let json_path = Path::new("tests/fixtures/test_crate.json");
     let crate_data = parser::load_rustdoc_json(json_path).expect("Failed to load JSON");
-    let output = converter::convert_to_markdown_multifile(&crate_data, false)
+    let output = converter::convert_to_markdown_multifile(&crate_data, &MarkdownOptions::default())
         .expect("Failed to convert to markdown");
 
     let lib_content = output
@@ -124,7 +170,7 @@ fn test_generic_struct_formatting() {
 fn test_enum_formatting() {
     let json_path = Path::new("tests/fixtures/test_crate.json");
     let crate_data = parser::load_rustdoc_json(json_path).expect("Failed to load JSON");
-    let output = converter::convert_to_markdown_multifile(&crate_data, false)
+    let output = converter::convert_to_markdown_multifile(&crate_data, &MarkdownOptions::default())
         .expect("Failed to convert to markdown");
 
     let lib_content = output
@@ -142,7 +188,7 @@ fn test_enum_formatting() {
 fn test_function_formatting() {
     let json_path = Path::new("tests/fixtures/test_crate.json");
     let crate_data = parser::load_rustdoc_json(json_path).expect("Failed to load JSON");
-    let output = converter::convert_to_markdown_multifile(&crate_data, false)
+    let output = converter::convert_to_markdown_multifile(&crate_data, &MarkdownOptions::default())
         .expect("Failed to convert to markdown");
 
     let functions_content = output
@@ -158,7 +204,7 @@ fn test_function_formatting() {
 fn test_trait_formatting() {
     let json_path = Path::new("tests/fixtures/test_crate.json");
     let crate_data = parser::load_rustdoc_json(json_path).expect("Failed to load JSON");
-    let output = converter::convert_to_markdown_multifile(&crate_data, false)
+    let output = converter::convert_to_markdown_multifile(&crate_data, &MarkdownOptions::default())
         .expect("Failed to convert to markdown");
 
     let lib_content = output
@@ -174,7 +220,7 @@ fn test_trait_formatting() {
 fn test_methods_and_impls() {
     let json_path = Path::new("tests/fixtures/test_crate.json");
     let crate_data = parser::load_rustdoc_json(json_path).expect("Failed to load JSON");
-    let output = converter::convert_to_markdown_multifile(&crate_data, false)
+    let output = converter::convert_to_markdown_multifile(&crate_data, &MarkdownOptions::default())
         .expect("Failed to convert to markdown");
 
     let lib_content = output
@@ -189,7 +235,7 @@ fn test_methods_and_impls() {
 fn test_constants_and_type_aliases() {
     let json_path = Path::new("tests/fixtures/test_crate.json");
     let crate_data = parser::load_rustdoc_json(json_path).expect("Failed to load JSON");
-    let output = converter::convert_to_markdown_multifile(&crate_data, false)
+    let output = converter::convert_to_markdown_multifile(&crate_data, &MarkdownOptions::default())
         .expect("Failed to convert to markdown");
 
     let lib_content = output
@@ -206,9 +252,14 @@ fn test_include_private() {
     let json_path = Path::new("tests/fixtures/test_crate.json");
     let crate_data = parser::load_rustdoc_json(json_path).expect("Failed to load JSON");
 
-    let output_public = converter::convert_to_markdown_multifile(&crate_data, false)
-        .expect("Failed to convert to markdown");
-    let output_private = converter::convert_to_markdown_multifile(&crate_data, true)
+    let output_public =
+        converter::convert_to_markdown_multifile(&crate_data, &MarkdownOptions::default())
+            .expect("Failed to convert to markdown");
+    let private_options = MarkdownOptions {
+        include_private: true,
+        ..Default::default()
+    };
+    let output_private = converter::convert_to_markdown_multifile(&crate_data, &private_options)
         .expect("Failed to convert to markdown");
 
     let public_count = output_public.files.len();
@@ -226,17 +277,128 @@ fn test_conversion_end_to_end() {
     let _ = std::fs::remove_dir_all(&output_dir);
 
     let json_path = Path::new("tests/fixtures/test_crate.json");
-    let options = ConversionOptions {
-        input_path: json_path,
-        output_dir: &output_dir,
-        include_private: false,
-    };
+    let options = ConversionOptions::new(json_path, &output_dir);
 
-    cargo_doc_md::convert_json_file(&options).expect("Conversion failed");
+    let output = cargo_doc_md::convert_json_file(&options).expect("Conversion failed");
 
     assert!(output_dir.join("test_crate").exists());
     assert!(output_dir.join("test_crate/index.md").exists());
+    assert!(output.files.is_empty());
+    assert!(output.file_sizes.contains_key("index.md"));
+
+    std::fs::remove_dir_all(&output_dir).ok();
+}
+
+#[test]
+fn test_conversion_into_custom_crate_dir() {
+    let output_dir = std::env::temp_dir().join("cargo_doc_md_test_crate_dir");
+    let _ = std::fs::remove_dir_all(&output_dir);
+
+    let mut options =
+        ConversionOptions::new(Path::new("tests/fixtures/test_crate.json"), &output_dir);
+    options.crate_dir = Some("test_crate-0.1");
+
+    cargo_doc_md::convert_json_file(&options).expect("Conversion failed");
+
+    assert!(output_dir.join("test_crate-0.1/index.md").exists());
+    assert!(!output_dir.join("test_crate").exists());
+
+    std::fs::remove_dir_all(&output_dir).ok();
+}
+
+#[test]
+fn test_convert_json_file_with_writer() {
+    let output_dir = std::env::temp_dir().join("cargo_doc_md_test_writer");
+    let _ = std::fs::remove_dir_all(&output_dir);
+
+    let json_path = Path::new("tests/fixtures/test_crate.json");
+    let mut options = ConversionOptions::new(json_path, &output_dir);
+    options.keep_content = true;
+
+    let mut seen = Vec::new();
+    let output = cargo_doc_md::convert_json_file_with_writer(&options, |path, markdown| {
+        seen.push(path.to_path_buf());
+        format!("<!-- {} -->\n{}", path.display(), markdown)
+    })
+    .expect("Conversion failed");
+
+    assert_eq!(seen.len(), output.files.len());
+    assert_eq!(seen[0], Path::new("test_crate/index.md"));
+
+    let index = std::fs::read_to_string(output_dir.join("test_crate/index.md")).unwrap();
+    assert!(index.starts_with("<!-- test_crate/index.md -->\n"));
+    assert!(!output.files["index.md"].starts_with("<!--"));
+    assert_eq!(
+        output.file_sizes["index.md"],
+        output.files["index.md"].len()
+    );
+
+    std::fs::remove_dir_all(&output_dir).ok();
+}
+
+#[test]
+fn test_convert_directory() {
+    use std::io::Write;
+
+    let input_dir = std::env::temp_dir().join("cargo_doc_md_test_directory_input");
+    let output_dir = std::env::temp_dir().join("cargo_doc_md_test_directory_output");
+    let _ = std::fs::remove_dir_all(&input_dir);
+    let _ = std::fs::remove_dir_all(&output_dir);
+    std::fs::create_dir_all(&input_dir).unwrap();
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::fast());
+    encoder
+        .write_all(&std::fs::read("tests/fixtures/test_crate.json").unwrap())
+        .unwrap();
+    std::fs::write(
+        input_dir.join("test_crate.json.gz"),
+        encoder.finish().unwrap(),
+    )
+    .unwrap();
+    std::fs::write(input_dir.join("notes.txt"), "not rustdoc").unwrap();
+    std::fs::create_dir_all(output_dir.join("test_crate")).unwrap();
+    std::fs::write(output_dir.join("test_crate/stale.md"), "old").unwrap();
+
+    let outputs =
+        cargo_doc_md::convert_directory(&input_dir, &output_dir, &MarkdownOptions::default())
+            .expect("Conversion failed");
+
+    assert_eq!(outputs.len(), 1);
+    assert!(output_dir.join("test_crate/types.md").is_file());
+    assert!(!output_dir.join("test_crate/stale.md").exists());
+    let index = std::fs::read_to_string(output_dir.join("index.md")).unwrap();
+    assert!(index.contains("## Crates (1)"));
+    assert!(index.contains("- [`test_crate`](test_crate/index.md) - "));
+
+    let options = MarkdownOptions {
+        frontmatter: true,
+        ..Default::default()
+    };
+    cargo_doc_md::convert_directory(&input_dir, &output_dir, &options).expect("Conversion failed");
+    let index = std::fs::read_to_string(output_dir.join("index.md")).unwrap();
+    assert!(index.starts_with("---\ntitle: \"Documentation Index\"\nslug: index\n"));
+
+    // Two files documenting one crate would write the same directory
+    std::fs::copy(
+        "tests/fixtures/test_crate.json",
+        input_dir.join("test_crate.json"),
+    )
+    .unwrap();
+    let Err(err) =
+        cargo_doc_md::convert_directory(&input_dir, &output_dir, &MarkdownOptions::default())
+    else {
+        panic!("a crate documented twice should be rejected");
+    };
+    assert!(err.to_string().contains("both document crate `test_crate`"));
+    std::fs::remove_file(input_dir.join("test_crate.json")).unwrap();
+
+    // A directory without JSON files is an error, not an empty index
+    std::fs::remove_file(input_dir.join("test_crate.json.gz")).unwrap();
+    assert!(
+        cargo_doc_md::convert_directory(&input_dir, &output_dir, &MarkdownOptions::default())
+            .is_err()
+    );
 
+    std::fs::remove_dir_all(&input_dir).ok();
     std::fs::remove_dir_all(&output_dir).ok();
 }
 
@@ -251,3 +413,1604 @@ fn test_legacy_single_file_output() {
     assert!(markdown.contains("## Table of Contents"));
     insta::assert_snapshot!("legacy_single_file", markdown);
 }
+
+#[test]
+fn test_convert_json_str_matches_multifile_output() {
+    let json_path = Path::new("tests/fixtures/test_crate.json");
+    let json = std::fs::read_to_string(json_path).expect("Failed to read JSON");
+    let files = cargo_doc_md::convert_json_str(&json, &MarkdownOptions::default())
+        .expect("Failed to convert JSON string");
+
+    let crate_data = parser::load_rustdoc_json(json_path).expect("Failed to load JSON");
+    let output = converter::convert_to_markdown_multifile(&crate_data, &MarkdownOptions::default())
+        .expect("Failed to convert to markdown");
+
+    assert_eq!(files.len(), output.files.len());
+    assert_eq!(files[0].0, Path::new("test_crate/index.md"));
+    assert_eq!(files[1].0, Path::new("test_crate/test_crate.md"));
+
+    for (path, content) in &files {
+        let relative = path.strip_prefix("test_crate").unwrap();
+        let expected = &output.files[relative.to_str().unwrap()];
+        assert_eq!(content, expected, "Content mismatch for {}", path.display());
+    }
+}
+
+#[test]
+fn test_frontmatter() {
+    let json_path = Path::new("tests/fixtures/test_crate.json");
+    let crate_data = parser::load_rustdoc_json(json_path).expect("Failed to load JSON");
+    let options = MarkdownOptions {
+        frontmatter: true,
+        ..Default::default()
+    };
+    let output = converter::convert_to_markdown_multifile(&crate_data, &options)
+        .expect("Failed to convert to markdown");
+
+    let index_content = output.files.get("index.md").expect("index.md not found");
+    assert!(index_content.starts_with(
+        "---\ntitle: \"test_crate\"\nslug: test_crate/index\nsidebar_position: 1\n---\n\n# test_crate\n"
+    ));
+
+    let async_content = output
+        .files
+        .get("async_example.md")
+        .expect("async_example module not found");
+    assert!(async_content.starts_with(
+        "---\ntitle: \"async_example\"\nslug: test_crate/async_example\nsidebar_position: 3\n---\n\n**[test_crate](index.md) › async_example**\n"
+    ));
+
+    let inner_content = output
+        .files
+        .get("nested/inner.md")
+        .expect("nested/inner module not found");
+    assert!(inner_content.starts_with(
+        "---\ntitle: \"inner\"\nslug: test_crate/nested/inner\nsidebar_position: 1\n---\n\n"
+    ));
+
+    // Slugs follow the crate's output directory, not its name
+    let options = MarkdownOptions {
+        frontmatter: true,
+        crate_dir: Some("deps/test_crate-0.1.0".to_string()),
+        ..Default::default()
+    };
+    let output = converter::convert_to_markdown_multifile(&crate_data, &options)
+        .expect("Failed to convert to markdown");
+    let index_content = output.files.get("index.md").expect("index.md not found");
+    assert!(index_content.contains("\nslug: deps/test_crate-0.1.0/index\n"));
+    let inner_content = output.files.get("nested/inner.md").unwrap();
+    assert!(inner_content.contains("\nslug: deps/test_crate-0.1.0/nested/inner\n"));
+}
+
+#[test]
+fn test_deprecated_items() {
+    let json_path = Path::new("tests/fixtures/test_crate.json");
+    let crate_data = parser::load_rustdoc_json(json_path).expect("Failed to load JSON");
+    let output = converter::convert_to_markdown_multifile(&crate_data, &MarkdownOptions::default())
+        .expect("Failed to convert to markdown");
+
+    let functions_content = output
+        .files
+        .get("functions.md")
+        .expect("functions module not found");
+
+    assert!(functions_content.contains(
+        "## test_crate::functions::old_add\n\n> ⚠️ **Deprecated**: use `add` instead (since 0.1.0)\n\n*Function*"
+    ));
+    assert!(functions_content.contains("- [~~`old_add`~~](#test_cratefunctionsold_add)"));
+    assert!(functions_content.contains("- [`add`](#test_cratefunctionsadd)"));
+}
+
+#[test]
+fn test_cross_crate_links() {
+    let json_path = Path::new("tests/fixtures/test_crate.json");
+    let crate_data = parser::load_rustdoc_json(json_path).expect("Failed to load JSON");
+
+    let output = converter::convert_to_markdown_multifile(&crate_data, &MarkdownOptions::default())
+        .expect("Failed to convert to markdown");
+    let errors_content = output
+        .files
+        .get("errors.md")
+        .expect("errors module not found");
+    assert!(errors_content.contains(
+        "[`io::Error`](https://doc.rust-lang.org/nightly/std/io/error/struct.Error.html)"
+    ));
+    assert!(
+        errors_content
+            .contains("[`From`](https://doc.rust-lang.org/nightly/core/convert/trait.From.html)")
+    );
+    assert!(errors_content.contains(
+        "[`std::error::Error`](https://doc.rust-lang.org/nightly/core/error/trait.Error.html)"
+    ));
+
+    let options = MarkdownOptions {
+        documented_crates: [("core".to_string(), "core".to_string())]
+            .into_iter()
+            .collect(),
+        ..Default::default()
+    };
+    let output = converter::convert_to_markdown_multifile(&crate_data, &options)
+        .expect("Failed to convert to markdown");
+    let errors_content = output
+        .files
+        .get("errors.md")
+        .expect("errors module not found");
+    assert!(errors_content.contains("[`From`](../core/convert.md#coreconvertfrom)"));
+    assert!(errors_content.contains("[`std::error::Error`](../core/error.md#coreerrorerror)"));
+
+    // In the nested layout, dependencies live under deps/ and links climb out of it
+    let options = MarkdownOptions {
+        documented_crates: [("core".to_string(), "deps/core".to_string())]
+            .into_iter()
+            .collect(),
+        crate_dir: Some("test_crate".to_string()),
+        ..Default::default()
+    };
+    let output = converter::convert_to_markdown_multifile(&crate_data, &options)
+        .expect("Failed to convert to markdown");
+    assert!(
+        output.files["errors.md"].contains("[`From`](../deps/core/convert.md#coreconvertfrom)")
+    );
+
+    let options = MarkdownOptions {
+        documented_crates: [("core".to_string(), "core".to_string())]
+            .into_iter()
+            .collect(),
+        crate_dir: Some("deps/test_crate".to_string()),
+        ..Default::default()
+    };
+    let output = converter::convert_to_markdown_multifile(&crate_data, &options)
+        .expect("Failed to convert to markdown");
+    assert!(output.files["errors.md"].contains("[`From`](../../core/convert.md#coreconvertfrom)"));
+}
+
+#[test]
+fn test_contents_anchors_match_headings() {
+    let json_path = Path::new("tests/fixtures/test_crate.json");
+    let crate_data = parser::load_rustdoc_json(json_path).expect("Failed to load JSON");
+    let output = converter::convert_to_markdown_multifile(&crate_data, &MarkdownOptions::default())
+        .expect("Failed to convert to markdown");
+
+    let nested_content = output
+        .files
+        .get("nested.md")
+        .expect("nested module not found");
+    assert!(nested_content.contains("- [`inner`](#module-inner)"));
+    assert!(nested_content.contains("## Module: inner\n\n"));
+
+    let types_content = output
+        .files
+        .get("types.md")
+        .expect("types module not found");
+    assert!(types_content.contains("(#test_cratetypescontainer)"));
+    assert!(types_content.contains("## test_crate::types::Container\n\n"));
+}
+
+#[test]
+fn test_exported_macros() {
+    let json_path = Path::new("tests/fixtures/test_crate.json");
+    let crate_data = parser::load_rustdoc_json(json_path).expect("Failed to load JSON");
+    let output = converter::convert_to_markdown_multifile(&crate_data, &MarkdownOptions::default())
+        .expect("Failed to convert to markdown");
+
+    let lib_content = output
+        .files
+        .get("test_crate.md")
+        .expect("root module not found");
+
+    assert!(lib_content.contains("**Macros**\n\n- [`create_struct`]"));
+    assert!(lib_content.contains(
+        "## test_crate::max\n\n*Declarative Macro*\n\nReturns the largest of its arguments.\n\n```rust\nmacro_rules! max {\n"
+    ));
+}
+
+#[test]
+fn test_exclude_patterns() {
+    let json_path = Path::new("tests/fixtures/test_crate.json");
+    let crate_data = parser::load_rustdoc_json(json_path).expect("Failed to load JSON");
+    let options = MarkdownOptions {
+        exclude: vec![
+            "test_crate::nested::*".to_string(),
+            "*::old_?dd".to_string(),
+        ],
+        ..Default::default()
+    };
+    let output = converter::convert_to_markdown_multifile(&crate_data, &options)
+        .expect("Failed to convert to markdown");
+
+    assert!(!output.files.keys().any(|path| path.starts_with("nested")));
+
+    let index_content = output.files.get("index.md").expect("index.md not found");
+    assert!(!index_content.contains("(nested.md)"));
+
+    let lib_content = output
+        .files
+        .get("test_crate.md")
+        .expect("root module not found");
+    assert!(!lib_content.contains("[`nested`]"));
+
+    let functions_content = output
+        .files
+        .get("functions.md")
+        .expect("functions module not found");
+    assert!(!functions_content.contains("old_add"));
+    assert!(functions_content.contains("old_multiply"));
+}
+
+/// The section of a module file documenting one item, up to the next item heading.
+fn item_section<'a>(content: &'a str, heading: &str) -> &'a str {
+    let start = content
+        .find(heading)
+        .unwrap_or_else(|| panic!("{} not found", heading));
+    let rest = &content[start..];
+    let end = rest[heading.len()..]
+        .find("\n## ")
+        .map_or(rest.len(), |i| i + heading.len());
+    &rest[..end]
+}
+
+#[test]
+fn test_where_clauses() {
+    let json_path = Path::new("tests/fixtures/test_crate.json");
+    let crate_data = parser::load_rustdoc_json(json_path).expect("Failed to load JSON");
+    let output = converter::convert_to_markdown_multifile(&crate_data, &MarkdownOptions::default())
+        .expect("Failed to convert to markdown");
+
+    let functions_content = output
+        .files
+        .get("functions.md")
+        .expect("functions module not found");
+    insta::assert_snapshot!(
+        "where_clause_function",
+        item_section(
+            functions_content,
+            "## test_crate::functions::complex_generics\n"
+        )
+    );
+
+    let lib_content = output
+        .files
+        .get("test_crate.md")
+        .expect("root module not found");
+    insta::assert_snapshot!(
+        "where_self_sized_impl",
+        item_section(lib_content, "## test_crate::Labeled\n")
+    );
+}
+
+#[test]
+fn test_doc_cfg_badges() {
+    let json_path = Path::new("tests/fixtures/test_crate.json");
+    let crate_data = parser::load_rustdoc_json(json_path).expect("Failed to load JSON");
+    let output = converter::convert_to_markdown_multifile(&crate_data, &MarkdownOptions::default())
+        .expect("Failed to convert to markdown");
+
+    let functions_content = output
+        .files
+        .get("functions.md")
+        .expect("functions module not found");
+
+    assert!(functions_content.contains(
+        "## test_crate::functions::decorated\n\n*Available on **feature `extras`** only.*\n\n"
+    ));
+    assert!(
+        functions_content
+            .contains("*Available on **Unix and (feature `extras` or non-Windows)** only.*")
+    );
+    assert!(
+        functions_content
+            .contains("- [`decorated`](#test_cratefunctionsdecorated) *(feature `extras`)* - ")
+    );
+}
+
+#[test]
+fn test_source_links() {
+    let json_path = Path::new("tests/fixtures/test_crate.json");
+    let crate_data = parser::load_rustdoc_json(json_path).expect("Failed to load JSON");
+
+    let output = converter::convert_to_markdown_multifile(&crate_data, &MarkdownOptions::default())
+        .expect("Failed to convert to markdown");
+    assert!(
+        !output
+            .files
+            .values()
+            .any(|content| content.contains("[[src]]"))
+    );
+
+    let options = MarkdownOptions {
+        src_base: Some("https://github.com/example/test_crate/blob/main/".to_string()),
+        ..Default::default()
+    };
+    let output = converter::convert_to_markdown_multifile(&crate_data, &options)
+        .expect("Failed to convert to markdown");
+
+    let functions_content = output
+        .files
+        .get("functions.md")
+        .expect("functions module not found");
+    assert!(functions_content.contains(
+        "## test_crate::functions::add [[src]](https://github.com/example/test_crate/blob/main/src/functions.rs#L"
+    ));
+    assert!(functions_content.contains("- [`add`](#test_cratefunctionsadd-src)"));
+}
+
+#[test]
+fn test_auto_and_blanket_impls_collapsed() {
+    let json_path = Path::new("tests/fixtures/test_crate.json");
+    let crate_data = parser::load_rustdoc_json(json_path).expect("Failed to load JSON");
+    let output = converter::convert_to_markdown_multifile(&crate_data, &MarkdownOptions::default())
+        .expect("Failed to convert to markdown");
+
+    let types_content = output
+        .files
+        .get("types.md")
+        .expect("types module not found");
+    let pair = item_section(types_content, "## test_crate::types::Pair");
+
+    let auto = pair
+        .find("<details>\n<summary>Auto Trait Implementations</summary>")
+        .expect("auto trait impls should be collapsed");
+    let blanket = pair
+        .find("<details>\n<summary>Blanket Implementations</summary>")
+        .expect("blanket impls should be collapsed");
+    assert!(auto < blanket);
+    assert!(
+        pair[auto..blanket].contains("- `impl<T, U> Send for Pair<T, U> where T: Send, U: Send`")
+    );
+    assert!(pair[blanket..].contains("- `impl<T> From<T> for T`"));
+
+    // Impls written in the crate stay outside the collapsed blocks
+    assert!(
+        pair.find("**Trait Implementations:**")
+            .is_some_and(|traits| traits < auto)
+    );
+}
+
+#[test]
+fn test_single_file_output() {
+    let json_path = Path::new("tests/fixtures/test_crate.json");
+    let crate_data = parser::load_rustdoc_json(json_path).expect("Failed to load JSON");
+    let options = MarkdownOptions {
+        single_file: true,
+        ..Default::default()
+    };
+    let output = converter::convert_to_markdown_multifile(&crate_data, &options)
+        .expect("Failed to convert to markdown");
+
+    assert_eq!(output.files.len(), 1);
+    let content = output
+        .files
+        .get("test_crate.md")
+        .expect("single file not found");
+
+    // Module sections are nested below the crate title by depth
+    assert!(content.starts_with("# test_crate\n\n"));
+    assert!(content.contains("\n## Module: test_crate\n\n"));
+    assert!(content.contains("\n### Module: functions\n\n"));
+    assert!(content.contains("\n#### test_crate::functions::add\n\n"));
+    assert!(content.contains("\n#### Module: nested::inner\n\n"));
+
+    // Module listings link to sections of the same document
+    assert!(content.contains("### [`functions`](#module-functions)"));
+    assert!(content.contains("- [`inner`](#module-nestedinner)"));
+    assert!(content.contains("- [`add`](#test_cratefunctionsadd)"));
+    assert!(!content.contains(".md)"));
+    assert!(!content.contains(".md#"));
+}
+
+#[test]
+fn test_reexports_link_to_definitions() {
+    let json_path = Path::new("tests/fixtures/test_crate.json");
+    let crate_data = parser::load_rustdoc_json(json_path).expect("Failed to load JSON");
+    let output = converter::convert_to_markdown_multifile(&crate_data, &MarkdownOptions::default())
+        .expect("Failed to convert to markdown");
+
+    let nested_content = output
+        .files
+        .get("nested.md")
+        .expect("nested module not found");
+    assert!(nested_content.contains(
+        "**Re-exports**\n\n- [`pub use inner::InnerStruct`](nested/inner.md#test_cratenestedinnerinnerstruct)\n"
+    ));
+    assert!(nested_content.contains("- [`pub use inner::deep::*`](nested/inner/deep.md)\n"));
+
+    let lib_content = output
+        .files
+        .get("test_crate.md")
+        .expect("root module not found");
+    assert!(
+        lib_content
+            .contains("- [`pub use patterns::Builder`](patterns.md#test_cratepatternsbuilder)")
+    );
+}
+
+#[test]
+fn test_ir_output() {
+    let json_path = Path::new("tests/fixtures/test_crate.json");
+    let crate_data = parser::load_rustdoc_json(json_path).expect("Failed to load JSON");
+    let ir = converter::convert_to_ir(&crate_data, &MarkdownOptions::default())
+        .expect("Failed to convert to IR");
+
+    assert_eq!(ir["schema_version"], converter::IR_SCHEMA_VERSION);
+    assert_eq!(ir["name"], "test_crate");
+
+    let modules = ir["modules"]
+        .as_array()
+        .expect("modules should be an array");
+    assert_eq!(modules[0]["path"], "test_crate");
+    assert_eq!(modules[0]["file"], "test_crate.md");
+
+    let types = modules
+        .iter()
+        .find(|module| module["path"] == "test_crate::types")
+        .expect("types module not found");
+    assert_eq!(types["file"], "types.md");
+
+    let container = types["items"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|item| item["name"] == "Container")
+        .expect("Container not found");
+    assert_eq!(container["path"], "test_crate::types::Container");
+    assert_eq!(container["kind"], "struct");
+    assert_eq!(container["signature"], "struct Container<T>");
+    assert_eq!(container["fields"][0]["type"], "Vec<T>");
+
+    let impls = container["impls"].as_array().unwrap();
+    assert_eq!(impls[0]["kind"], "inherent");
+    assert!(
+        impls[0]["items"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|method| method["signature"] == "fn len(&self) -> usize")
+    );
+    assert!(
+        impls
+            .iter()
+            .any(|impl_| impl_["kind"] == "auto" && impl_["trait"] == "Send")
+    );
+
+    let nested = modules
+        .iter()
+        .find(|module| module["path"] == "test_crate::nested")
+        .expect("nested module not found");
+    assert_eq!(
+        nested["reexports"][0]["target"],
+        "test_crate::nested::inner::InnerStruct"
+    );
+}
+
+#[test]
+fn test_enum_discriminants() {
+    let json_path = Path::new("tests/fixtures/test_crate.json");
+    let crate_data = parser::load_rustdoc_json(json_path).expect("Failed to load JSON");
+    let output = converter::convert_to_markdown_multifile(&crate_data, &MarkdownOptions::default())
+        .expect("Failed to convert to markdown");
+
+    let types_content = output
+        .files
+        .get("types.md")
+        .expect("types module not found");
+    let section = item_section(types_content, "## test_crate::types::Priority\n");
+    let variants = &section[section.find("**Variants:**").expect("variants not found")..];
+    let variants = &variants[..variants.find("\n\n").unwrap_or(variants.len())];
+    insta::assert_snapshot!("enum_discriminants", variants);
+}
+
+#[test]
+fn test_doc_aliases() {
+    let json_path = Path::new("tests/fixtures/test_crate.json");
+    let crate_data = parser::load_rustdoc_json(json_path).expect("Failed to load JSON");
+    let output = converter::convert_to_markdown_multifile(&crate_data, &MarkdownOptions::default())
+        .expect("Failed to convert to markdown");
+
+    let functions_content = output
+        .files
+        .get("functions.md")
+        .expect("functions module not found");
+    let add = item_section(functions_content, "## test_crate::functions::add\n");
+    assert!(add.contains("*Aliases: `sum`, `plus`*\n"));
+
+    let filter = item_section(functions_content, "## test_crate::functions::filter\n");
+    assert!(!filter.contains("*Aliases:"));
+}
+
+#[test]
+fn test_search_index() {
+    let json_path = Path::new("tests/fixtures/test_crate.json");
+    let crate_data = parser::load_rustdoc_json(json_path).expect("Failed to load JSON");
+    let output = converter::convert_to_markdown_multifile(&crate_data, &MarkdownOptions::default())
+        .expect("Failed to convert to markdown");
+
+    let paths: Vec<&str> = output
+        .search_index
+        .iter()
+        .map(|entry| entry.path.as_str())
+        .collect();
+    let mut sorted = paths.clone();
+    sorted.sort();
+    assert_eq!(paths, sorted, "entries should be sorted by path");
+
+    let add = output
+        .search_index
+        .iter()
+        .find(|entry| entry.path == "test_crate::functions::add")
+        .expect("add not indexed");
+    assert_eq!(add.kind, "function");
+    assert_eq!(add.file, "functions.md");
+    assert_eq!(add.anchor.as_deref(), Some("test_cratefunctionsadd"));
+    assert_eq!(add.summary.as_deref(), Some("Adds two numbers together."));
+    assert_eq!(add.aliases, ["sum", "plus"]);
+
+    let inner = output
+        .search_index
+        .iter()
+        .find(|entry| entry.path == "test_crate::nested::inner")
+        .expect("nested::inner not indexed");
+    assert_eq!(inner.file, "nested/inner.md");
+    assert_eq!(inner.anchor, None);
+}
+
+#[test]
+fn test_function_parameters() {
+    let json_path = Path::new("tests/fixtures/test_crate.json");
+    let crate_data = parser::load_rustdoc_json(json_path).expect("Failed to load JSON");
+    let output = converter::convert_to_markdown_multifile(&crate_data, &MarkdownOptions::default())
+        .expect("Failed to convert to markdown");
+
+    let functions_content = output
+        .files
+        .get("functions.md")
+        .expect("functions module not found");
+    assert!(functions_content.contains("fn add(a: i32, b: i32) -> i32"));
+    assert!(functions_content.contains("fn ignored_argument(_: i32) -> bool"));
+    assert!(
+        functions_content
+            .contains("fn printf(format: *const std::ffi::c_char, ...) -> std::ffi::c_int")
+    );
+
+    let types_content = output
+        .files
+        .get("types.md")
+        .expect("types module not found");
+    assert!(types_content.contains("- `fn add(&mut self, item: T)`"));
+    assert!(types_content.contains("- `fn len(&self) -> usize`"));
+    assert!(!types_content.contains("self: &Self"));
+}
+
+#[test]
+fn test_streaming_matches_multifile_output() {
+    let json_path = Path::new("tests/fixtures/test_crate.json");
+    let crate_data = parser::load_rustdoc_json(json_path).expect("Failed to load JSON");
+    let options = MarkdownOptions {
+        frontmatter: true,
+        ..Default::default()
+    };
+    let output = converter::convert_to_markdown_multifile(&crate_data, &options)
+        .expect("Failed to convert to markdown");
+
+    let mut streamed = Vec::new();
+    let streamed_output =
+        converter::convert_to_markdown_streaming(&crate_data, &options, |path, content| {
+            streamed.push((path.to_string(), content));
+            Ok(())
+        })
+        .expect("Failed to convert to markdown");
+    assert!(streamed_output.files.is_empty());
+
+    let expected: Vec<(String, String)> = output
+        .files_in_order()
+        .into_iter()
+        .map(|(path, content)| (path.to_string(), content.to_string()))
+        .collect();
+    assert_eq!(streamed, expected);
+}
+
+#[test]
+fn test_const_generics() {
+    let json_path = Path::new("tests/fixtures/test_crate.json");
+    let crate_data = parser::load_rustdoc_json(json_path).expect("Failed to load JSON");
+    let output = converter::convert_to_markdown_multifile(&crate_data, &MarkdownOptions::default())
+        .expect("Failed to convert to markdown");
+
+    let types_content = output
+        .files
+        .get("types.md")
+        .expect("types module not found");
+    insta::assert_snapshot!(
+        "const_generic_function",
+        item_section(types_content, "## test_crate::types::first_n\n")
+    );
+
+    let buffer = item_section(types_content, "## test_crate::types::FixedBuffer\n");
+    assert!(buffer.contains("**Generic Parameters:**\n- 'a\n- T\n- const N: usize = 4\n"));
+    assert!(buffer.contains("`impl<'a, T: Copy + Default, const N: usize> FixedBuffer<'a, T, N>`"));
+}
+
+#[test]
+fn test_max_depth() {
+    let json_path = Path::new("tests/fixtures/test_crate.json");
+    let crate_data = parser::load_rustdoc_json(json_path).expect("Failed to load JSON");
+
+    let options = MarkdownOptions {
+        max_depth: Some(1),
+        ..Default::default()
+    };
+    let output = converter::convert_to_markdown_multifile(&crate_data, &options)
+        .expect("Failed to convert to markdown");
+
+    assert!(output.files.contains_key("nested.md"));
+    assert!(!output.files.contains_key("nested/inner.md"));
+    assert!(!output.files.contains_key("nested/inner/deep.md"));
+
+    let nested = &output.files["nested.md"];
+    assert!(nested.contains("- [`inner`](#module-nestedinner)"));
+    assert!(nested.contains(
+        "**[test_crate](index.md) › [nested](#module-nested) › inner**\n\n## Module: nested::inner\n"
+    ));
+    assert!(nested.contains("\n### test_crate::nested::inner::InnerStruct\n"));
+    assert!(nested.contains("\n#### Module: nested::inner::deep::deeper\n"));
+
+    let index = &output.files["index.md"];
+    assert!(index.contains("### [`nested::inner`](nested.md#module-nestedinner)"));
+    assert!(output.item_counts["nested.md"]["struct"] >= 3);
+
+    let options = MarkdownOptions {
+        max_depth: Some(0),
+        ..Default::default()
+    };
+    let output = converter::convert_to_markdown_multifile(&crate_data, &options)
+        .expect("Failed to convert to markdown");
+    let files: Vec<&str> = output.files.keys().map(String::as_str).collect();
+    assert_eq!(files, ["index.md"]);
+    assert!(output.files["index.md"].contains("### [`types`](#module-types)"));
+    assert!(output.files["index.md"].contains("\n## Module: test_crate\n"));
+}
+
+#[test]
+fn test_impl_trait_and_async_signatures() {
+    let json_path = Path::new("tests/fixtures/test_crate.json");
+    let crate_data = parser::load_rustdoc_json(json_path).expect("Failed to load JSON");
+    let output = converter::convert_to_markdown_multifile(&crate_data, &MarkdownOptions::default())
+        .expect("Failed to convert to markdown");
+
+    let functions_content = output
+        .files
+        .get("functions.md")
+        .expect("functions module not found");
+    assert!(functions_content.contains("\nfn evens(limit: u8) -> impl Iterator<Item = u8>\n"));
+    assert!(
+        functions_content
+            .contains("\nfn describe_twice(value: impl std::fmt::Display + Clone) -> String\n")
+    );
+    assert!(
+        functions_content
+            .contains("\nfn words<'a>(text: &'a str) -> impl Iterator<Item = &'a str> + use<'a>\n")
+    );
+
+    let async_content = output
+        .files
+        .get("async_example.md")
+        .expect("async_example module not found");
+    assert!(async_content.contains("\nasync fn simple_async() -> String\n"));
+    assert!(async_content.contains("\nfn returns_future() -> impl Future<Output = String>\n"));
+    assert!(!async_content.contains("fn simple_async() -> impl Future"));
+}
+
+#[test]
+fn test_name_collisions_across_namespaces() {
+    let json_path = Path::new("tests/fixtures/test_crate.json");
+    let crate_data = parser::load_rustdoc_json(json_path).expect("Failed to load JSON");
+    let output = converter::convert_to_markdown_multifile(&crate_data, &MarkdownOptions::default())
+        .expect("Failed to convert to markdown");
+
+    let collisions = output
+        .files
+        .get("collisions.md")
+        .expect("collisions module not found");
+    insta::assert_snapshot!("collisions_module", collisions);
+
+    assert!(
+        output.files.contains_key("collisions/foo.md"),
+        "module foo should keep its own file"
+    );
+
+    let types = output
+        .files
+        .get("types.md")
+        .expect("types module not found");
+    assert!(
+        !types.contains(" (struct)\n"),
+        "unambiguous items keep their plain heading"
+    );
+}
+
+#[test]
+fn test_lifetime_parameters() {
+    let json_path = Path::new("tests/fixtures/test_crate.json");
+    let crate_data = parser::load_rustdoc_json(json_path).expect("Failed to load JSON");
+    let output = converter::convert_to_markdown_multifile(&crate_data, &MarkdownOptions::default())
+        .expect("Failed to convert to markdown");
+
+    let types_content = output
+        .files
+        .get("types.md")
+        .expect("types module not found");
+    let ref_struct = item_section(types_content, "## test_crate::types::RefStruct\n");
+    assert!(ref_struct.contains("**Generic Parameters:**\n- 'a\n"));
+    assert!(ref_struct.contains("- `data: &'a str`"));
+    insta::assert_snapshot!("ref_struct", ref_struct);
+
+    let lifetimes_content = output
+        .files
+        .get("lifetimes.md")
+        .expect("lifetimes module not found");
+    let lifetime_struct = item_section(
+        lifetimes_content,
+        "## test_crate::lifetimes::LifetimeStruct\n",
+    );
+    assert!(lifetime_struct.contains("**Where Clauses:**\n- `T: 'a`\n"));
+    let double_borrow = item_section(
+        lifetimes_content,
+        "## test_crate::lifetimes::DoubleBorrow\n",
+    );
+    assert!(double_borrow.contains("`impl<'a, 'b> DoubleBorrow<'a, 'b> where 'b: 'a`"));
+}
+
+#[test]
+fn test_gzipped_json() {
+    use std::io::Write;
+
+    let json_path = Path::new("tests/fixtures/test_crate.json");
+    let gz_path = Path::new("target/test_crate.json.gz");
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::fast());
+    encoder
+        .write_all(&std::fs::read(json_path).unwrap())
+        .unwrap();
+    std::fs::write(gz_path, encoder.finish().unwrap()).unwrap();
+
+    let plain = parser::load_rustdoc_json(json_path).expect("Failed to load JSON");
+    let gzipped = parser::load_rustdoc_json(gz_path).expect("Failed to load gzipped JSON");
+    let options = MarkdownOptions::default();
+    assert_eq!(
+        converter::convert_to_markdown_multifile(&gzipped, &options)
+            .unwrap()
+            .files,
+        converter::convert_to_markdown_multifile(&plain, &options)
+            .unwrap()
+            .files
+    );
+
+    std::fs::remove_file(gz_path).ok();
+}
+
+#[test]
+fn test_trait_required_and_provided_methods() {
+    let json_path = Path::new("tests/fixtures/test_crate.json");
+    let crate_data = parser::load_rustdoc_json(json_path).expect("Failed to load JSON");
+    let output = converter::convert_to_markdown_multifile(&crate_data, &MarkdownOptions::default())
+        .expect("Failed to convert to markdown");
+
+    let lib_content = output
+        .files
+        .get("test_crate.md")
+        .expect("lib module not found");
+    insta::assert_snapshot!(
+        "my_trait",
+        item_section(lib_content, "## test_crate::MyTrait\n")
+    );
+}
+
+#[test]
+fn test_associated_functions_and_methods() {
+    let json_path = Path::new("tests/fixtures/test_crate.json");
+    let crate_data = parser::load_rustdoc_json(json_path).expect("Failed to load JSON");
+    let output = converter::convert_to_markdown_multifile(&crate_data, &MarkdownOptions::default())
+        .expect("Failed to convert to markdown");
+
+    let lib_content = output
+        .files
+        .get("test_crate.md")
+        .expect("lib module not found");
+    let section = item_section(lib_content, "## test_crate::PlainStruct\n");
+
+    // `new` has no receiver, `get_value` takes `&self`
+    let functions = section.find("**Associated Functions:**").unwrap();
+    let methods = section.find("**Methods:**").unwrap();
+    let new = section.find("- `fn new(").unwrap();
+    let get_value = section.find("- `fn get_value(&self)").unwrap();
+    assert!(functions < new && new < methods && methods < get_value);
+
+    insta::assert_snapshot!("plain_struct", section);
+}
+
+#[test]
+fn test_unsafe_traits_and_impls() {
+    let json_path = Path::new("tests/fixtures/test_crate.json");
+    let crate_data = parser::load_rustdoc_json(json_path).expect("Failed to load JSON");
+    let output = converter::convert_to_markdown_multifile(&crate_data, &MarkdownOptions::default())
+        .expect("Failed to convert to markdown");
+
+    let traits_content = output
+        .files
+        .get("traits.md")
+        .expect("traits module not found");
+    let counter = item_section(traits_content, "## test_crate::traits::Counter\n");
+    assert!(counter.contains("- **Zeroable** (`unsafe impl Zeroable for Counter`)"));
+    let raw_buffer = item_section(traits_content, "## test_crate::traits::RawBuffer\n");
+    assert!(raw_buffer.contains("**Traits:** unsafe Send\n"));
+    // Auto trait impls are not written as `unsafe impl`
+    assert!(counter.contains("- `impl Send for Counter`"));
+
+    insta::assert_snapshot!(
+        "zeroable_trait",
+        item_section(traits_content, "## test_crate::traits::Zeroable\n")
+    );
+}
+
+#[test]
+fn test_derived_traits() {
+    let json_path = Path::new("tests/fixtures/test_crate.json");
+    let crate_data = parser::load_rustdoc_json(json_path).expect("Failed to load JSON");
+    let output = converter::convert_to_markdown_multifile(&crate_data, &MarkdownOptions::default())
+        .expect("Failed to convert to markdown");
+
+    let lib_content = output
+        .files
+        .get("test_crate.md")
+        .expect("lib module not found");
+    let simple_enum = item_section(lib_content, "## test_crate::SimpleEnum\n");
+
+    // Listed once, in derive order, and not again as impl blocks
+    assert!(simple_enum.contains("*Derives: `Debug`, `Clone`, `PartialEq`, `Eq`*"));
+    assert!(!simple_enum.contains("**Trait Implementations:**"));
+    assert!(!simple_enum.contains("StructuralPartialEq"));
+
+    insta::assert_snapshot!("simple_enum", simple_enum);
+}
+
+#[test]
+fn test_trait_impl_methods_name_self_type() {
+    let json_path = Path::new("tests/fixtures/test_crate.json");
+    let crate_data = parser::load_rustdoc_json(json_path).expect("Failed to load JSON");
+    let output = converter::convert_to_markdown_multifile(&crate_data, &MarkdownOptions::default())
+        .expect("Failed to convert to markdown");
+
+    // `Self` is the implementing type, and `Self::Assoc` the impl's associated type
+    let types = &output.files["types.md"];
+    let container = item_section(types, "## test_crate::types::Container\n");
+    assert!(container.contains("`fn default() -> Container<T>`"));
+    let traits = &output.files["traits.md"];
+    let assoc_impl = item_section(traits, "## test_crate::traits::AssociatedImpl\n");
+    assert!(assoc_impl.contains("`fn get_assoc(&self) -> String`"));
+
+    // Receivers keep their short form
+    assert!(!assoc_impl.contains("self: &"));
+}
+
+#[test]
+fn test_wikilinks() {
+    let json_path = Path::new("tests/fixtures/test_crate.json");
+    let crate_data = parser::load_rustdoc_json(json_path).expect("Failed to load JSON");
+    let options = MarkdownOptions {
+        wikilinks: true,
+        ..Default::default()
+    };
+    let output = converter::convert_to_markdown_multifile(&crate_data, &options)
+        .expect("Failed to convert to markdown");
+
+    // Paths are from the vault root, fragments are heading text
+    let index = &output.files["index.md"];
+    assert!(index.contains("- [[test_crate/types|`types`]]"));
+    let lib = &output.files["test_crate.md"];
+    assert!(lib.contains(
+        "[[test_crate/patterns#test_crate patterns Builder|`pub use patterns::Builder`]]"
+    ));
+
+    let pair = item_section(&output.files["types.md"], "## test_crate::types::Pair\n");
+    assert!(pair.contains("[[#test_crate types Container|`Container`]]"));
+    assert!(pair.contains("[[#test_crate types FixedBuffer|a buffer]]"));
+
+    // Web links stay markdown links
+    assert!(
+        !output
+            .files
+            .values()
+            .any(|content| content.contains(".md)"))
+    );
+    assert!(lib.contains("[`String`](https://doc.rust-lang.org/alloc/string/struct.String.html)"));
+}
+
+#[test]
+fn test_function_modifiers() {
+    let json_path = Path::new("tests/fixtures/test_crate.json");
+    let crate_data = parser::load_rustdoc_json(json_path).expect("Failed to load JSON");
+    let output = converter::convert_to_markdown_multifile(&crate_data, &MarkdownOptions::default())
+        .expect("Failed to convert to markdown");
+    let functions = &output.files["functions.md"];
+
+    for signature in [
+        "const fn const_function(x: i32) -> i32",
+        "extern \"C\" fn c_callback(value: i32) -> i32",
+        "const unsafe fn read_unchecked(ptr: *const u8) -> u8",
+        "unsafe extern \"C\" fn printf(format: *const std::ffi::c_char, ...) -> std::ffi::c_int",
+    ] {
+        assert!(
+            functions.contains(&format!("```rust\n{}\n```", signature)),
+            "missing signature: {}",
+            signature
+        );
+    }
+}
+
+#[test]
+fn test_sealed_trait_note() {
+    let json_path = Path::new("tests/fixtures/test_crate.json");
+    let crate_data = parser::load_rustdoc_json(json_path).expect("Failed to load JSON");
+    let output = converter::convert_to_markdown_multifile(&crate_data, &MarkdownOptions::default())
+        .expect("Failed to convert to markdown");
+    let traits = &output.files["traits.md"];
+    let note = "*This trait is sealed and cannot be implemented outside this crate.*";
+
+    let sealed = item_section(traits, "## test_crate::traits::Sealed\n");
+    assert!(sealed.starts_with(&format!(
+        "## test_crate::traits::Sealed\n\n*Trait*\n\n{}",
+        note
+    )));
+
+    // Public supertraits don't seal a trait
+    let super_trait = item_section(traits, "## test_crate::traits::SuperTrait\n");
+    assert!(!super_trait.contains(note));
+}
+
+#[test]
+fn test_type_alias_definitions() {
+    let json_path = Path::new("tests/fixtures/test_crate.json");
+    let crate_data = parser::load_rustdoc_json(json_path).expect("Failed to load JSON");
+    let output = converter::convert_to_markdown_multifile(&crate_data, &MarkdownOptions::default())
+        .expect("Failed to convert to markdown");
+
+    let string_map = item_section(
+        &output.files["types.md"],
+        "## test_crate::types::StringMap\n",
+    );
+    assert!(string_map.contains("type StringMap = std::collections::HashMap<String, String>;"));
+    insta::assert_snapshot!("string_map_alias", string_map);
+
+    let result = item_section(&output.files["test_crate.md"], "## test_crate::Result\n");
+    assert!(result.contains("type Result<T> = std::result::Result<T, Error>;"));
+    insta::assert_snapshot!("result_alias", result);
+}
+
+#[test]
+fn test_signature_type_links() {
+    let json_path = Path::new("tests/fixtures/test_crate.json");
+    let crate_data = parser::load_rustdoc_json(json_path).expect("Failed to load JSON");
+    let output = converter::convert_to_markdown_multifile(&crate_data, &MarkdownOptions::default())
+        .expect("Failed to convert to markdown");
+
+    let string_map = item_section(
+        &output.files["types.md"],
+        "## test_crate::types::StringMap\n",
+    );
+    assert!(string_map.contains(
+        "*Types: [`HashMap`](https://doc.rust-lang.org/std/collections/hash/map/struct.HashMap.html), \
+         [`String`](https://doc.rust-lang.org/alloc/string/struct.String.html)*"
+    ));
+
+    // Crate-local types link to their section, primitives to their std page
+    let with_result = item_section(
+        &output.files["test_crate.md"],
+        "## test_crate::function_with_result\n",
+    );
+    assert!(with_result.contains("[`Result`](#test_crateresult)"));
+    assert!(with_result.contains("[`i32`](https://doc.rust-lang.org/std/primitive.i32.html)"));
+
+    // Crates documented in the same run are linked relatively instead
+    let options = MarkdownOptions {
+        documented_crates: [("alloc".to_string(), "alloc".to_string())]
+            .into_iter()
+            .collect(),
+        ..Default::default()
+    };
+    let output = converter::convert_to_markdown_multifile(&crate_data, &options)
+        .expect("Failed to convert to markdown");
+    let string_map = item_section(
+        &output.files["types.md"],
+        "## test_crate::types::StringMap\n",
+    );
+    assert!(string_map.contains("[`String`](../alloc/string.md#allocstringstring)"));
+}
+
+#[test]
+fn test_indented_doc_comments() {
+    let json_path = Path::new("tests/fixtures/test_crate.json");
+    let mut crate_data = parser::load_rustdoc_json(json_path).expect("Failed to load JSON");
+    let render = |crate_data: &rustdoc_types::Crate| {
+        let output =
+            converter::convert_to_markdown_multifile(crate_data, &MarkdownOptions::default())
+                .expect("Failed to convert to markdown");
+        output.files["functions.md"].clone()
+    };
+
+    let functions = render(&crate_data);
+    let checksum = item_section(&functions, "## test_crate::functions::checksum\n");
+    assert!(checksum.contains("\n```\nuse test_crate::functions::checksum;\n"));
+    assert!(
+        checksum.contains("\n    for byte in data {\n        sum = sum.wrapping_add(*byte);\n")
+    );
+    insta::assert_snapshot!("indented_doc_comment", checksum);
+
+    // Older toolchains hand the docs over still indented as written; the output must match
+    for item in crate_data.index.values_mut() {
+        if matches!(item.name.as_deref(), Some("checksum" | "repeat")) {
+            let docs = item.docs.as_deref().unwrap_or_default();
+            let indented: Vec<String> = docs.lines().map(|line| format!("    {}", line)).collect();
+            item.docs = Some(indented.join("\n"));
+        }
+    }
+    assert_eq!(render(&crate_data), functions);
+}
+
+#[test]
+fn test_intra_doc_links() {
+    let json_path = Path::new("tests/fixtures/test_crate.json");
+    let crate_data = parser::load_rustdoc_json(json_path).expect("Failed to load JSON");
+    let output = converter::convert_to_markdown_multifile(&crate_data, &MarkdownOptions::default())
+        .expect("Failed to convert to markdown");
+
+    let types_content = output
+        .files
+        .get("types.md")
+        .expect("types module not found");
+    let pair = item_section(types_content, "## test_crate::types::Pair\n");
+    // Shorthand and explicit-path forms, within the same file
+    assert!(pair.contains("a [`Container`](#test_cratetypescontainer) holds many"));
+    assert!(pair.contains("[crate::types::Status](#test_cratetypesstatus)"));
+    assert!(pair.contains("[a buffer](#test_cratetypesfixedbuffer)"));
+    // Private and missing targets keep their text without a broken link
+    assert!(pair.contains("Helpers like\n`swap_values` and the checker are internal"));
+    assert!(pair.contains("and `Triple` does not exist."));
+
+    // Links across files are relative to the linking file
+    let index_content = output.files.get("index.md").expect("index.md not found");
+    assert!(index_content.contains("- [`types`](types.md) - Type definitions"));
+}
+
+#[test]
+fn test_stability_notes() {
+    let json_path = Path::new("tests/fixtures/test_crate.json");
+    let mut crate_data = parser::load_rustdoc_json(json_path).expect("Failed to load JSON");
+    // Only staged_api crates like std carry stability attributes, so add them by hand
+    for item in crate_data.index.values_mut() {
+        let attr = match item.name.as_deref() {
+            Some("Container") => {
+                "#[attr = Stability {stability: Stability {level: Stable {since: Version(RustcVersion { major: 1, minor: 2, patch: 0 })},\nfeature: \"container\"}}]"
+            }
+            Some("Pair") => {
+                "#[attr = Stability {stability: Stability {level: Stable {since: Version(RustcVersion { major: 1, minor: 70, patch: 0 })},\nfeature: \"pair\"}}]"
+            }
+            Some("Rgb") => {
+                "#[attr = Stability {stability: Stability {level: Unstable {reason: None,\nissue: 1234}, feature: \"rgb_color\"}}]"
+            }
+            Some("Status") => "#[stable(feature = \"status\", since = \"1.10.0\")]",
+            _ => continue,
+        };
+        item.attrs
+            .push(rustdoc_types::Attribute::Other(attr.to_string()));
+    }
+    let render = |options: &MarkdownOptions| {
+        let output = converter::convert_to_markdown_multifile(&crate_data, options)
+            .expect("Failed to convert to markdown");
+        output.files["types.md"].clone()
+    };
+
+    let types = render(&MarkdownOptions::default());
+    let container = item_section(&types, "## test_crate::types::Container\n");
+    assert!(container.starts_with("## test_crate::types::Container\n\n*Stable since 1.2.0*\n\n"));
+    let rgb = item_section(&types, "## test_crate::types::Rgb\n");
+    assert!(rgb.contains("*Unstable (feature `rgb_color`)*\n\n"));
+    let status = item_section(&types, "## test_crate::types::Status\n");
+    assert!(status.contains("*Stable since 1.10.0*\n\n"));
+    let priority = item_section(&types, "## test_crate::types::Priority\n");
+    assert!(!priority.contains("Stable") && !priority.contains("Unstable"));
+
+    let options = MarkdownOptions {
+        since: Some("1.9.0".to_string()),
+        ..Default::default()
+    };
+    let types = render(&options);
+    assert!(!types.contains("*Stable since 1.2.0*"));
+    assert!(types.contains("*Stable since 1.10.0*"));
+    assert!(types.contains("*Stable since 1.70.0*"));
+    assert!(types.contains("*Unstable (feature `rgb_color`)*"));
+}
+
+#[test]
+fn test_smart_pointer_receivers() {
+    let json_path = Path::new("tests/fixtures/test_crate.json");
+    let crate_data = parser::load_rustdoc_json(json_path).expect("Failed to load JSON");
+    let output = converter::convert_to_markdown_multifile(&crate_data, &MarkdownOptions::default())
+        .expect("Failed to convert to markdown");
+
+    let job = item_section(&output.files["types.md"], "## test_crate::types::Job\n");
+    assert!(job.contains("`fn consume(self: Box<Self>) -> String`"));
+    assert!(job.contains("`fn share(self: Rc<Self>) -> Rc<Self>`"));
+    assert!(job.contains("`fn step(self: Pin<&mut Self>)`"));
+    // Trait impls keep the receiver as declared rather than naming the implementing type
+    assert!(job.contains("`fn run(self: Box<Self>) -> String`"));
+    insta::assert_snapshot!("smart_pointer_receivers", job);
+}
+
+#[test]
+fn test_supertrait_bounds() {
+    let json_path = Path::new("tests/fixtures/test_crate.json");
+    let crate_data = parser::load_rustdoc_json(json_path).expect("Failed to load JSON");
+    let output = converter::convert_to_markdown_multifile(&crate_data, &MarkdownOptions::default())
+        .expect("Failed to convert to markdown");
+
+    let traits = &output.files["traits.md"];
+    let describe = item_section(traits, "## test_crate::traits::Describe\n");
+    assert!(describe.contains(
+        "```rust\ntrait Describe<'a, T>: Clone + fmt::Debug + 'a\nwhere\n    T: fmt::Display + 'a,\n{ ... }\n```"
+    ));
+    insta::assert_snapshot!("supertrait_bounds", describe);
+
+    let super_trait = item_section(traits, "## test_crate::traits::SuperTrait\n");
+    assert!(super_trait.contains("```rust\ntrait SuperTrait: Clone + fmt::Debug { ... }\n```"));
+    // Traits with nothing to declare beyond their name get no block
+    let iterator = item_section(traits, "## test_crate::traits::Iterator\n");
+    assert!(!iterator.contains("```rust\ntrait"));
+}
+
+#[test]
+fn test_item_headings_are_fully_qualified() {
+    let json_path = Path::new("tests/fixtures/test_crate.json");
+    let crate_data = parser::load_rustdoc_json(json_path).expect("Failed to load JSON");
+    let output = converter::convert_to_markdown_multifile(&crate_data, &MarkdownOptions::default())
+        .expect("Failed to convert to markdown");
+
+    // Each file names its items in full, so it reads on its own
+    let types = &output.files["types.md"];
+    assert!(types.contains("\n## test_crate::types::Pair\n"));
+    assert!(!types.contains("\n## Pair\n"));
+    let deep = &output.files["nested/inner/deep.md"];
+    assert!(deep.contains("\n## test_crate::nested::inner::deep::"));
+
+    // With private items, each heading starts with the item's visibility
+    let private_options = MarkdownOptions {
+        include_private: true,
+        ..Default::default()
+    };
+    let private = converter::convert_to_markdown_multifile(&crate_data, &private_options)
+        .expect("Failed to convert to markdown");
+    assert!(private.files["types.md"].contains("\n## pub test_crate::types::Pair\n"));
+    assert!(private.files["nested.md"].contains("\n## Module: inner\n"));
+
+    // In-file links from the contents listing land on those headings
+    for (file, content) in output.files.iter().chain(&private.files) {
+        let anchors = converter::heading_anchors(content);
+        for (line, dest) in converter::markdown_links(content) {
+            if let Some(anchor) = dest.strip_prefix('#') {
+                assert!(
+                    anchors.contains(anchor),
+                    "{}:{}: no heading for #{}",
+                    file,
+                    line,
+                    anchor
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn test_collapsible_impls() {
+    let json_path = Path::new("tests/fixtures/test_crate.json");
+    let crate_data = parser::load_rustdoc_json(json_path).expect("Failed to load JSON");
+    let options = MarkdownOptions {
+        collapsible: true,
+        ..Default::default()
+    };
+    let output = converter::convert_to_markdown_multifile(&crate_data, &options)
+        .expect("Failed to convert to markdown");
+
+    let pair = item_section(&output.files["types.md"], "## test_crate::types::Pair\n");
+    // Summaries show the impl signature, escaped for HTML
+    assert!(pair.contains(
+        "<details>\n<summary><code>impl&lt;T, U&gt; Pair&lt;T, U&gt;</code></summary>\n\n**Associated Functions:**\n"
+    ));
+    assert!(pair.contains(
+        "<summary><code>impl&lt;T, U&gt; From&lt;(T, U)&gt; for Pair&lt;T, U&gt;</code></summary>"
+    ));
+    assert_eq!(
+        pair.matches("<details>").count(),
+        pair.matches("</details>").count()
+    );
+    insta::assert_snapshot!("collapsible_impls", pair);
+
+    // Plain markdown stays the default
+    let plain = converter::convert_to_markdown_multifile(&crate_data, &MarkdownOptions::default())
+        .expect("Failed to convert to markdown");
+    let pair = item_section(&plain.files["types.md"], "## test_crate::types::Pair\n");
+    assert!(!pair.contains("<summary><code>"));
+}
+
+#[test]
+fn test_github_alerts_pass_through() {
+    let json_path = Path::new("tests/fixtures/test_crate.json");
+    let crate_data = parser::load_rustdoc_json(json_path).expect("Failed to load JSON");
+    let output = converter::convert_to_markdown_multifile(&crate_data, &MarkdownOptions::default())
+        .expect("Failed to convert to markdown");
+
+    let remove_file = item_section(
+        &output.files["functions.md"],
+        "## test_crate::functions::remove_file\n",
+    );
+    // The alert markers aren't taken for shortcut links, and the quotes aren't reflowed
+    assert!(remove_file.contains(
+        "> [!WARNING]\n> The file is removed immediately, without going through the trash.\n\n"
+    ));
+    assert!(remove_file.contains("> [!NOTE]\n> Missing files are not an error; see [`checksum`]"));
+    insta::assert_snapshot!("github_alerts", remove_file);
+}
+
+#[test]
+fn test_negative_impls() {
+    let json_path = Path::new("tests/fixtures/test_crate.json");
+    let crate_data = parser::load_rustdoc_json(json_path).expect("Failed to load JSON");
+    let output = converter::convert_to_markdown_multifile(&crate_data, &MarkdownOptions::default())
+        .expect("Failed to convert to markdown");
+
+    let types = &output.files["types.md"];
+    let thread_bound = item_section(types, "## test_crate::types::ThreadBound\n");
+    assert!(thread_bound.contains(
+        "**Negative Implementations:**\n\n- `impl !Send for ThreadBound`\n- `impl !Sync for ThreadBound`\n"
+    ));
+    // Not listed as if the traits were implemented
+    assert!(!thread_bound.contains("**Traits:**"));
+    assert!(!thread_bound.contains("`impl Send for ThreadBound`"));
+    insta::assert_snapshot!("negative_impls", thread_bound);
+
+    // Auto traits a type doesn't get are negative too
+    let raw_buffer = item_section(
+        &output.files["traits.md"],
+        "## test_crate::traits::RawBuffer\n",
+    );
+    assert!(raw_buffer.contains("- `impl !Sync for RawBuffer`"));
+}
+
+#[test]
+fn test_source_item_order() {
+    let json_path = Path::new("tests/fixtures/test_crate.json");
+    let crate_data = parser::load_rustdoc_json(json_path).expect("Failed to load JSON");
+    let options = MarkdownOptions {
+        order: ItemOrder::Source,
+        ..Default::default()
+    };
+    let output = converter::convert_to_markdown_multifile(&crate_data, &options)
+        .expect("Failed to convert to markdown");
+
+    let traits = &output.files["traits.md"];
+    let position = |heading: &str| {
+        traits
+            .find(&format!("## test_crate::traits::{}\n", heading))
+            .unwrap_or_else(|| panic!("{} should be documented", heading))
+    };
+    // Declaration order, not alphabetical: Iterator comes first in traits.rs
+    assert!(position("Iterator") < position("Display"));
+    assert!(position("Display") < position("Converter"));
+    assert!(position("Associated") < position("Lending"));
+    let contents = &traits[..traits.find("\n---\n").expect("contents listing")];
+    assert!(contents.find("[`Iterator`]") < contents.find("[`Display`]"));
+    insta::assert_snapshot!("source_item_order", contents);
+}
+
+#[test]
+fn test_generic_associated_types() {
+    let json_path = Path::new("tests/fixtures/test_crate.json");
+    let crate_data = parser::load_rustdoc_json(json_path).expect("Failed to load JSON");
+    let output = converter::convert_to_markdown_multifile(&crate_data, &MarkdownOptions::default())
+        .expect("Failed to convert to markdown");
+
+    let lending = item_section(
+        &output.files["traits.md"],
+        "## test_crate::traits::Lending\n",
+    );
+    assert!(lending.contains("- `type Item<'a>: fmt::Debug where Self: 'a;`"));
+    assert!(lending.contains("- `type Len: Copy + Into<usize> where Self: Sized;`"));
+    insta::assert_snapshot!("generic_associated_types", lending);
+}
+
+#[test]
+fn test_trait_object_bounds() {
+    let json_path = Path::new("tests/fixtures/test_crate.json");
+    let crate_data = parser::load_rustdoc_json(json_path).expect("Failed to load JSON");
+    let output = converter::convert_to_markdown_multifile(&crate_data, &MarkdownOptions::default())
+        .expect("Failed to convert to markdown");
+
+    let functions = &output.files["functions.md"];
+    let boxed = item_section(functions, "## test_crate::functions::boxed_display\n");
+    assert!(
+        boxed.contains(
+            "fn boxed_display(value: u32) -> Box<dyn std::fmt::Display + Send + 'static>"
+        )
+    );
+    // A reference to a multi-bound trait object needs parentheses to parse
+    let borrowed = item_section(functions, "## test_crate::functions::display_to_string\n");
+    assert!(
+        borrowed.contains("fn display_to_string(value: &(dyn std::fmt::Display + Sync)) -> String")
+    );
+    insta::assert_snapshot!("trait_object_bounds", boxed);
+}
+
+#[test]
+fn test_crate_docs_open_the_index() {
+    let json_path = Path::new("tests/fixtures/test_crate.json");
+    let crate_data = parser::load_rustdoc_json(json_path).expect("Failed to load JSON");
+    let output = converter::convert_to_markdown_multifile(&crate_data, &MarkdownOptions::default())
+        .expect("Failed to convert to markdown");
+
+    let index = &output.files["index.md"];
+    assert!(index.starts_with("# test_crate\n\n# Test Crate\n\nA comprehensive test crate"));
+    // Every paragraph comes before the module listing, with intra-doc links resolved
+    let overview = &index[..index.find("## Modules\n").expect("module listing")];
+    assert!(overview.contains("This crate serves as a test fixture"));
+    assert!(overview.contains("## Safety\n\nThis crate contains `unsafe` code"));
+    assert!(overview.contains(
+        "Start with [`PlainStruct`](test_crate.md#test_crateplainstruct) and the \
+         [`Builder`](patterns.md#test_cratepatternsbuilder), or see \
+         [`types::Container`](types.md#test_cratetypescontainer)"
+    ));
+}
+
+#[test]
+fn test_doc_model() {
+    let json = std::fs::read_to_string("tests/fixtures/test_crate.json").unwrap();
+    let model = cargo_doc_md::parse_json_str(&json).expect("Failed to parse JSON");
+    assert_eq!(model.name(), "test_crate");
+    assert!(model.docs().unwrap().starts_with("# Test Crate"));
+
+    let modules: Vec<&str> = model.modules().map(|module| module.path()).collect();
+    assert_eq!(modules[0], "test_crate");
+    assert!(modules.contains(&"test_crate::nested::inner::deep"));
+    let types = model.module("test_crate::types").expect("types module");
+    assert!(
+        types
+            .item()
+            .unwrap()
+            .docs()
+            .unwrap()
+            .starts_with("Type definitions")
+    );
+    let container = types
+        .items()
+        .find(|item| item.name() == "Container")
+        .expect("Container");
+    assert_eq!(container.kind(), "struct");
+    assert_eq!(container.path(), "test_crate::types::Container");
+    assert_eq!(
+        container.summary().as_deref(),
+        Some("A generic container for items of type `T`.")
+    );
+
+    let mut async_functions: Vec<(String, Option<String>)> = model
+        .items()
+        .filter(|item| item.is_public() && item.is_async())
+        .map(|item| (item.path(), item.signature()))
+        .collect();
+    async_functions.sort();
+    assert!(!async_functions.is_empty());
+    assert!(async_functions.iter().all(|(_, signature)| {
+        signature
+            .as_deref()
+            .is_some_and(|signature| signature.contains("async fn "))
+    }));
+
+    // Rendering the model is converting the crate
+    let crate_data = parser::parse_rustdoc_json(&json).unwrap();
+    let expected =
+        converter::convert_to_markdown_multifile(&crate_data, &MarkdownOptions::default()).unwrap();
+    let rendered = model.render(&MarkdownOptions::default()).unwrap();
+    assert_eq!(rendered.files, expected.files);
+}
+
+#[test]
+fn test_hidden_example_lines() {
+    let json_path = Path::new("tests/fixtures/test_crate.json");
+    let crate_data = parser::load_rustdoc_json(json_path).expect("Failed to load JSON");
+    let output = converter::convert_to_markdown_multifile(&crate_data, &MarkdownOptions::default())
+        .expect("Failed to convert to markdown");
+
+    let parse_pair = item_section(
+        &output.files["functions.md"],
+        "## test_crate::functions::parse_pair\n",
+    );
+    assert!(parse_pair.contains(
+        "```\nlet (key, value) = parse_pair(\"name=ferris\")?;\n#[derive(Debug)]\nstruct Entry"
+    ));
+    assert!(!parse_pair.contains("# fn main()"));
+    assert!(!parse_pair.contains("Some(())"));
+    // Other languages keep their `#` lines
+    assert!(parse_pair.contains("```sh\n# prints the pair\n"));
+    insta::assert_snapshot!("hidden_example_lines", parse_pair);
+
+    let options = MarkdownOptions {
+        keep_hidden_lines: true,
+        ..Default::default()
+    };
+    let output = converter::convert_to_markdown_multifile(&crate_data, &options)
+        .expect("Failed to convert to markdown");
+    let parse_pair = item_section(
+        &output.files["functions.md"],
+        "## test_crate::functions::parse_pair\n",
+    );
+    assert!(parse_pair.contains("# use test_crate::functions::parse_pair;\n# fn main()"));
+    assert!(parse_pair.contains("\n##[derive(Debug)]\n"));
+    insta::assert_snapshot!("hidden_example_lines_kept", parse_pair);
+}
+
+#[test]
+fn test_constant_values() {
+    let json_path = Path::new("tests/fixtures/test_crate.json");
+    let crate_data = parser::load_rustdoc_json(json_path).expect("Failed to load JSON");
+    let output = converter::convert_to_markdown_multifile(&crate_data, &MarkdownOptions::default())
+        .expect("Failed to convert to markdown");
+
+    let max_size = item_section(&output.files["test_crate.md"], "## test_crate::MAX_SIZE\n");
+    assert!(max_size.contains("```rust\nconst MAX_SIZE: usize = 100;\n```"));
+    let version = item_section(&output.files["test_crate.md"], "## test_crate::VERSION\n");
+    assert!(version.contains("const VERSION: &str = \"0.1.0\";"));
+
+    let types = &output.files["types.md"];
+    let default_capacity = item_section(types, "## test_crate::types::DEFAULT_CAPACITY\n");
+    assert!(default_capacity.contains("const DEFAULT_CAPACITY: usize = 10;"));
+    // Computed initializers aren't shown
+    let double_capacity = item_section(types, "## test_crate::types::DOUBLE_CAPACITY\n");
+    assert!(double_capacity.contains("const DOUBLE_CAPACITY: usize = _;"));
+
+    insta::assert_snapshot!(
+        "constant_values",
+        [max_size, default_capacity, double_capacity].join("---\n\n")
+    );
+}
+
+#[test]
+fn test_extract_sections() {
+    let json_path = Path::new("tests/fixtures/test_crate.json");
+    let crate_data = parser::load_rustdoc_json(json_path).expect("Failed to load JSON");
+    let options = MarkdownOptions {
+        extract_sections: true,
+        ..Default::default()
+    };
+    let output = converter::convert_to_markdown_multifile(&crate_data, &options)
+        .expect("Failed to convert to markdown");
+
+    let read_config = item_section(
+        &output.files["functions.md"],
+        "## test_crate::functions::read_config\n",
+    );
+    let signature_end = read_config
+        .find("-> std::io::Result<String>\n```\n\n")
+        .expect("signature not found");
+    let notes = &read_config[signature_end..];
+    // One paragraph becomes one line; lists stay lists
+    assert!(notes.contains(
+        "**Returns:** The file's contents, with line endings normalized to `\\n`.\n\n**Errors:**\n\n- "
+    ));
+    assert!(notes.contains("**Panics:** Panics if `path` is empty.\n\n"));
+    assert!(!notes.contains("Examples"));
+    // The sections stay in the docs too
+    assert!(read_config[..signature_end].contains("# Errors"));
+    insta::assert_snapshot!("extract_sections", read_config);
+
+    let plain = converter::convert_to_markdown_multifile(&crate_data, &MarkdownOptions::default())
+        .expect("Failed to convert to markdown");
+    assert!(!plain.files["functions.md"].contains("**Returns:**"));
+}
+
+#[test]
+fn test_generic_trait_impl_headers() {
+    let json_path = Path::new("tests/fixtures/test_crate.json");
+    let crate_data = parser::load_rustdoc_json(json_path).expect("Failed to load JSON");
+    let output = converter::convert_to_markdown_multifile(&crate_data, &MarkdownOptions::default())
+        .expect("Failed to convert to markdown");
+
+    let tagged = item_section(&output.files["types.md"], "## test_crate::types::Tagged\n");
+    // The trait's arguments are kept even when the impl has no bounds
+    assert!(tagged.contains("- **From** (`impl<T> From<T> for Tagged<T>`)\n"));
+    assert!(tagged.contains(
+        "- **PartialEq** (`impl<T, U> PartialEq<Tagged<U>> for Tagged<T> where T: PartialEq<U>`)\n"
+    ));
+    assert!(tagged.contains("- **Display** (`impl<T: fmt::Display> Display for Tagged<T>`)\n"));
+    insta::assert_snapshot!("generic_trait_impl_headers", tagged);
+
+    let pair = item_section(&output.files["types.md"], "## test_crate::types::Pair\n");
+    assert!(pair.contains("- **From** (`impl<T, U> From<(T, U)> for Pair<T, U>`)\n"));
+}
+
+#[test]
+fn test_impls_on_references_and_arrays() {
+    let json_path = Path::new("tests/fixtures/test_crate.json");
+    let crate_data = parser::load_rustdoc_json(json_path).expect("Failed to load JSON");
+    let output = converter::convert_to_markdown_multifile(&crate_data, &MarkdownOptions::default())
+        .expect("Failed to convert to markdown");
+
+    let plain = item_section(
+        &output.files["test_crate.md"],
+        "## test_crate::PlainStruct\n",
+    );
+    // Each impl keeps its header, so they aren't mistaken for the struct's own
+    assert!(plain.contains("- **MyTrait** (`impl MyTrait for &PlainStruct`)\n"));
+    assert!(plain.contains("- **MyTrait** (`impl MyTrait for [PlainStruct; 2]`)\n"));
+    assert!(plain.contains("- **MyTrait**\n"));
+}
+
+#[test]
+fn test_unsupported_format_version() {
+    let json = std::fs::read_to_string("tests/fixtures/test_crate.json").unwrap();
+    let version = format!(
+        "\"format_version\":{}",
+        parser::SUPPORTED_FORMAT_VERSIONS.end()
+    );
+    assert!(json.contains(&version));
+
+    // A newer format that happens to parse is still refused
+    let newer = json.replace(&version, "\"format_version\":9999");
+    let err = parser::parse_rustdoc_json(&newer).unwrap_err().to_string();
+    assert!(
+        err.starts_with("rustdoc JSON format version 9999 is not supported (expected "),
+        "{}",
+        err
+    );
+    assert!(err.ends_with("update cargo-doc-md or your nightly toolchain"));
+
+    // An old format fails on its version, not on the first field that changed
+    let err = parser::parse_rustdoc_json(r#"{"root": 0, "format_version": 1}"#).unwrap_err();
+    assert!(
+        err.to_string()
+            .starts_with("rustdoc JSON format version 1 is not supported"),
+        "{}",
+        err
+    );
+
+    let err = parser::parse_rustdoc_json(r#"{"root": 0}"#).unwrap_err();
+    assert_eq!(err.to_string(), "Invalid rustdoc JSON");
+
+    // The string entry point checks the version too
+    let err = cargo_doc_md::convert_json_string(&newer, false).unwrap_err();
+    assert!(
+        err.to_string()
+            .starts_with("rustdoc JSON format version 9999")
+    );
+}
+
+#[test]
+fn test_enum_variant_fields() {
+    let json_path = Path::new("tests/fixtures/test_crate.json");
+    let crate_data = parser::load_rustdoc_json(json_path).expect("Failed to load JSON");
+    let output = converter::convert_to_markdown_multifile(&crate_data, &MarkdownOptions::default())
+        .expect("Failed to convert to markdown");
+
+    let complex = item_section(
+        &output.files["test_crate.md"],
+        "## test_crate::ComplexEnum\n",
+    );
+    assert!(complex.contains("- `Unit`\n"));
+    assert!(complex.contains(
+        "- `Tuple` - A name and a count, by position.\n  - **.0**: `String`\n  - **.1**: `i32`\n"
+    ));
+    assert!(complex.contains(
+        "- `Struct` - A person, by name and age.\n  - **name**: `String` - Full name, as written.\n  - **age**: `u32`\n"
+    ));
+
+    let status = item_section(&output.files["types.md"], "## test_crate::types::Status\n");
+    assert!(status.contains("- `Idle` - The operation is idle and waiting to start.\n"));
+    assert!(status.contains(
+        "- `Failed` - The operation failed with an error message.\n  - **error**: `String`\n"
+    ));
+
+    insta::assert_snapshot!("enum_variant_fields", format!("{}\n{}", complex, status));
+}